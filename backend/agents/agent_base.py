@@ -1,28 +1,57 @@
 # backend/agents/agent_base.py
 import asyncio
 import logging
+import os
+import shutil
 import uuid
 from datetime import datetime, timezone
+from pathlib import Path
 
 import grpc
 from google.protobuf.timestamp_pb2 import Timestamp
 from backend.protos import agent_manager_pb2 as mgr_pb2
 from backend.protos import agent_manager_pb2_grpc as mgr_grpc
 from backend.protos import common_pb2 as common_pb
+from backend.auth import auth_metadata
 
 logger = logging.getLogger("AgentBase")
 
+# socket the desktop shell's AgentManager listens on when it uses Unix sockets
+# (see frontend/src-tauri/src/transport.rs)
+MANAGER_SOCKET = os.path.join(os.environ.get("VIDEO_ANALYZER_DATA_DIR", "data"), "run", "manager.sock")
+
+
+def _default_manager_addr():
+    if os.path.exists(MANAGER_SOCKET):
+        return f"unix:{os.path.abspath(MANAGER_SOCKET)}"
+    return "localhost:50052"
+
+
+# AgentManager hosted by the desktop shell; override to point elsewhere
+MANAGER_ADDR = os.environ.get("AGENT_MANAGER_ADDR") or _default_manager_addr()
+
 
 class AgentBase:
-    def __init__(self, name: str, a_type: common_pb.AgentType, server_addr="localhost:50051"):
+    def __init__(self, name: str, a_type: common_pb.AgentType, server_addr=MANAGER_ADDR):
         self.name = name
         self.type = a_type
         self.server_addr = server_addr
         self.id = str(uuid.uuid4())
         self._channel = grpc.aio.insecure_channel(self.server_addr)
         self._stub = mgr_grpc.AgentManagerStub(self._channel)
+        # the shell's AgentManager rejects calls without its per-launch secret
+        self._metadata = auth_metadata()
         self._task = None
         self._stop = False
+        # task ids received and not yet reported on, so redelivered run-task
+        # commands are ignored
+        self._seen_tasks = set()
+        # task ids to acknowledge on the next heartbeat
+        self._pending_acks = []
+        # task id -> asyncio task running it, so cancel-task can stop it
+        self._running = {}
+        # task id -> files/dirs written so far, removed if the task is cancelled
+        self._artifacts = {}
 
     async def register(self):
         info = common_pb.AgentInfo(
@@ -32,7 +61,7 @@ class AgentBase:
             version="0.1",
             capabilities="",
         )
-        resp = await self._stub.RegisterAgent(info)
+        resp = await self._stub.RegisterAgent(info, metadata=self._metadata)
         logger.info(f"Registered with server: {resp.assigned_id} ok={resp.ok}")
 
     async def heartbeat_loop(self):
@@ -54,18 +83,81 @@ class AgentBase:
 
         # Start bidi stream
         try:
-            async for resp in self._stub.Heartbeat(outgoing()):
+            async for resp in self._stub.Heartbeat(outgoing(), metadata=self._metadata):
                 # inspect commands
                 if resp.commands:
                     for cmd in resp.commands:
-                        # process commands (we expect "run-task:taskid:input_uri")
-                        await self.handle_command(cmd)
+                        # process commands (we expect "run-task:taskid:input_uri"
+                        # and "cancel-task:taskid")
+                        if cmd.startswith("run-task:"):
+                            task_id = cmd.split(":", 2)[1]
+                            if task_id in self._seen_tasks:
+                                # the manager resent it before seeing our ack
+                                continue
+                            self._seen_tasks.add(task_id)
+                            self._pending_acks.append(task_id)
+                            # run in the background so later commands (cancel) still arrive
+                            self._running[task_id] = asyncio.create_task(self._run_task(task_id, cmd))
+                        elif cmd.startswith("cancel-task:"):
+                            await self.cancel_task(cmd.split(":", 1)[1])
+                        else:
+                            await self.handle_command(cmd)
         except grpc.RpcError as e:
             logger.info(f"Heartbeat RPC ended: {e}")
 
     def _capabilities_for_heartbeat(self):
-        # override to include progress tokens if needed
-        return getattr(self, "_cap_token", "")
+        # ack tokens first, then whatever progress token the subclass set
+        tokens = [f"ack:{task_id}" for task_id in self._pending_acks]
+        self._pending_acks.clear()
+        cap = getattr(self, "_cap_token", "")
+        if cap:
+            tokens.append(cap)
+        if cap.startswith("result:"):
+            # report a result once, and accept the task again if the manager
+            # retries it
+            self._cap_token = ""
+            self._seen_tasks.discard(cap.split(":", 2)[1])
+        return ";".join(tokens)
+
+    async def _run_task(self, task_id: str, cmd: str):
+        try:
+            await self.handle_command(cmd)
+        except asyncio.CancelledError:
+            logger.info(f"Task {task_id} cancelled")
+            self._cleanup_artifacts(task_id)
+        except Exception as e:
+            logger.exception(f"Task {task_id} failed")
+            self._cap_token = f"result:{task_id}:ERROR_{type(e).__name__}"
+        finally:
+            self._running.pop(task_id, None)
+            # finished tasks keep their output
+            self._artifacts.pop(task_id, None)
+
+    async def cancel_task(self, task_id: str):
+        running = self._running.get(task_id)
+        if running:
+            running.cancel()
+            await asyncio.gather(running, return_exceptions=True)
+        else:
+            self._cleanup_artifacts(task_id)
+        # stop reporting progress for it
+        if getattr(self, "_cap_token", "").split(":")[1:2] == [task_id]:
+            self._cap_token = ""
+
+    def track_artifact(self, task_id: str, path):
+        """Record output about to be written for task_id; it is deleted if the task is cancelled."""
+        self._artifacts.setdefault(task_id, []).append(Path(path))
+
+    def _cleanup_artifacts(self, task_id: str):
+        for path in self._artifacts.pop(task_id, []):
+            try:
+                if path.is_dir():
+                    shutil.rmtree(path)
+                else:
+                    path.unlink(missing_ok=True)
+                logger.info(f"Removed partial output {path}")
+            except OSError as e:
+                logger.warning(f"Could not remove {path}: {e}")
 
     async def handle_command(self, cmd: str):
         # override in subclass
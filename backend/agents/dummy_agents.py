@@ -6,14 +6,14 @@ from datetime import datetime, timezone
 
 from backend.protos import common_pb2 as common_pb
 
-from backend.agents.agent_base import AgentBase
+from backend.agents.agent_base import AgentBase, MANAGER_ADDR
 
 logger = logging.getLogger("DummyAgent")
 logging.basicConfig(level=logging.INFO)
 
 
 class DummyAgent(AgentBase):
-    def __init__(self, name="dummy-transcriber", server_addr="localhost:50051"):
+    def __init__(self, name="dummy-transcriber", server_addr=MANAGER_ADDR):
         super().__init__(name=name, a_type=common_pb.AgentType.AGENT_TRANSCRIPTION, server_addr=server_addr)
         # internal state for which task we're executing
         self._current_task = None
@@ -41,6 +41,7 @@ class DummyAgent(AgentBase):
             await asyncio.sleep(1.0)  # wait 1 sec between updates
         # write a fake output file
         out_path = os.path.abspath(f"./fake_output_{task_id}.txt")
+        self.track_artifact(task_id, out_path)
         with open(out_path, "w") as f:
             f.write(f"Fake output for {task_id}\ninput: {input_uri}\n")
         # report result token
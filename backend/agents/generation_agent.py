@@ -2,7 +2,7 @@
 import asyncio
 import logging
 from pathlib import Path
-from backend.agents.agent_base import AgentBase
+from backend.agents.agent_base import AgentBase, MANAGER_ADDR
 from backend.protos import common_pb2 as common_pb
 from ..utils.generation_utils import generate_pdf, generate_pptx
 
@@ -10,7 +10,7 @@ logger = logging.getLogger("GenerationAgent")
 logging.basicConfig(level=logging.INFO)
 
 class GenerationAgent(AgentBase):
-    def __init__(self, name="generation-agent", server_addr="localhost:50051"):
+    def __init__(self, name="generation-agent", server_addr=MANAGER_ADDR):
         super().__init__(name=name, a_type=common_pb.AgentType.AGENT_GENERATION, server_addr=server_addr)
         self._cap_token = ""
 
@@ -49,11 +49,13 @@ class GenerationAgent(AgentBase):
         # PDF
         self._cap_token = f"progress:{task_id}:10"
         pdf_out = video_dir / f"{task_id}_summary.pdf"
+        self.track_artifact(task_id, pdf_out)
         generate_pdf(str(video_dir), str(transcript_path), str(pdf_out))
         self._cap_token = f"progress:{task_id}:60"
 
         # PPTX
         ppt_out = video_dir / f"{task_id}_slides.pptx"
+        self.track_artifact(task_id, ppt_out)
         generate_pptx(str(video_dir), str(transcript_path), str(ppt_out))
         self._cap_token = f"progress:{task_id}:95"
 
@@ -7,7 +7,7 @@ from pathlib import Path
 from datetime import datetime
 
 from ..utils.transcribe import transcribe_audio
-from backend.agents.agent_base import AgentBase  # from your Step 3
+from backend.agents.agent_base import AgentBase, MANAGER_ADDR  # from your Step 3
 from backend.protos import common_pb2 as common_pb
 
 logger = logging.getLogger("TranscriptionAgent")
@@ -15,7 +15,7 @@ logging.basicConfig(level=logging.INFO)
 
 
 class TranscriptionAgent(AgentBase):
-    def __init__(self, name="transcription-agent", server_addr="localhost:50051",
+    def __init__(self, name="transcription-agent", server_addr=MANAGER_ADDR,
                  model_size="small", device="cpu", confidence_threshold=0.6):
         super().__init__(name=name, a_type=common_pb.AgentType.AGENT_TRANSCRIPTION, server_addr=server_addr)
         self.model_size = model_size
@@ -55,6 +55,7 @@ class TranscriptionAgent(AgentBase):
 
         # write transcript file
         out_path = base_dir / f"transcript_{task_id}.json"
+        self.track_artifact(task_id, out_path)
         with open(out_path, "w", encoding="utf-8") as f:
             json.dump({
                 "task_id": task_id,
@@ -66,6 +67,7 @@ class TranscriptionAgent(AgentBase):
 
         if low_conf:
             clar_path = base_dir / f"clarify_requests_{task_id}.json"
+            self.track_artifact(task_id, clar_path)
             with open(clar_path, "w", encoding="utf-8") as f:
                 json.dump(low_conf, f, indent=2, ensure_ascii=False)
 
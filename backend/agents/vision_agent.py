@@ -3,7 +3,7 @@ import asyncio
 import logging
 from pathlib import Path
 
-from backend.agents.agent_base import AgentBase
+from backend.agents.agent_base import AgentBase, MANAGER_ADDR
 from backend.protos import common_pb2 as common_pb
 from ..utils.vision_utils import process_video_frames
 
@@ -12,7 +12,7 @@ logging.basicConfig(level=logging.INFO)
 
 
 class VisionAgent(AgentBase):
-    def __init__(self, name="vision-agent", server_addr="localhost:50051",
+    def __init__(self, name="vision-agent", server_addr=MANAGER_ADDR,
                  model_path="backend/models/yolov8n.onnx"):
         super().__init__(name=name, a_type=common_pb.AgentType.AGENT_VISION, server_addr=server_addr)
         self.model_path = model_path
@@ -37,9 +37,13 @@ class VisionAgent(AgentBase):
         self._cap_token = f"progress:{task_id}:10"
         await asyncio.sleep(0.5)
 
+        graphs_dir = out_dir / "graphs"
+        if not graphs_dir.exists():
+            self.track_artifact(task_id, graphs_dir)
+
         loop = asyncio.get_running_loop()
         frame_count = await loop.run_in_executor(None,
-            lambda: process_video_frames(str(frames_dir), self.model_path, str(out_dir / "graphs")))
+            lambda: process_video_frames(str(frames_dir), self.model_path, str(graphs_dir)))
 
         self._cap_token = f"result:{task_id}:{str(out_dir)}"
         logger.info(f"Vision analysis done for {frame_count} frames at {out_dir}")
@@ -1,17 +1,25 @@
 # backend/attachments_store.py
+import hashlib
 import os
 import uuid
 from pathlib import Path
 
-ATTACH_DIR = Path("data/attachments")
+# Same layout as the shell's store (frontend/src-tauri/src/history/attachments.rs):
+# files are named after the SHA-256 of their content, so each is kept once.
+DATA_DIR = Path(os.environ.get("VIDEO_ANALYZER_DATA_DIR", "data"))
+ATTACH_DIR = DATA_DIR / "attachments"
 ATTACH_DIR.mkdir(parents=True, exist_ok=True)
 
 def save_attachment_bytes(filename: str, content_bytes: bytes) -> str:
-    # create unique name to avoid collisions
-    ext = Path(filename).suffix or ".bin"
-    uid = uuid.uuid4().hex
-    target = ATTACH_DIR / f"{uid}{ext}"
-    with open(target, "wb") as f:
-        f.write(content_bytes)
-    # return POSIX path string
-    return str(target)
+    digest = hashlib.sha256(content_bytes).hexdigest()
+    relative = f"attachments/{digest[:2]}/{digest}"
+    target = DATA_DIR / relative
+    if not target.exists():
+        target.parent.mkdir(parents=True, exist_ok=True)
+        # write aside first so a partial file never carries the digest's name
+        temp = ATTACH_DIR / f".{uuid.uuid4().hex}.part"
+        with open(temp, "wb") as f:
+            f.write(content_bytes)
+        os.replace(temp, target)
+    # path relative to data/, as stored in attachments.path
+    return relative
@@ -0,0 +1,57 @@
+# backend/auth.py
+"""Shared-secret authentication with the desktop shell.
+
+The shell generates a secret per launch, passes it to the backend it spawns
+in VIDEO_ANALYZER_AUTH_TOKEN and writes it to data/auth_token for agents
+started separately. Calls carry it as `authorization: Bearer <secret>`.
+"""
+import hmac
+import logging
+import os
+
+import grpc
+
+logger = logging.getLogger("auth")
+
+TOKEN_ENV = "VIDEO_ANALYZER_AUTH_TOKEN"
+TOKEN_FILE = os.path.join(os.environ.get("VIDEO_ANALYZER_DATA_DIR", "data"), "auth_token")
+HEADER = "authorization"
+
+
+def load_token():
+    """Secret from the environment, else from the file the shell wrote; None if neither."""
+    token = os.environ.get(TOKEN_ENV)
+    if token:
+        return token
+    try:
+        with open(TOKEN_FILE, "r", encoding="utf-8") as f:
+            return f.read().strip() or None
+    except OSError:
+        return None
+
+
+def auth_metadata(token=None):
+    """Call metadata carrying the secret, for `metadata=` on stub calls."""
+    token = token or load_token()
+    if not token:
+        logger.warning("no auth token found; calls to the desktop shell will be rejected")
+        return ()
+    return ((HEADER, f"Bearer {token}"),)
+
+
+class TokenAuthInterceptor(grpc.ServerInterceptor):
+    """Rejects calls that do not carry the secret."""
+
+    def __init__(self, token):
+        self._expected = f"Bearer {token}"
+
+        def deny(request, context):
+            context.abort(grpc.StatusCode.UNAUTHENTICATED, "missing or invalid token")
+
+        self._deny = grpc.unary_unary_rpc_method_handler(deny)
+
+    def intercept_service(self, continuation, handler_call_details):
+        metadata = dict(handler_call_details.invocation_metadata or ())
+        if hmac.compare_digest(metadata.get(HEADER, ""), self._expected):
+            return continuation(handler_call_details)
+        return self._deny
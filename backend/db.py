@@ -1,12 +1,14 @@
 # backend/db.py
 import json
+import os
 import sqlite3
 from typing import List, Optional
 import time
 import uuid
 from dataclasses import dataclass
 
-DB_PATH = "data/chat_history.db"  # commit .gitignore or allow example
+# the desktop shell passes its data directory so both sides share one database
+DB_PATH = os.path.join(os.environ.get("VIDEO_ANALYZER_DATA_DIR", "data"), "chat_history.db")
 
 def _conn():
     conn = sqlite3.connect(DB_PATH, check_same_thread=False)
@@ -61,17 +63,44 @@ def ensure_conversation(conversation_id: str, title: Optional[str] = None):
         conn.commit()
     conn.close()
 
-def store_message(message: dict):
+class UnknownMessage(LookupError):
+    """The message a new one follows or edits is not in its conversation."""
+
+
+class NotEditable(ValueError):
+    """Only the user's messages can be edited."""
+
+
+def _has_branches(cur):
+    # the desktop shell's migrations add parent_id and active_leaf_id; a
+    # database only this backend has opened keeps one linear history
+    cur.execute("PRAGMA table_info(messages)")
+    return any(r["name"] == "parent_id" for r in cur.fetchall())
+
+def _find_message(cur, conversation_id: str, message_id: str):
+    cur.execute("SELECT sender, parent_id FROM messages WHERE id = ? AND conversation_id = ?",
+                (message_id, conversation_id))
+    r = cur.fetchone()
+    if not r:
+        raise UnknownMessage(f"unknown message {message_id}")
+    return r
+
+def store_message(message: dict, edit_of: str = ""):
     """
-    message: dict with keys id, conversation_id, sender, text, created_at, confidence, needs_clarification, attachments (list), metadata_json
+    message: dict with keys id, conversation_id, sender, text, created_at, confidence, needs_clarification, attachments (list), metadata_json,
+    and optionally parent_id, the message it follows; without one it ends the active branch
+    edit_of: optional id of a user message to store this one as a new version of, branching there
+
+    Either way the message becomes the active leaf, and message["parent_id"] is filled in.
+    Pending attachments listed by id are linked to the message.
+    Raises UnknownMessage or NotEditable if parent_id or edit_of cannot be used.
     """
     conn = _conn()
     cur = conn.cursor()
     ensure_conversation(message["conversation_id"])
-    cur.execute("""
-      INSERT INTO messages(id, conversation_id, sender, text, created_at, confidence, needs_clarification, attachments_json, metadata_json)
-      VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-    """, (
+    branches = _has_branches(cur)
+    columns = "id, conversation_id, sender, text, created_at, confidence, needs_clarification, attachments_json, metadata_json"
+    values = [
         message["id"],
         message["conversation_id"],
         message["sender"],
@@ -81,29 +110,85 @@ def store_message(message: dict):
         1 if message.get("needs_clarification") else 0,
         json.dumps(message.get("attachments", [])),
         json.dumps(message.get("metadata_json", {}))
-    ))
-    conn.commit()
-    conn.close()
+    ]
+    try:
+        if branches:
+            cur.execute("BEGIN IMMEDIATE")
+            if edit_of:
+                target = _find_message(cur, message["conversation_id"], edit_of)
+                if target["sender"] != "user":
+                    raise NotEditable(f"message {edit_of} is not from the user")
+                parent_id = target["parent_id"]
+            elif message.get("parent_id"):
+                _find_message(cur, message["conversation_id"], message["parent_id"])
+                parent_id = message["parent_id"]
+            else:
+                cur.execute("SELECT active_leaf_id FROM conversations WHERE id = ?", (message["conversation_id"],))
+                parent_id = cur.fetchone()["active_leaf_id"]
+            if parent_id is None:
+                # the insert trigger attaches a message without a parent to the active leaf
+                cur.execute("UPDATE conversations SET active_leaf_id = NULL WHERE id = ?", (message["conversation_id"],))
+            columns += ", parent_id"
+            values.append(parent_id)
+            message["parent_id"] = parent_id or ""
+        cur.execute(f"INSERT INTO messages({columns}) VALUES ({', '.join('?' * len(values))})", values)
+        # claim the files added through the shell's attachment store, which
+        # deletes ones still pending after a day; other entries (paths, ids
+        # already linked) are left as they are
+        for attachment in message.get("attachments", []):
+            cur.execute("UPDATE attachments SET message_id = ? WHERE id = ? AND message_id IS NULL",
+                        (message["id"], attachment))
+        conn.commit()
+    except Exception:
+        conn.rollback()
+        raise
+    finally:
+        conn.close()
+
+def _row_to_dict(r):
+    keys = r.keys()
+    return {
+        "id": r["id"],
+        "conversation_id": r["conversation_id"],
+        "sender": r["sender"],
+        "text": r["text"],
+        "created_at": r["created_at"],
+        "confidence": r["confidence"],
+        "needs_clarification": bool(r["needs_clarification"]),
+        "attachments": json.loads(r["attachments_json"]),
+        "metadata_json": json.loads(r["metadata_json"]),
+        "parent_id": (r["parent_id"] if "parent_id" in keys else None) or "",
+        "sibling_ids": json.loads(r["sibling_ids"]) if "sibling_ids" in keys else [],
+    }
 
 def get_history(conversation_id: str, limit: int = 100, offset: int = 0):
+    """Messages on the active branch, oldest first, each with the ids of its versions."""
     conn = _conn()
     cur = conn.cursor()
-    cur.execute("""
-      SELECT * FROM messages WHERE conversation_id = ? ORDER BY created_at ASC LIMIT ? OFFSET ?
-    """, (conversation_id, limit, offset))
+    if _has_branches(cur):
+        cur.execute("""
+          WITH RECURSIVE branch(message_id, depth) AS (
+            SELECT active_leaf_id, 0 FROM conversations WHERE id = ?
+            UNION ALL
+            SELECT m.parent_id, branch.depth + 1
+            FROM branch JOIN messages m ON m.id = branch.message_id
+            WHERE m.parent_id IS NOT NULL
+          )
+          SELECT messages.*, (
+            SELECT json_group_array(id) FROM (
+              SELECT s.id FROM messages s
+              WHERE s.conversation_id = messages.conversation_id
+                AND s.parent_id IS messages.parent_id
+              ORDER BY s.created_at ASC, s.rowid ASC
+            )
+          ) AS sibling_ids
+          FROM branch JOIN messages ON messages.id = branch.message_id
+          ORDER BY branch.depth DESC LIMIT ? OFFSET ?
+        """, (conversation_id, limit, offset))
+    else:
+        cur.execute("""
+          SELECT * FROM messages WHERE conversation_id = ? ORDER BY created_at ASC LIMIT ? OFFSET ?
+        """, (conversation_id, limit, offset))
     rows = cur.fetchall()
     conn.close()
-    result = []
-    for r in rows:
-        result.append({
-            "id": r["id"],
-            "conversation_id": r["conversation_id"],
-            "sender": r["sender"],
-            "text": r["text"],
-            "created_at": r["created_at"],
-            "confidence": r["confidence"],
-            "needs_clarification": bool(r["needs_clarification"]),
-            "attachments": json.loads(r["attachments_json"]),
-            "metadata_json": json.loads(r["metadata_json"])
-        })
-    return result
+    return [_row_to_dict(r) for r in rows]
@@ -162,12 +162,40 @@ class MCPServer(mgr_grpc.AgentManagerServicer):
                 listeners.remove(q)
             logger.info(f"Client unsubscribed from progress for task {task_id}")
 
+    # CancelTask(CancelTaskRequest) -> CancelTaskResponse
+    async def CancelTask(self, request, context):
+        task_id = request.task_id
+        async with self._lock:
+            t = self.tasks.get(task_id)
+            if not t:
+                await context.abort(grpc.StatusCode.NOT_FOUND, f"unknown task {task_id}")
+            if t["status"] in (common_pb.TaskStatus.TASK_SUCCEEDED, common_pb.TaskStatus.TASK_FAILED, common_pb.TaskStatus.TASK_CANCELLED):
+                return mgr_pb2.CancelTaskResponse(cancelled=False, message="Task already finished")
+            t["status"] = common_pb.TaskStatus.TASK_CANCELLED
+            t["message"] = request.reason or "Cancelled"
+            # tell the agent to stop and drop partial output
+            if t["agent"] in self.agent_queues:
+                await self.agent_queues[t["agent"]].put(f"cancel-task:{task_id}")
+        update = common_pb.ProgressUpdate(
+            task_id=task_id,
+            status=common_pb.TaskStatus.TASK_CANCELLED,
+            percent=t["percent"],
+            message=t["message"],
+            updated_at=Timestamp()
+        )
+        update.updated_at.GetCurrentTime()
+        listeners = self.progress_listeners.get(task_id, [])
+        for q in listeners:
+            await q.put(update)
+        logger.info(f"Cancelled task {task_id}")
+        return mgr_pb2.CancelTaskResponse(cancelled=True, message="Cancelled")
+
     # Helpers to update tasks and notify listeners
     async def _update_task_progress(self, task_id, percent, message):
         async with self._lock:
             t = self.tasks.get(task_id)
-            if not t:
-                # ignore unknown tasks
+            if not t or t["status"] == common_pb.TaskStatus.TASK_CANCELLED:
+                # ignore unknown and cancelled tasks
                 return
             t["status"] = common_pb.TaskStatus.TASK_RUNNING
             t["percent"] = percent
@@ -188,7 +216,7 @@ class MCPServer(mgr_grpc.AgentManagerServicer):
     async def _complete_task(self, task_id, success: bool, output_uri: str, error_message: str):
         async with self._lock:
             t = self.tasks.get(task_id)
-            if not t:
+            if not t or t["status"] == common_pb.TaskStatus.TASK_CANCELLED:
                 return
             t["status"] = common_pb.TaskStatus.TASK_SUCCEEDED if success else common_pb.TaskStatus.TASK_FAILED
             t["percent"] = 100
@@ -207,7 +235,7 @@ class MCPServer(mgr_grpc.AgentManagerServicer):
             await q.put(update)
 
 
-async def serve_async(host="[::]", port=50051):
+async def serve_async(host="[::]", port=50052):
     server = grpc.aio.server()
     mcp = MCPServer()
     mgr_grpc.add_AgentManagerServicer_to_server(mcp, server)
@@ -61,6 +61,11 @@ class AgentManagerStub(object):
                 request_serializer=agent__manager__pb2.TaskProgressRequest.SerializeToString,
                 response_deserializer=common__pb2.ProgressUpdate.FromString,
                 _registered_method=True)
+        self.CancelTask = channel.unary_unary(
+                '/genai.manager.AgentManager/CancelTask',
+                request_serializer=agent__manager__pb2.CancelTaskRequest.SerializeToString,
+                response_deserializer=agent__manager__pb2.CancelTaskResponse.FromString,
+                _registered_method=True)
 
 
 class AgentManagerServicer(object):
@@ -101,6 +106,13 @@ class AgentManagerServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def CancelTask(self, request, context):
+        """Cancel a queued or running task; its agent is told to stop via Heartbeat
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
 
 def add_AgentManagerServicer_to_server(servicer, server):
     rpc_method_handlers = {
@@ -129,6 +141,11 @@ def add_AgentManagerServicer_to_server(servicer, server):
                     request_deserializer=agent__manager__pb2.TaskProgressRequest.FromString,
                     response_serializer=common__pb2.ProgressUpdate.SerializeToString,
             ),
+            'CancelTask': grpc.unary_unary_rpc_method_handler(
+                    servicer.CancelTask,
+                    request_deserializer=agent__manager__pb2.CancelTaskRequest.FromString,
+                    response_serializer=agent__manager__pb2.CancelTaskResponse.SerializeToString,
+            ),
     }
     generic_handler = grpc.method_handlers_generic_handler(
             'genai.manager.AgentManager', rpc_method_handlers)
@@ -274,3 +291,30 @@ class AgentManager(object):
             timeout,
             metadata,
             _registered_method=True)
+
+    @staticmethod
+    def CancelTask(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/genai.manager.AgentManager/CancelTask',
+            agent__manager__pb2.CancelTaskRequest.SerializeToString,
+            agent__manager__pb2.CancelTaskResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
@@ -25,7 +25,7 @@ _sym_db = _symbol_database.Default()
 from google.protobuf import timestamp_pb2 as google_dot_protobuf_dot_timestamp__pb2
 
 
-DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\nchat.proto\x12\x12videoanalyzer.chat\x1a\x1fgoogle/protobuf/timestamp.proto\"\xbd\x01\n\x07Message\x12\n\n\x02id\x18\x01 \x01(\t\x12\x17\n\x0f\x63onversation_id\x18\x02 \x01(\t\x12\x0e\n\x06sender\x18\x03 \x01(\t\x12\x0c\n\x04text\x18\x04 \x01(\t\x12\x12\n\ncreated_at\x18\x05 \x01(\x03\x12\x12\n\nconfidence\x18\x06 \x01(\x01\x12\x1b\n\x13needs_clarification\x18\x07 \x01(\x08\x12\x13\n\x0b\x61ttachments\x18\x08 \x03(\t\x12\x15\n\rmetadata_json\x18\t \x01(\t\"u\n\x12SendMessageRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\x12,\n\x07message\x18\x02 \x01(\x0b\x32\x1b.videoanalyzer.chat.Message\x12\x18\n\x10stream_responses\x18\x03 \x01(\x08\"J\n\x13SendMessageResponse\x12\x33\n\x0estored_message\x18\x01 \x01(\x0b\x32\x1b.videoanalyzer.chat.Message\"K\n\x11GetHistoryRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\x12\r\n\x05limit\x18\x02 \x01(\x05\x12\x0e\n\x06offset\x18\x03 \x01(\x05\"C\n\x12GetHistoryResponse\x12-\n\x08messages\x18\x01 \x03(\x0b\x32\x1b.videoanalyzer.chat.Message\"q\n\x0eStreamResponse\x12\x16\n\x0cpartial_text\x18\x01 \x01(\tH\x00\x12.\n\x07message\x18\x02 \x01(\x0b\x32\x1b.videoanalyzer.chat.MessageH\x00\x12\x0c\n\x04\x64one\x18\x03 \x01(\x08\x42\t\n\x07payload2\xab\x02\n\x0b\x43hatService\x12^\n\x0bSendMessage\x12&.videoanalyzer.chat.SendMessageRequest\x1a\'.videoanalyzer.chat.SendMessageResponse\x12[\n\nGetHistory\x12%.videoanalyzer.chat.GetHistoryRequest\x1a&.videoanalyzer.chat.GetHistoryResponse\x12_\n\x0fStreamResponses\x12&.videoanalyzer.chat.SendMessageRequest\x1a\".videoanalyzer.chat.StreamResponse0\x01\x62\x06proto3')
+DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\nchat.proto\x12\x12videoanalyzer.chat\x1a\x1fgoogle/protobuf/timestamp.proto\"\xe5\x01\n\x07Message\x12\n\n\x02id\x18\x01 \x01(\t\x12\x17\n\x0f\x63onversation_id\x18\x02 \x01(\t\x12\x0e\n\x06sender\x18\x03 \x01(\t\x12\x0c\n\x04text\x18\x04 \x01(\t\x12\x12\n\ncreated_at\x18\x05 \x01(\x03\x12\x12\n\nconfidence\x18\x06 \x01(\x01\x12\x1b\n\x13needs_clarification\x18\x07 \x01(\x08\x12\x13\n\x0b\x61ttachments\x18\x08 \x03(\t\x12\x15\n\rmetadata_json\x18\t \x01(\t\x12\x11\n\tparent_id\x18\n \x01(\t\x12\x13\n\x0bsibling_ids\x18\x0b \x03(\t\"\x86\x01\n\x12SendMessageRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\x12,\n\x07message\x18\x02 \x01(\x0b\x32\x1b.videoanalyzer.chat.Message\x12\x18\n\x10stream_responses\x18\x03 \x01(\x08\x12\x0f\n\x07\x65\x64it_of\x18\x04 \x01(\t\"J\n\x13SendMessageResponse\x12\x33\n\x0estored_message\x18\x01 \x01(\x0b\x32\x1b.videoanalyzer.chat.Message\"K\n\x11GetHistoryRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\x12\r\n\x05limit\x18\x02 \x01(\x05\x12\x0e\n\x06offset\x18\x03 \x01(\x05\"C\n\x12GetHistoryResponse\x12-\n\x08messages\x18\x01 \x03(\x0b\x32\x1b.videoanalyzer.chat.Message\"q\n\x0eStreamResponse\x12\x16\n\x0cpartial_text\x18\x01 \x01(\tH\x00\x12.\n\x07message\x18\x02 \x01(\x0b\x32\x1b.videoanalyzer.chat.MessageH\x00\x12\x0c\n\x04\x64one\x18\x03 \x01(\x08\x42\t\n\x07payload\"\xcf\x01\n\x0c\x43onversation\x12\n\n\x02id\x18\x01 \x01(\t\x12\r\n\x05title\x18\x02 \x01(\t\x12\x12\n\ncreated_at\x18\x03 \x01(\x03\x12\x12\n\nupdated_at\x18\x04 \x01(\x03\x12\x10\n\x08\x61rchived\x18\x05 \x01(\x08\x12\x15\n\rmessage_count\x18\x06 \x01(\x05\x12\x18\n\x10\x61ttachment_count\x18\x07 \x01(\x05\x12\x1c\n\x14last_message_preview\x18\x08 \x01(\t\x12\x1b\n\x13last_message_sender\x18\t \x01(\t\"S\n\x18ListConversationsRequest\x12\x18\n\x10include_archived\x18\x01 \x01(\x08\x12\r\n\x05limit\x18\x02 \x01(\x05\x12\x0e\n\x06offset\x18\x03 \x01(\x05\"T\n\x19ListConversationsResponse\x12\x37\n\rconversations\x18\x01 \x03(\x0b\x32 .videoanalyzer.chat.Conversation\"C\n\x19RenameConversationRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\x12\r\n\x05title\x18\x02 \x01(\t\"G\n\x1a\x41rchiveConversationRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\x12\x10\n\x08\x61rchived\x18\x02 \x01(\x08\"4\n\x19\x44\x65leteConversationRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\"-\n\x1a\x44\x65leteConversationResponse\x12\x0f\n\x07\x64\x65leted\x18\x01 \x01(\x08\"B\n\x13SwitchBranchRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\x12\x12\n\nmessage_id\x18\x02 \x01(\t\"@\n\x11RegenerateRequest\x12\x17\n\x0f\x63onversation_id\x18\x01 \x01(\t\x12\x12\n\nmessage_id\x18\x02 \x01(\t\"\x88\x01\n\x15SearchMessagesRequest\x12\r\n\x05query\x18\x01 \x01(\t\x12\x0e\n\x06sender\x18\x02 \x01(\t\x12\x0c\n\x04\x66rom\x18\x03 \x01(\x03\x12\n\n\x02to\x18\x04 \x01(\x03\x12\x17\n\x0f\x63onversation_id\x18\x05 \x01(\t\x12\r\n\x05limit\x18\x06 \x01(\x05\x12\x0e\n\x06offset\x18\x07 \x01(\x05\"\x98\x01\n\tSearchHit\x12\x12\n\nmessage_id\x18\x01 \x01(\t\x12\x17\n\x0f\x63onversation_id\x18\x02 \x01(\t\x12\x1a\n\x12\x63onversation_title\x18\x03 \x01(\t\x12\x0e\n\x06sender\x18\x04 \x01(\t\x12\x12\n\ncreated_at\x18\x05 \x01(\x03\x12\x0f\n\x07snippet\x18\x06 \x01(\t\x12\r\n\x05score\x18\x07 \x01(\x01\"E\n\x16SearchMessagesResponse\x12+\n\x04hits\x18\x01 \x03(\x0b\x32\x1d.videoanalyzer.chat.SearchHit2\x87\x08\n\x0b\x43hatService\x12^\n\x0bSendMessage\x12&.videoanalyzer.chat.SendMessageRequest\x1a\'.videoanalyzer.chat.SendMessageResponse\x12[\n\nGetHistory\x12%.videoanalyzer.chat.GetHistoryRequest\x1a&.videoanalyzer.chat.GetHistoryResponse\x12_\n\x0fStreamResponses\x12&.videoanalyzer.chat.SendMessageRequest\x1a\".videoanalyzer.chat.StreamResponse0\x01\x12p\n\x11ListConversations\x12,.videoanalyzer.chat.ListConversationsRequest\x1a-.videoanalyzer.chat.ListConversationsResponse\x12\x65\n\x12RenameConversation\x12-.videoanalyzer.chat.RenameConversationRequest\x1a .videoanalyzer.chat.Conversation\x12g\n\x13\x41rchiveConversation\x12..videoanalyzer.chat.ArchiveConversationRequest\x1a .videoanalyzer.chat.Conversation\x12s\n\x12\x44\x65leteConversation\x12-.videoanalyzer.chat.DeleteConversationRequest\x1a..videoanalyzer.chat.DeleteConversationResponse\x12g\n\x0eSearchMessages\x12).videoanalyzer.chat.SearchMessagesRequest\x1a*.videoanalyzer.chat.SearchMessagesResponse\x12_\n\x0cSwitchBranch\x12\'.videoanalyzer.chat.SwitchBranchRequest\x1a&.videoanalyzer.chat.GetHistoryResponse\x12Y\n\nRegenerate\x12%.videoanalyzer.chat.RegenerateRequest\x1a\".videoanalyzer.chat.StreamResponse0\x01\x62\x06proto3')
 
 _globals = globals()
 _builder.BuildMessageAndEnumDescriptors(DESCRIPTOR, _globals)
@@ -33,17 +33,41 @@ _builder.BuildTopDescriptorsAndMessages(DESCRIPTOR, 'chat_pb2', _globals)
 if not _descriptor._USE_C_DESCRIPTORS:
   DESCRIPTOR._loaded_options = None
   _globals['_MESSAGE']._serialized_start=68
-  _globals['_MESSAGE']._serialized_end=257
-  _globals['_SENDMESSAGEREQUEST']._serialized_start=259
-  _globals['_SENDMESSAGEREQUEST']._serialized_end=376
-  _globals['_SENDMESSAGERESPONSE']._serialized_start=378
-  _globals['_SENDMESSAGERESPONSE']._serialized_end=452
-  _globals['_GETHISTORYREQUEST']._serialized_start=454
-  _globals['_GETHISTORYREQUEST']._serialized_end=529
-  _globals['_GETHISTORYRESPONSE']._serialized_start=531
-  _globals['_GETHISTORYRESPONSE']._serialized_end=598
-  _globals['_STREAMRESPONSE']._serialized_start=600
-  _globals['_STREAMRESPONSE']._serialized_end=713
-  _globals['_CHATSERVICE']._serialized_start=716
-  _globals['_CHATSERVICE']._serialized_end=1015
+  _globals['_MESSAGE']._serialized_end=297
+  _globals['_SENDMESSAGEREQUEST']._serialized_start=300
+  _globals['_SENDMESSAGEREQUEST']._serialized_end=434
+  _globals['_SENDMESSAGERESPONSE']._serialized_start=436
+  _globals['_SENDMESSAGERESPONSE']._serialized_end=510
+  _globals['_GETHISTORYREQUEST']._serialized_start=512
+  _globals['_GETHISTORYREQUEST']._serialized_end=587
+  _globals['_GETHISTORYRESPONSE']._serialized_start=589
+  _globals['_GETHISTORYRESPONSE']._serialized_end=656
+  _globals['_STREAMRESPONSE']._serialized_start=658
+  _globals['_STREAMRESPONSE']._serialized_end=771
+  _globals['_CONVERSATION']._serialized_start=774
+  _globals['_CONVERSATION']._serialized_end=981
+  _globals['_LISTCONVERSATIONSREQUEST']._serialized_start=983
+  _globals['_LISTCONVERSATIONSREQUEST']._serialized_end=1066
+  _globals['_LISTCONVERSATIONSRESPONSE']._serialized_start=1068
+  _globals['_LISTCONVERSATIONSRESPONSE']._serialized_end=1152
+  _globals['_RENAMECONVERSATIONREQUEST']._serialized_start=1154
+  _globals['_RENAMECONVERSATIONREQUEST']._serialized_end=1221
+  _globals['_ARCHIVECONVERSATIONREQUEST']._serialized_start=1223
+  _globals['_ARCHIVECONVERSATIONREQUEST']._serialized_end=1294
+  _globals['_DELETECONVERSATIONREQUEST']._serialized_start=1296
+  _globals['_DELETECONVERSATIONREQUEST']._serialized_end=1348
+  _globals['_DELETECONVERSATIONRESPONSE']._serialized_start=1350
+  _globals['_DELETECONVERSATIONRESPONSE']._serialized_end=1395
+  _globals['_SWITCHBRANCHREQUEST']._serialized_start=1397
+  _globals['_SWITCHBRANCHREQUEST']._serialized_end=1463
+  _globals['_REGENERATEREQUEST']._serialized_start=1465
+  _globals['_REGENERATEREQUEST']._serialized_end=1529
+  _globals['_SEARCHMESSAGESREQUEST']._serialized_start=1532
+  _globals['_SEARCHMESSAGESREQUEST']._serialized_end=1668
+  _globals['_SEARCHHIT']._serialized_start=1671
+  _globals['_SEARCHHIT']._serialized_end=1823
+  _globals['_SEARCHMESSAGESRESPONSE']._serialized_start=1825
+  _globals['_SEARCHMESSAGESRESPONSE']._serialized_end=1894
+  _globals['_CHATSERVICE']._serialized_start=1897
+  _globals['_CHATSERVICE']._serialized_end=2928
 # @@protoc_insertion_point(module_scope)
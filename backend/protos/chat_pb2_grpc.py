@@ -44,11 +44,46 @@ class ChatServiceStub(object):
                 request_serializer=chat__pb2.GetHistoryRequest.SerializeToString,
                 response_deserializer=chat__pb2.GetHistoryResponse.FromString,
                 _registered_method=True)
+        self.Regenerate = channel.unary_stream(
+                '/videoanalyzer.chat.ChatService/Regenerate',
+                request_serializer=chat__pb2.RegenerateRequest.SerializeToString,
+                response_deserializer=chat__pb2.StreamResponse.FromString,
+                _registered_method=True)
         self.StreamResponses = channel.unary_stream(
                 '/videoanalyzer.chat.ChatService/StreamResponses',
                 request_serializer=chat__pb2.SendMessageRequest.SerializeToString,
                 response_deserializer=chat__pb2.StreamResponse.FromString,
                 _registered_method=True)
+        self.ListConversations = channel.unary_unary(
+                '/videoanalyzer.chat.ChatService/ListConversations',
+                request_serializer=chat__pb2.ListConversationsRequest.SerializeToString,
+                response_deserializer=chat__pb2.ListConversationsResponse.FromString,
+                _registered_method=True)
+        self.RenameConversation = channel.unary_unary(
+                '/videoanalyzer.chat.ChatService/RenameConversation',
+                request_serializer=chat__pb2.RenameConversationRequest.SerializeToString,
+                response_deserializer=chat__pb2.Conversation.FromString,
+                _registered_method=True)
+        self.ArchiveConversation = channel.unary_unary(
+                '/videoanalyzer.chat.ChatService/ArchiveConversation',
+                request_serializer=chat__pb2.ArchiveConversationRequest.SerializeToString,
+                response_deserializer=chat__pb2.Conversation.FromString,
+                _registered_method=True)
+        self.DeleteConversation = channel.unary_unary(
+                '/videoanalyzer.chat.ChatService/DeleteConversation',
+                request_serializer=chat__pb2.DeleteConversationRequest.SerializeToString,
+                response_deserializer=chat__pb2.DeleteConversationResponse.FromString,
+                _registered_method=True)
+        self.SearchMessages = channel.unary_unary(
+                '/videoanalyzer.chat.ChatService/SearchMessages',
+                request_serializer=chat__pb2.SearchMessagesRequest.SerializeToString,
+                response_deserializer=chat__pb2.SearchMessagesResponse.FromString,
+                _registered_method=True)
+        self.SwitchBranch = channel.unary_unary(
+                '/videoanalyzer.chat.ChatService/SwitchBranch',
+                request_serializer=chat__pb2.SwitchBranchRequest.SerializeToString,
+                response_deserializer=chat__pb2.GetHistoryResponse.FromString,
+                _registered_method=True)
 
 
 class ChatServiceServicer(object):
@@ -61,7 +96,8 @@ class ChatServiceServicer(object):
         raise NotImplementedError('Method not implemented!')
 
     def GetHistory(self, request, context):
-        """Missing associated documentation comment in .proto file."""
+        """Messages of the active branch, oldest first
+        """
         context.set_code(grpc.StatusCode.UNIMPLEMENTED)
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
@@ -72,6 +108,54 @@ class ChatServiceServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def ListConversations(self, request, context):
+        """Conversations with message counts and a preview of the last message
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def RenameConversation(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def ArchiveConversation(self, request, context):
+        """Hide a conversation from ListConversations without deleting it
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def DeleteConversation(self, request, context):
+        """Delete a conversation with its messages and attachment rows
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def SearchMessages(self, request, context):
+        """Full-text search over message text
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def SwitchBranch(self, request, context):
+        """Make the branch through message_id active; returns the new active branch
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def Regenerate(self, request, context):
+        """Answer a user message again, storing the reply as another version of the previous one
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
 
 def add_ChatServiceServicer_to_server(servicer, server):
     rpc_method_handlers = {
@@ -90,6 +174,41 @@ def add_ChatServiceServicer_to_server(servicer, server):
                     request_deserializer=chat__pb2.SendMessageRequest.FromString,
                     response_serializer=chat__pb2.StreamResponse.SerializeToString,
             ),
+            'ListConversations': grpc.unary_unary_rpc_method_handler(
+                    servicer.ListConversations,
+                    request_deserializer=chat__pb2.ListConversationsRequest.FromString,
+                    response_serializer=chat__pb2.ListConversationsResponse.SerializeToString,
+            ),
+            'RenameConversation': grpc.unary_unary_rpc_method_handler(
+                    servicer.RenameConversation,
+                    request_deserializer=chat__pb2.RenameConversationRequest.FromString,
+                    response_serializer=chat__pb2.Conversation.SerializeToString,
+            ),
+            'ArchiveConversation': grpc.unary_unary_rpc_method_handler(
+                    servicer.ArchiveConversation,
+                    request_deserializer=chat__pb2.ArchiveConversationRequest.FromString,
+                    response_serializer=chat__pb2.Conversation.SerializeToString,
+            ),
+            'DeleteConversation': grpc.unary_unary_rpc_method_handler(
+                    servicer.DeleteConversation,
+                    request_deserializer=chat__pb2.DeleteConversationRequest.FromString,
+                    response_serializer=chat__pb2.DeleteConversationResponse.SerializeToString,
+            ),
+            'SearchMessages': grpc.unary_unary_rpc_method_handler(
+                    servicer.SearchMessages,
+                    request_deserializer=chat__pb2.SearchMessagesRequest.FromString,
+                    response_serializer=chat__pb2.SearchMessagesResponse.SerializeToString,
+            ),
+            'SwitchBranch': grpc.unary_unary_rpc_method_handler(
+                    servicer.SwitchBranch,
+                    request_deserializer=chat__pb2.SwitchBranchRequest.FromString,
+                    response_serializer=chat__pb2.GetHistoryResponse.SerializeToString,
+            ),
+            'Regenerate': grpc.unary_stream_rpc_method_handler(
+                    servicer.Regenerate,
+                    request_deserializer=chat__pb2.RegenerateRequest.FromString,
+                    response_serializer=chat__pb2.StreamResponse.SerializeToString,
+            ),
     }
     generic_handler = grpc.method_handlers_generic_handler(
             'videoanalyzer.chat.ChatService', rpc_method_handlers)
@@ -181,3 +300,192 @@ class ChatService(object):
             timeout,
             metadata,
             _registered_method=True)
+
+    @staticmethod
+    def ListConversations(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/videoanalyzer.chat.ChatService/ListConversations',
+            chat__pb2.ListConversationsRequest.SerializeToString,
+            chat__pb2.ListConversationsResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def RenameConversation(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/videoanalyzer.chat.ChatService/RenameConversation',
+            chat__pb2.RenameConversationRequest.SerializeToString,
+            chat__pb2.Conversation.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def ArchiveConversation(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/videoanalyzer.chat.ChatService/ArchiveConversation',
+            chat__pb2.ArchiveConversationRequest.SerializeToString,
+            chat__pb2.Conversation.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def DeleteConversation(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/videoanalyzer.chat.ChatService/DeleteConversation',
+            chat__pb2.DeleteConversationRequest.SerializeToString,
+            chat__pb2.DeleteConversationResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def SearchMessages(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/videoanalyzer.chat.ChatService/SearchMessages',
+            chat__pb2.SearchMessagesRequest.SerializeToString,
+            chat__pb2.SearchMessagesResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def SwitchBranch(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/videoanalyzer.chat.ChatService/SwitchBranch',
+            chat__pb2.SwitchBranchRequest.SerializeToString,
+            chat__pb2.GetHistoryResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def Regenerate(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_stream(
+            request,
+            target,
+            '/videoanalyzer.chat.ChatService/Regenerate',
+            chat__pb2.RegenerateRequest.SerializeToString,
+            chat__pb2.StreamResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
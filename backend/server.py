@@ -1,17 +1,36 @@
 # backend/server.py
+import argparse
+import os
+import signal
 import time
 import uuid
 import json
 from concurrent import futures
 import grpc
+from grpc_health.v1 import health, health_pb2, health_pb2_grpc
 
 from backend.protos import chat_pb2_grpc, chat_pb2  # generated code
-from backend.db import init_db, store_message, get_history, now_ms
+from backend.db import init_db, store_message, get_history, now_ms, UnknownMessage, NotEditable
 from backend.attachments_store import save_attachment_bytes
+from backend.auth import TOKEN_ENV, TokenAuthInterceptor
 
 # Initialize DB
 init_db()
 
+# Only messages and history are served here. The desktop shell answers
+# ListConversations, RenameConversation, ArchiveConversation,
+# DeleteConversation, SearchMessages, SwitchBranch and Regenerate itself over
+# the same database (see frontend/src-tauri/src/grpc.rs), so the remaining
+# RPCs fall back to UNIMPLEMENTED.
+def _store_or_abort(context, message, edit_of=""):
+    try:
+        store_message(message, edit_of)
+    except UnknownMessage as e:
+        context.abort(grpc.StatusCode.NOT_FOUND, str(e))
+    except NotEditable as e:
+        context.abort(grpc.StatusCode.INVALID_ARGUMENT, str(e))
+
+
 class ChatServiceServicer(chat_pb2_grpc.ChatServiceServicer):
     def SendMessage(self, request, context):
         # store the incoming message
@@ -25,9 +44,10 @@ class ChatServiceServicer(chat_pb2_grpc.ChatServiceServicer):
             "confidence": msg.confidence if msg.confidence != 0 else None,
             "needs_clarification": msg.needs_clarification,
             "attachments": list(msg.attachments),
-            "metadata_json": json.loads(msg.metadata_json) if msg.metadata_json else {}
+            "metadata_json": json.loads(msg.metadata_json) if msg.metadata_json else {},
+            "parent_id": msg.parent_id,
         }
-        store_message(stored)
+        _store_or_abort(context, stored, request.edit_of)
         # optionally trigger agent(s) here; for SendMessage we return stored message
         resp_msg = chat_pb2.Message(
             id=stored["id"],
@@ -38,7 +58,8 @@ class ChatServiceServicer(chat_pb2_grpc.ChatServiceServicer):
             confidence=stored["confidence"] or 0.0,
             needs_clarification=stored["needs_clarification"],
             attachments=stored["attachments"],
-            metadata_json=json.dumps(stored["metadata_json"])
+            metadata_json=json.dumps(stored["metadata_json"]),
+            parent_id=stored["parent_id"],
         )
         return chat_pb2.SendMessageResponse(stored_message=resp_msg)
 
@@ -56,7 +77,9 @@ class ChatServiceServicer(chat_pb2_grpc.ChatServiceServicer):
                 confidence=r["confidence"] or 0.0,
                 needs_clarification=r["needs_clarification"],
                 attachments=r["attachments"],
-                metadata_json=json.dumps(r["metadata_json"])
+                metadata_json=json.dumps(r["metadata_json"]),
+                parent_id=r["parent_id"],
+                sibling_ids=r["sibling_ids"],
             )
             msgs.append(m)
         return chat_pb2.GetHistoryResponse(messages=msgs)
@@ -79,19 +102,26 @@ class ChatServiceServicer(chat_pb2_grpc.ChatServiceServicer):
             "confidence": request.message.confidence if request.message.confidence != 0 else None,
             "needs_clarification": request.message.needs_clarification,
             "attachments": list(request.message.attachments),
-            "metadata_json": json.loads(request.message.metadata_json) if request.message.metadata_json else {}
+            "metadata_json": json.loads(request.message.metadata_json) if request.message.metadata_json else {},
+            "parent_id": request.message.parent_id,
         }
-        store_message(user_stored)
+        _store_or_abort(context, user_stored, request.edit_of)
 
         # Example: local "agent" will respond; you should plug in OpenVINO/HF model generation here.
         # We'll simulate streaming by chunking a reply string.
         reply_text = f"Simulated agent reply summarizing: {user_text[:200]}"
         chunk_size = 40
         for i in range(0, len(reply_text), chunk_size):
+            if not context.is_active():
+                # client cancelled; it persists the partial reply itself
+                return
             chunk = reply_text[i:i+chunk_size]
             yield chat_pb2.StreamResponse(partial_text=chunk, done=False)
             time.sleep(0.05)  # small delay to emulate streaming
 
+        if not context.is_active():
+            return
+
         # when done, persist final agent message
         agent_msg = {
             "id": uuid.uuid4().hex,
@@ -102,7 +132,9 @@ class ChatServiceServicer(chat_pb2_grpc.ChatServiceServicer):
             "confidence": 0.9,
             "needs_clarification": False,
             "attachments": [],
-            "metadata_json": {}
+            "metadata_json": {},
+            # answers the user message even if another branch was shown meanwhile
+            "parent_id": user_stored["id"],
         }
         store_message(agent_msg)
         # yield final message as Message payload + done=True
@@ -115,16 +147,67 @@ class ChatServiceServicer(chat_pb2_grpc.ChatServiceServicer):
             confidence=agent_msg["confidence"],
             needs_clarification=agent_msg["needs_clarification"],
             attachments=agent_msg["attachments"],
-            metadata_json=json.dumps(agent_msg["metadata_json"])
+            metadata_json=json.dumps(agent_msg["metadata_json"]),
+            parent_id=agent_msg["parent_id"],
         )
         yield chat_pb2.StreamResponse(message=msg_proto, done=True)
 
-def serve(port=50051):
-    server = grpc.server(futures.ThreadPoolExecutor(max_workers=10))
+# Model files each agent service needs; its health reports NOT_SERVING while
+# any is missing. Transcription fetches its own model on first use.
+MODEL_REQUIREMENTS = {
+    "genai.transcription.TranscriptionAgent": [],
+    "genai.vision.VisionAgent": ["backend/models/yolov8n.onnx"],
+    "genai.generation.GenerationAgent": [],
+}
+
+def add_health_servicer(server):
+    """Serve grpc.health.v1.Health for the chat service and agent model availability."""
+    servicer = health.HealthServicer()
+    health_pb2_grpc.add_HealthServicer_to_server(servicer, server)
+    serving = health_pb2.HealthCheckResponse.SERVING
+    servicer.set("", serving)
+    servicer.set(chat_pb2.DESCRIPTOR.services_by_name["ChatService"].full_name, serving)
+    for service, paths in MODEL_REQUIREMENTS.items():
+        missing = [p for p in paths if not os.path.exists(p)]
+        if missing:
+            print(f"{service} unavailable, missing models: {', '.join(missing)}")
+        servicer.set(service, health_pb2.HealthCheckResponse.NOT_SERVING if missing else serving)
+    return servicer
+
+# Printed once serving, followed by the bound address; the desktop shell
+# waits for this line (see HANDSHAKE in frontend/src-tauri/src/sidecar.rs).
+READY_PREFIX = "VIDEO_ANALYZER_READY"
+
+def serve(port=50051, host="127.0.0.1", unix=None):
+    # the desktop shell stops us with SIGTERM; shut down as on Ctrl-C
+    signal.signal(signal.SIGTERM, signal.default_int_handler)
+    interceptors = []
+    token = os.environ.get(TOKEN_ENV)
+    if token:
+        interceptors.append(TokenAuthInterceptor(token))
+    else:
+        print(f"{TOKEN_ENV} not set; accepting unauthenticated calls")
+    server = grpc.server(futures.ThreadPoolExecutor(max_workers=10), interceptors=interceptors)
     chat_pb2_grpc.add_ChatServiceServicer_to_server(ChatServiceServicer(), server)
-    server.add_insecure_port(f"[::]:{port}")
+    add_health_servicer(server)
+    if unix:
+        # a socket left over from a previous run would block the bind
+        if os.path.exists(unix):
+            os.remove(unix)
+        address = f"unix:{unix}"
+    else:
+        address = f"{host}:{port}"
+    bound = server.add_insecure_port(address)
+    if not bound:
+        raise SystemExit(f"could not listen on {address}")
+    if unix:
+        # only the current user may connect
+        os.chmod(unix, 0o600)
+    else:
+        address = f"{host}:{bound}"
     server.start()
-    print(f"gRPC server listening on {port}")
+    print(f"gRPC server listening on {address}")
+    print(f"{READY_PREFIX} {address}", flush=True)
     try:
         while True:
             time.sleep(3600)
@@ -132,4 +215,12 @@ def serve(port=50051):
         server.stop(0)
 
 if __name__ == "__main__":
-    serve()
+    parser = argparse.ArgumentParser(description="Video analyzer chat backend")
+    # loopback only by default: chat history should not be reachable from the LAN
+    parser.add_argument("--host", default="127.0.0.1")
+    # 0 picks any free port; the handshake line reports the one bound
+    parser.add_argument("--port", type=int, default=int(os.environ.get("VIDEO_ANALYZER_PORT", 50051)))
+    # serve on a Unix domain socket instead of TCP (Linux/macOS)
+    parser.add_argument("--unix", metavar="PATH")
+    args = parser.parse_args()
+    serve(port=args.port, host=args.host, unix=args.unix)
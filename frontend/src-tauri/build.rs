@@ -1,3 +1,34 @@
+const PROTO_DIR: &str = "../../protos";
+
+const PROTOS: &[&str] = &[
+  "common.proto",
+  "chat.proto",
+  "agent_manager.proto",
+  "transcription.proto",
+  "vision.proto",
+  "generation.proto",
+];
+
 fn main() {
+  // Use the vendored protoc unless the caller already points at one, so
+  // building the shell does not require a system-wide protobuf install.
+  if std::env::var_os("PROTOC").is_none() {
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is unavailable");
+    std::env::set_var("PROTOC", protoc);
+  }
+
+  let protos: Vec<String> = PROTOS
+    .iter()
+    .map(|name| format!("{PROTO_DIR}/{name}"))
+    .collect();
+  for proto in &protos {
+    println!("cargo:rerun-if-changed={proto}");
+  }
+
+  tonic_build::configure()
+    .build_server(true)
+    .compile_protos(&protos, &[PROTO_DIR])
+    .expect("failed to compile protos");
+
   tauri_build::build()
 }
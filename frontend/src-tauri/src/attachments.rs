@@ -0,0 +1,71 @@
+//! Tauri commands for message attachments, kept in the shell's attachment
+//! store next to `chat_history.db`.
+//!
+//! The webview adds a file with [`add_attachment`] and lists the returned
+//! id in the `attachments` of the message it then sends; the id resolves to
+//! the stored file through [`get_attachment`] and [`read_attachment`].
+//!
+//! The user picks the file in a native dialog rather than the webview
+//! passing a path, so the webview can only reach files the user chose.
+
+use std::path::PathBuf;
+
+use tauri::ipc::Response;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+use crate::chat::chat_store;
+use crate::error::{Error, Result};
+use crate::history::{Attachment, AttachmentError};
+
+/// Asks for a file and copies it into the attachment store. Resolves to
+/// `null` if the user cancelled.
+#[tauri::command]
+pub async fn add_attachment(app: AppHandle) -> Result<Option<Attachment>> {
+  let store = chat_store(&app)?;
+
+  let (tx, rx) = oneshot::channel();
+  app
+    .dialog()
+    .file()
+    .set_title("Attach a file")
+    .pick_file(move |path| {
+      let _ = tx.send(path);
+    });
+  // A dialog closed without answering counts as cancelled.
+  let Some(path) = rx
+    .await
+    .ok()
+    .flatten()
+    .and_then(|path| path.as_path().map(PathBuf::from))
+  else {
+    return Ok(None);
+  };
+
+  // Hashing and copying a video takes a while; keep it off the runtime.
+  let attachment =
+    tauri::async_runtime::spawn_blocking(move || store.add_attachment(&path)).await??;
+  Ok(Some(attachment))
+}
+
+/// The attachment with `id`, including the path of its file.
+#[tauri::command]
+pub fn get_attachment(app: AppHandle, id: String) -> Result<Attachment> {
+  chat_store(&app)?
+    .attachment(&id)?
+    .ok_or(Error::UnknownAttachment(id))
+}
+
+/// The bytes of the attachment with `id`, sent to the webview as an
+/// `ArrayBuffer`.
+#[tauri::command]
+pub async fn read_attachment(app: AppHandle, id: String) -> Result<Response> {
+  let attachment = chat_store(&app)?
+    .attachment(&id)?
+    .ok_or(Error::UnknownAttachment(id))?;
+  let bytes = tauri::async_runtime::spawn_blocking(move || std::fs::read(attachment.path))
+    .await?
+    .map_err(AttachmentError::from)?;
+  Ok(Response::new(bytes))
+}
@@ -0,0 +1,218 @@
+//! Per-launch shared secret between the desktop shell, the Python backend
+//! and the agents.
+//!
+//! The shell generates the secret at startup, hands it to the backend it
+//! spawns through [`TOKEN_ENV`] and writes it to [`TOKEN_FILE`] in the data
+//! directory for agents started separately. Every gRPC call carries it as
+//! `authorization: Bearer <secret>`; servers reject calls without it.
+
+use std::io::Write;
+use std::path::Path;
+
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+/// Environment variable carrying the secret; setting it before launch pins
+/// the secret instead of generating one, e.g. for a backend started by hand.
+pub const TOKEN_ENV: &str = "VIDEO_ANALYZER_AUTH_TOKEN";
+
+/// File in the data directory holding the secret. Read by
+/// `backend/auth.py`.
+pub const TOKEN_FILE: &str = "auth_token";
+
+const HEADER: &str = "authorization";
+
+/// Returns a fresh random secret as 64 hex characters.
+pub fn generate() -> String {
+  let mut bytes = [0u8; 32];
+  getrandom::getrandom(&mut bytes).expect("no OS randomness available");
+  bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Writes the secret to [`TOKEN_FILE`] in `data_dir`, readable by the
+/// current user only.
+pub fn write_token_file(data_dir: &Path, token: &str) -> std::io::Result<()> {
+  std::fs::create_dir_all(data_dir)?;
+  let path = data_dir.join(TOKEN_FILE);
+  let mut options = std::fs::OpenOptions::new();
+  options.write(true).create(true).truncate(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    options.mode(0o600);
+    // `mode` only applies to new files.
+    if path.exists() {
+      std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+  }
+  options.open(&path)?.write_all(token.as_bytes())
+}
+
+/// Whether `token` can be sent as a header value: visible ASCII only.
+pub fn is_valid(token: &str) -> bool {
+  // Header values also admit bytes past ASCII, which the backend's gRPC
+  // rejects as metadata.
+  token.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+/// Panics unless `token` [`is_valid`]; [`crate::config::Config`] only
+/// holds valid tokens.
+fn bearer(token: &str) -> MetadataValue<Ascii> {
+  format!("Bearer {token}")
+    .parse()
+    .expect("token is not valid metadata")
+}
+
+/// Client interceptor that attaches the secret to every call.
+#[derive(Clone)]
+pub struct AttachToken {
+  value: MetadataValue<Ascii>,
+}
+
+impl AttachToken {
+  pub fn new(token: &str) -> Self {
+    Self {
+      value: bearer(token),
+    }
+  }
+}
+
+impl Interceptor for AttachToken {
+  fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+    request.metadata_mut().insert(HEADER, self.value.clone());
+    Ok(request)
+  }
+}
+
+/// Server interceptor that rejects calls without the secret.
+#[derive(Clone)]
+pub struct RequireToken {
+  value: MetadataValue<Ascii>,
+}
+
+impl RequireToken {
+  pub fn new(token: &str) -> Self {
+    Self {
+      value: bearer(token),
+    }
+  }
+}
+
+impl Interceptor for RequireToken {
+  fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+    match request.metadata().get(HEADER) {
+      Some(value) if constant_time_eq(value.as_bytes(), self.value.as_bytes()) => Ok(request),
+      _ => Err(Status::unauthenticated("missing or invalid token")),
+    }
+  }
+}
+
+/// Compares without returning early, so timing does not leak how much of a
+/// guess matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+  a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::history::testing::ScratchDir;
+
+  const TOKEN: &str = "0123456789abcdef";
+
+  /// The code `RequireToken` rejects a call carrying `header` with.
+  fn rejection(header: Option<&str>) -> Option<tonic::Code> {
+    let mut request = Request::new(());
+    if let Some(header) = header {
+      request
+        .metadata_mut()
+        .insert(HEADER, header.parse().unwrap());
+    }
+    RequireToken::new(TOKEN)
+      .call(request)
+      .err()
+      .map(|status| status.code())
+  }
+
+  #[test]
+  fn compares_whole_values() {
+    assert!(constant_time_eq(b"secret", b"secret"));
+    assert!(!constant_time_eq(b"secret", b"secreT"));
+    assert!(!constant_time_eq(b"secret", b"secret2"));
+    assert!(!constant_time_eq(b"secret", b"secre"));
+    assert!(!constant_time_eq(b"", b"s"));
+    assert!(constant_time_eq(b"", b""));
+  }
+
+  #[test]
+  fn tokens_must_be_visible_ascii() {
+    assert!(is_valid(TOKEN));
+    assert!(is_valid(&generate()));
+    assert!(!is_valid("line\nbreak"));
+    assert!(!is_valid("two words"));
+    assert!(!is_valid("caf\u{e9}"));
+  }
+
+  #[test]
+  fn generated_tokens_differ() {
+    let token = generate();
+    assert_eq!(token.len(), 64);
+    assert!(token.bytes().all(|byte| byte.is_ascii_hexdigit()));
+    assert_ne!(token, generate());
+  }
+
+  #[test]
+  fn accepts_only_the_exact_bearer_header() {
+    assert_eq!(rejection(Some(&format!("Bearer {TOKEN}"))), None);
+    for header in [
+      None,
+      Some("Bearer wrong".to_string()),
+      Some(format!("Bearer {TOKEN}0")),
+      Some(format!("Bearer {}", &TOKEN[1..])),
+      Some(format!("bearer {TOKEN}")),
+      Some(format!("BEARER {TOKEN}")),
+      Some(format!("Bearer  {TOKEN}")),
+      Some(format!("Bearer\t{TOKEN}")),
+      Some(TOKEN.to_string()),
+    ] {
+      assert_eq!(
+        rejection(header.as_deref()),
+        Some(tonic::Code::Unauthenticated),
+        "{header:?}"
+      );
+    }
+  }
+
+  #[test]
+  fn attached_tokens_are_accepted() {
+    let request = AttachToken::new(TOKEN).call(Request::new(())).unwrap();
+    assert!(RequireToken::new(TOKEN).call(request).is_ok());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn token_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = ScratchDir::new("auth");
+    let path = dir.path().join(TOKEN_FILE);
+    std::fs::write(&path, "old token, world readable").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    for token in ["first", TOKEN] {
+      write_token_file(dir.path(), token).unwrap();
+      let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+      assert_eq!(mode & 0o777, 0o600);
+      assert_eq!(std::fs::read_to_string(&path).unwrap(), token);
+    }
+
+    let fresh = dir.path().join("nested");
+    write_token_file(&fresh, TOKEN).unwrap();
+    let mode = std::fs::metadata(fresh.join(TOKEN_FILE))
+      .unwrap()
+      .permissions()
+      .mode();
+    assert_eq!(mode & 0o777, 0o600);
+  }
+}
@@ -0,0 +1,787 @@
+//! Tauri commands wrapping `ChatService`: messages, streamed replies, the
+//! conversation list and search.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager as _, State};
+use tokio_util::sync::CancellationToken;
+use tonic::Streaming;
+
+use crate::error::{Error, Result};
+use crate::grpc::Backend;
+use crate::history::{new_id, ChatStore, Titler};
+use crate::proto::chat::stream_response::Payload;
+use crate::proto::chat::{
+  ArchiveConversationRequest, Conversation, DeleteConversationRequest, GetHistoryRequest,
+  ListConversationsRequest, Message, RegenerateRequest, RenameConversationRequest, SearchHit,
+  SearchMessagesRequest, SendMessageRequest, StreamResponse, SwitchBranchRequest,
+};
+
+/// `GetHistory` page size used when the caller does not pass one; matches
+/// the default in `backend/server.py`.
+const DEFAULT_HISTORY_LIMIT: i32 = 100;
+
+/// A chat message as exchanged with the webview.
+///
+/// Mirrors `videoanalyzer.chat.Message`, except that `confidence` and
+/// `parent_id` are absent rather than `0.0` or empty when unset and
+/// `metadata_json` is parsed JSON instead of a string. Every field is
+/// optional on input so the UI only needs to send `text` (and `sender` for
+/// non-user messages).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatMessage {
+  pub id: String,
+  pub conversation_id: String,
+  pub sender: String,
+  pub text: String,
+  pub created_at: i64,
+  pub confidence: Option<f64>,
+  pub needs_clarification: bool,
+  pub attachments: Vec<String>,
+  pub metadata_json: Value,
+  pub parent_id: Option<String>,
+  /// Versions of this message, oldest first, itself included; more than one
+  /// where the conversation branches.
+  pub sibling_ids: Vec<String>,
+}
+
+impl From<Message> for ChatMessage {
+  fn from(msg: Message) -> Self {
+    Self {
+      id: msg.id,
+      conversation_id: msg.conversation_id,
+      sender: msg.sender,
+      text: msg.text,
+      created_at: msg.created_at,
+      confidence: (msg.confidence != 0.0).then_some(msg.confidence),
+      needs_clarification: msg.needs_clarification,
+      attachments: msg.attachments,
+      metadata_json: parse_metadata(msg.metadata_json),
+      parent_id: (!msg.parent_id.is_empty()).then_some(msg.parent_id),
+      sibling_ids: msg.sibling_ids,
+    }
+  }
+}
+
+impl From<ChatMessage> for Message {
+  fn from(msg: ChatMessage) -> Self {
+    Self {
+      id: msg.id,
+      conversation_id: msg.conversation_id,
+      sender: msg.sender,
+      text: msg.text,
+      created_at: msg.created_at,
+      confidence: msg.confidence.unwrap_or_default(),
+      needs_clarification: msg.needs_clarification,
+      attachments: msg.attachments,
+      metadata_json: match msg.metadata_json {
+        Value::Null => String::new(),
+        value => value.to_string(),
+      },
+      parent_id: msg.parent_id.unwrap_or_default(),
+      sibling_ids: msg.sibling_ids,
+    }
+  }
+}
+
+/// A conversation as listed in the sidebar; mirrors
+/// `videoanalyzer.chat.Conversation`, with the last-message fields absent
+/// for an empty conversation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatConversation {
+  pub id: String,
+  pub title: String,
+  pub created_at: i64,
+  pub updated_at: i64,
+  pub archived: bool,
+  pub message_count: i32,
+  pub attachment_count: i32,
+  pub last_message_preview: Option<String>,
+  pub last_message_sender: Option<String>,
+}
+
+impl From<Conversation> for ChatConversation {
+  fn from(conversation: Conversation) -> Self {
+    let has_messages = conversation.message_count > 0;
+    Self {
+      id: conversation.id,
+      title: conversation.title,
+      created_at: conversation.created_at,
+      updated_at: conversation.updated_at,
+      archived: conversation.archived,
+      message_count: conversation.message_count,
+      attachment_count: conversation.attachment_count,
+      last_message_preview: has_messages.then_some(conversation.last_message_preview),
+      last_message_sender: has_messages.then_some(conversation.last_message_sender),
+    }
+  }
+}
+
+/// A search result; mirrors `videoanalyzer.chat.SearchHit`. `snippet` is
+/// HTML with the matched words wrapped in `<mark>`, safe to render as is.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+  pub message_id: String,
+  pub conversation_id: String,
+  pub conversation_title: String,
+  pub sender: String,
+  pub created_at: i64,
+  pub snippet: String,
+  pub score: f64,
+}
+
+impl From<SearchHit> for SearchResult {
+  fn from(hit: SearchHit) -> Self {
+    Self {
+      message_id: hit.message_id,
+      conversation_id: hit.conversation_id,
+      conversation_title: hit.conversation_title,
+      sender: hit.sender,
+      created_at: hit.created_at,
+      snippet: hit.snippet,
+      score: hit.score,
+    }
+  }
+}
+
+/// One element of a `StreamResponses` call, forwarded to the webview in the
+/// order the backend produced it.
+///
+/// Every stream ends with exactly one terminal event: `done` once the
+/// backend closes the stream, `cancelled` after [`cancel_stream`], or
+/// `failed` if the call breaks off.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum StreamEvent {
+  /// A chunk of the agent reply while it is being generated.
+  Partial {
+    text: String,
+  },
+  /// The agent reply as stored by the backend; replaces the partial text.
+  Message {
+    message: ChatMessage,
+  },
+  Done,
+  /// The user cancelled; `message` is the stored truncated reply, if any
+  /// text had arrived.
+  Cancelled {
+    message: Option<ChatMessage>,
+  },
+  Failed {
+    error: String,
+  },
+}
+
+/// Parses `metadata_json`, falling back to an empty object when unset and to
+/// the raw string when the backend stored something that is not JSON.
+fn parse_metadata(raw: String) -> Value {
+  if raw.is_empty() {
+    return Value::Object(Default::default());
+  }
+  serde_json::from_str(&raw).unwrap_or(Value::String(raw))
+}
+
+#[tauri::command]
+pub async fn send_message(
+  app: AppHandle,
+  backend: State<'_, Backend>,
+  conversation_id: String,
+  message: ChatMessage,
+) -> Result<ChatMessage> {
+  let response = backend
+    .chat()
+    .await?
+    .send_message(SendMessageRequest {
+      conversation_id,
+      message: Some(message.into()),
+      stream_responses: false,
+      edit_of: String::new(),
+    })
+    .await?
+    .into_inner();
+  let stored = response.stored_message.unwrap_or_default();
+  if stored.sender != "user" {
+    title_after_reply(&app, stored.conversation_id.clone());
+  }
+  Ok(stored.into())
+}
+
+/// Messages of the active branch, oldest first.
+#[tauri::command]
+pub async fn get_history(
+  backend: State<'_, Backend>,
+  conversation_id: String,
+  limit: Option<i32>,
+  offset: Option<i32>,
+) -> Result<Vec<ChatMessage>> {
+  let response = backend
+    .chat()
+    .await?
+    .get_history(GetHistoryRequest {
+      conversation_id,
+      limit: limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+      offset: offset.unwrap_or(0),
+    })
+    .await?
+    .into_inner();
+  Ok(response.messages.into_iter().map(Into::into).collect())
+}
+
+/// Shows the branch through `message_id`, one of the versions listed in a
+/// message's `sibling_ids`, and returns the conversation's new active
+/// branch.
+#[tauri::command]
+pub async fn switch_branch(
+  backend: State<'_, Backend>,
+  conversation_id: String,
+  message_id: String,
+) -> Result<Vec<ChatMessage>> {
+  let response = backend
+    .history()
+    .await?
+    .switch_branch(SwitchBranchRequest {
+      conversation_id,
+      message_id,
+    })
+    .await?
+    .into_inner();
+  Ok(response.messages.into_iter().map(Into::into).collect())
+}
+
+/// Lists conversations, most recently active first. Archived ones are left
+/// out unless `include_archived` is set.
+#[tauri::command]
+pub async fn list_conversations(
+  backend: State<'_, Backend>,
+  include_archived: Option<bool>,
+  limit: Option<i32>,
+  offset: Option<i32>,
+) -> Result<Vec<ChatConversation>> {
+  let response = backend
+    .history()
+    .await?
+    .list_conversations(ListConversationsRequest {
+      include_archived: include_archived.unwrap_or(false),
+      limit: limit.unwrap_or(0),
+      offset: offset.unwrap_or(0),
+    })
+    .await?
+    .into_inner();
+  Ok(response.conversations.into_iter().map(Into::into).collect())
+}
+
+#[tauri::command]
+pub async fn rename_conversation(
+  backend: State<'_, Backend>,
+  conversation_id: String,
+  title: String,
+) -> Result<ChatConversation> {
+  let conversation = backend
+    .history()
+    .await?
+    .rename_conversation(RenameConversationRequest {
+      conversation_id,
+      title,
+    })
+    .await?
+    .into_inner();
+  Ok(conversation.into())
+}
+
+/// Archives a conversation, or restores it when `archived` is `false`.
+#[tauri::command]
+pub async fn archive_conversation(
+  backend: State<'_, Backend>,
+  conversation_id: String,
+  archived: Option<bool>,
+) -> Result<ChatConversation> {
+  let conversation = backend
+    .history()
+    .await?
+    .archive_conversation(ArchiveConversationRequest {
+      conversation_id,
+      archived: archived.unwrap_or(true),
+    })
+    .await?
+    .into_inner();
+  Ok(conversation.into())
+}
+
+/// Deletes a conversation with its messages. Returns `false` if it did not
+/// exist.
+#[tauri::command]
+pub async fn delete_conversation(
+  backend: State<'_, Backend>,
+  conversation_id: String,
+) -> Result<bool> {
+  let response = backend
+    .history()
+    .await?
+    .delete_conversation(DeleteConversationRequest { conversation_id })
+    .await?
+    .into_inner();
+  Ok(response.deleted)
+}
+
+/// Narrows [`search_messages`]; every field is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchFilters {
+  pub sender: Option<String>,
+  /// Earliest `created_at`, unix ms, inclusive.
+  pub from: Option<i64>,
+  /// Latest `created_at`, unix ms, inclusive.
+  pub to: Option<i64>,
+  pub conversation_id: Option<String>,
+}
+
+/// Searches message text, best match first.
+#[tauri::command]
+pub async fn search_messages(
+  backend: State<'_, Backend>,
+  query: String,
+  filters: Option<SearchFilters>,
+  limit: Option<i32>,
+  offset: Option<i32>,
+) -> Result<Vec<SearchResult>> {
+  let filters = filters.unwrap_or_default();
+  let response = backend
+    .history()
+    .await?
+    .search_messages(SearchMessagesRequest {
+      query,
+      sender: filters.sender.unwrap_or_default(),
+      from: filters.from.unwrap_or(0),
+      to: filters.to.unwrap_or(0),
+      conversation_id: filters.conversation_id.unwrap_or_default(),
+      limit: limit.unwrap_or(0),
+      offset: offset.unwrap_or(0),
+    })
+    .await?
+    .into_inner();
+  Ok(response.hits.into_iter().map(Into::into).collect())
+}
+
+/// Stores `message` and streams the agent reply over `on_event`.
+///
+/// With `edit_of`, the message is stored as a new version of that earlier
+/// message and the reply continues from there on a new branch.
+///
+/// Resolves once the terminal event has been sent. A stream already running
+/// for the same conversation is cancelled first.
+#[tauri::command]
+pub async fn stream_responses(
+  app: AppHandle,
+  backend: State<'_, Backend>,
+  streams: State<'_, ActiveStreams>,
+  conversation_id: String,
+  message: ChatMessage,
+  edit_of: Option<String>,
+  on_event: Channel<StreamEvent>,
+) -> Result<()> {
+  let key = effective_conversation_id(&conversation_id, &message.conversation_id);
+  let mut message = message;
+  // Assigned here rather than by the backend so a truncated reply can be
+  // stored under this message without waiting for it to come back.
+  if message.id.is_empty() {
+    message.id = new_id();
+  }
+  let user_id = message.id.clone();
+  let request = SendMessageRequest {
+    conversation_id,
+    message: Some(message.into()),
+    stream_responses: true,
+    edit_of: edit_of.unwrap_or_default(),
+  };
+  let call = async {
+    let stream = backend.chat().await?.stream_responses(request).await?;
+    Ok::<_, Error>(stream.into_inner())
+  };
+  run_stream(&backend, &streams, &key, &user_id, call, &on_event).await?;
+  title_after_reply(&app, key);
+  Ok(())
+}
+
+/// Answers `message_id`, or the last user message of the active branch,
+/// again and streams the new reply over `on_event` like
+/// [`stream_responses`].
+///
+/// The new reply is stored as another version of the earlier ones, listed
+/// in their `sibling_ids`; [`switch_branch`] pages between them, and later
+/// messages follow whichever is shown.
+#[tauri::command]
+pub async fn regenerate_reply(
+  backend: State<'_, Backend>,
+  streams: State<'_, ActiveStreams>,
+  conversation_id: String,
+  message_id: Option<String>,
+  on_event: Channel<StreamEvent>,
+) -> Result<()> {
+  let key = effective_conversation_id(&conversation_id, "");
+  let message_id = match message_id {
+    Some(id) => id,
+    None => last_user_message(&backend, &key).await?,
+  };
+  let request = RegenerateRequest {
+    conversation_id,
+    message_id: message_id.clone(),
+  };
+  let call = async {
+    let stream = backend.history().await?.regenerate(request).await?;
+    Ok::<_, Error>(stream.into_inner())
+  };
+  run_stream(&backend, &streams, &key, &message_id, call, &on_event).await
+}
+
+/// The id of the last user message on the active branch, the one
+/// `regenerate_reply` answers when not given one.
+async fn last_user_message(backend: &Backend, conversation_id: &str) -> Result<String> {
+  let response = backend
+    .history()
+    .await?
+    .get_history(GetHistoryRequest {
+      conversation_id: conversation_id.to_string(),
+      limit: i32::MAX,
+      offset: 0,
+    })
+    .await?
+    .into_inner();
+  response
+    .messages
+    .into_iter()
+    .rev()
+    .find(|message| message.sender == "user")
+    .map(|message| message.id)
+    .ok_or_else(|| tonic::Status::failed_precondition("no user message to answer").into())
+}
+
+/// Forwards the reply to `user_id` streamed by `call` and sends the
+/// terminal event, storing what arrived before a cancellation.
+async fn run_stream(
+  backend: &Backend,
+  streams: &ActiveStreams,
+  key: &str,
+  user_id: &str,
+  call: impl Future<Output = Result<Streaming<StreamResponse>>>,
+  on_event: &Channel<StreamEvent>,
+) -> Result<()> {
+  let (stream_id, stop) = streams.register(key);
+  let result = forward_stream(call, on_event, &stop.token).await;
+  streams.finish(key, stream_id);
+
+  let result = match result {
+    Ok(Outcome::Completed) => Ok(StreamEvent::Done),
+    Ok(Outcome::Cancelled { partial }) => {
+      let truncated_by = if stop.superseded() {
+        "superseded"
+      } else {
+        "user"
+      };
+      store_truncated_reply(backend, key, user_id, truncated_by, partial)
+        .await
+        .map(|message| StreamEvent::Cancelled { message })
+    }
+    Err(err) => Err(err),
+  };
+  let terminal = match &result {
+    Ok(event) => event.clone(),
+    Err(err) => StreamEvent::Failed {
+      error: err.to_string(),
+    },
+  };
+  on_event.send(terminal)?;
+  result.map(drop)
+}
+
+/// Cancels the in-flight `stream_responses` or `regenerate_reply` call for
+/// `conversation_id`.
+///
+/// Returns `false` if nothing was streaming for that conversation.
+#[tauri::command]
+pub fn cancel_stream(streams: State<'_, ActiveStreams>, conversation_id: String) -> bool {
+  streams.cancel(&effective_conversation_id(&conversation_id, ""))
+}
+
+enum Outcome {
+  Completed,
+  /// The user cancelled, or a newer stream replaced this one; `partial` is
+  /// the reply text received so far.
+  Cancelled {
+    partial: String,
+  },
+}
+
+async fn forward_stream(
+  call: impl Future<Output = Result<Streaming<StreamResponse>>>,
+  on_event: &Channel<StreamEvent>,
+  cancel: &CancellationToken,
+) -> Result<Outcome> {
+  let mut stream = tokio::select! {
+    stream = call => stream?,
+    _ = cancel.cancelled() => return Ok(Outcome::Cancelled { partial: String::new() }),
+  };
+
+  // Dropping `stream` on cancellation resets the HTTP/2 stream, which is
+  // what tells the backend to stop generating.
+  let mut partial = String::new();
+  loop {
+    let response = tokio::select! {
+      response = stream.message() => response?,
+      _ = cancel.cancelled() => return Ok(Outcome::Cancelled { partial }),
+    };
+    let Some(response) = response else {
+      break;
+    };
+    match response.payload {
+      Some(Payload::PartialText(text)) => {
+        partial.push_str(&text);
+        on_event.send(StreamEvent::Partial { text })?;
+      }
+      Some(Payload::Message(message)) => on_event.send(StreamEvent::Message {
+        message: message.into(),
+      })?,
+      None => {}
+    }
+    if response.done {
+      break;
+    }
+  }
+  Ok(Outcome::Completed)
+}
+
+/// Persists the reply text received before a cancellation under
+/// `parent_id`, marked as truncated so later reads can tell it apart from a
+/// complete answer. `truncated_by` is `user` or `superseded`.
+async fn store_truncated_reply(
+  backend: &Backend,
+  conversation_id: &str,
+  parent_id: &str,
+  truncated_by: &str,
+  partial: String,
+) -> Result<Option<ChatMessage>> {
+  if partial.is_empty() {
+    return Ok(None);
+  }
+  let reply = truncated_reply(conversation_id, parent_id, truncated_by, partial);
+  let response = backend
+    .chat()
+    .await?
+    .send_message(SendMessageRequest {
+      conversation_id: conversation_id.to_string(),
+      message: Some(reply.into()),
+      stream_responses: false,
+      edit_of: String::new(),
+    })
+    .await?
+    .into_inner();
+  Ok(response.stored_message.map(Into::into))
+}
+
+/// The message [`store_truncated_reply`] stores.
+fn truncated_reply(
+  conversation_id: &str,
+  parent_id: &str,
+  truncated_by: &str,
+  partial: String,
+) -> ChatMessage {
+  ChatMessage {
+    conversation_id: conversation_id.to_string(),
+    sender: "agent".to_string(),
+    text: partial,
+    created_at: now_ms(),
+    metadata_json: json!({ "truncated": true, "truncated_by": truncated_by }),
+    parent_id: Some(parent_id.to_string()),
+    ..Default::default()
+  }
+}
+
+/// Resolves the conversation a request lands in the same way the backend
+/// does: request field, then message field, then `"default"`. Streams are
+/// keyed by it, so cancelling finds them however the id was spelled.
+fn effective_conversation_id(conversation_id: &str, message_conversation_id: &str) -> String {
+  [conversation_id, message_conversation_id]
+    .into_iter()
+    .find(|id| !id.is_empty())
+    .unwrap_or("default")
+    .to_string()
+}
+
+/// Titles `conversation_id` once a reply is stored, if the backend hosts
+/// chat; the shell's `ChatServer` titles the replies it stores itself.
+fn title_after_reply(app: &AppHandle, conversation_id: String) {
+  if let Some(titler) = app.try_state::<Titler>() {
+    titler.after_reply(conversation_id);
+  }
+}
+
+/// The shell's chat store, for commands that work on it directly rather
+/// than through `ChatService`.
+pub(crate) fn chat_store(app: &AppHandle) -> Result<Arc<ChatStore>> {
+  app
+    .try_state::<Arc<ChatStore>>()
+    .map(|store| store.inner().clone())
+    .ok_or(Error::NoChatStore)
+}
+
+pub(crate) fn now_ms() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+/// In-flight `StreamResponses` calls, keyed by conversation id.
+#[derive(Default)]
+pub struct ActiveStreams {
+  next_id: AtomicU64,
+  streams: Mutex<HashMap<String, (u64, Stop)>>,
+}
+
+/// Stops one stream, remembering whether a newer one took its place.
+#[derive(Clone, Default)]
+struct Stop {
+  token: CancellationToken,
+  superseded: Arc<AtomicBool>,
+}
+
+impl Stop {
+  fn superseded(&self) -> bool {
+    self.superseded.load(Ordering::Relaxed)
+  }
+}
+
+impl ActiveStreams {
+  fn register(&self, conversation_id: &str) -> (u64, Stop) {
+    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+    let stop = Stop::default();
+    let previous = self
+      .streams
+      .lock()
+      .unwrap()
+      .insert(conversation_id.to_string(), (id, stop.clone()));
+    if let Some((_, previous)) = previous {
+      previous.superseded.store(true, Ordering::Relaxed);
+      previous.token.cancel();
+    }
+    (id, stop)
+  }
+
+  /// Forgets stream `id`, unless a newer stream has since taken its slot.
+  fn finish(&self, conversation_id: &str, id: u64) {
+    let mut streams = self.streams.lock().unwrap();
+    if streams
+      .get(conversation_id)
+      .is_some_and(|(current, _)| *current == id)
+    {
+      streams.remove(conversation_id);
+    }
+  }
+
+  fn cancel(&self, conversation_id: &str) -> bool {
+    match self.streams.lock().unwrap().remove(conversation_id) {
+      Some((_, stop)) => {
+        stop.token.cancel();
+        true
+      }
+      None => false,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn blank_conversation_ids_mean_default() {
+    assert_eq!(effective_conversation_id("", ""), "default");
+    assert_eq!(effective_conversation_id("", "c1"), "c1");
+    assert_eq!(effective_conversation_id("c2", "c1"), "c2");
+  }
+
+  #[test]
+  fn cancel_stops_the_stream_of_the_default_conversation() {
+    let streams = ActiveStreams::default();
+    let key = effective_conversation_id("", "");
+    let (_, stop) = streams.register(&key);
+    assert!(streams.cancel(&effective_conversation_id("", "")));
+    assert!(stop.token.is_cancelled());
+    assert!(!stop.superseded());
+    assert!(!streams.cancel(&key));
+  }
+
+  #[test]
+  fn a_new_stream_supersedes_the_previous_one() {
+    let streams = ActiveStreams::default();
+    let (first_id, first) = streams.register("c1");
+    let (_, other) = streams.register("c2");
+    let (second_id, second) = streams.register("c1");
+    assert_ne!(first_id, second_id);
+    assert!(first.token.is_cancelled() && first.superseded());
+    assert!(!second.token.is_cancelled() && !other.token.is_cancelled());
+
+    // The superseded stream winding down leaves the new one registered.
+    streams.finish("c1", first_id);
+    assert!(streams.cancel("c1"));
+    assert!(second.token.is_cancelled() && !second.superseded());
+    assert!(!other.token.is_cancelled());
+  }
+
+  #[test]
+  fn finished_streams_are_not_cancelled() {
+    let streams = ActiveStreams::default();
+    let (id, stop) = streams.register("c1");
+    streams.finish("c1", id);
+    assert!(!streams.cancel("c1"));
+    assert!(!stop.token.is_cancelled());
+  }
+
+  #[test]
+  fn truncated_reply_is_marked_and_answers_the_user_message() {
+    for truncated_by in ["user", "superseded"] {
+      let reply = Message::from(truncated_reply("c1", "q", truncated_by, "Half an".into()));
+      assert!(reply.id.is_empty());
+      assert_eq!(reply.conversation_id, "c1");
+      assert_eq!(reply.sender, "agent");
+      assert_eq!(reply.text, "Half an");
+      assert_eq!(reply.parent_id, "q");
+      let metadata: Value = serde_json::from_str(&reply.metadata_json).unwrap();
+      assert_eq!(
+        metadata,
+        json!({ "truncated": true, "truncated_by": truncated_by })
+      );
+    }
+  }
+
+  #[test]
+  fn unset_message_fields_are_absent_for_the_webview() {
+    let message = ChatMessage::from(Message {
+      id: "m".into(),
+      ..Default::default()
+    });
+    assert_eq!(message.confidence, None);
+    assert_eq!(message.parent_id, None);
+    assert_eq!(message.metadata_json, json!({}));
+    let message = Message::from(message);
+    assert_eq!(message.confidence, 0.0);
+    assert_eq!(message.parent_id, "");
+    assert_eq!(message.metadata_json, "{}");
+
+    let message = Message::from(ChatMessage {
+      metadata_json: Value::Null,
+      ..Default::default()
+    });
+    assert_eq!(message.metadata_json, "");
+    assert_eq!(
+      parse_metadata("not json".into()),
+      Value::String("not json".into())
+    );
+  }
+}
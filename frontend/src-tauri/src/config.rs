@@ -0,0 +1,156 @@
+//! Startup configuration, read from the environment.
+
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::auth;
+use crate::transport::Transport;
+
+/// Where the agent manager listens unless `AGENT_MANAGER_ADDR` says
+/// otherwise. Kept off 50051, which `backend/server.py` already owns.
+pub const DEFAULT_MANAGER_ADDR: &str = "127.0.0.1:50052";
+
+/// Agents heartbeat every second, so a few seconds of silence means trouble.
+pub const DEFAULT_AGENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Agents acknowledge a `run-task` command on their next heartbeat.
+pub const DEFAULT_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+pub const DEFAULT_PYTHON: &str = if cfg!(windows) { "python" } else { "python3" };
+
+#[derive(Debug, Clone)]
+pub struct Config {
+  /// gRPC URI of a Python backend started by hand
+  /// (`VIDEO_ANALYZER_BACKEND_ADDR`). Unset when the shell spawns the
+  /// backend, which reports its address once it is serving.
+  pub backend_addr: Option<String>,
+  /// Socket the Rust agent manager binds (`AGENT_MANAGER_ADDR`). Python
+  /// agents read the same variable to find it.
+  pub manager_addr: SocketAddr,
+  /// How long an agent may go without a heartbeat before it is marked
+  /// stale (`AGENT_TIMEOUT_SECS`).
+  pub agent_timeout: Duration,
+  /// How long a delivered task may go unacknowledged before it is sent
+  /// again (`AGENT_ACK_TIMEOUT_SECS`).
+  pub ack_timeout: Duration,
+  /// How often a failed task is retried (`TASK_MAX_RETRIES`).
+  pub max_retries: u32,
+  /// Directory holding `chat_history.db` and the task store
+  /// (`VIDEO_ANALYZER_DATA_DIR`). Debug builds default to the repository's
+  /// `data/`, shared with `backend/`; release builds leave it unset and use
+  /// the app data directory.
+  pub data_dir: Option<PathBuf>,
+  /// Whether the shell serves `ChatService` itself over `chat_history.db`
+  /// instead of relying on the backend for it
+  /// (`VIDEO_ANALYZER_NATIVE_CHAT`, on by default). Either way the shell
+  /// answers the calls the backend does not implement (see
+  /// [`crate::grpc::Backend::history`]).
+  pub native_chat: bool,
+  /// Whether to launch and supervise the backend; off when
+  /// `VIDEO_ANALYZER_BACKEND_ADDR` points at one started by hand.
+  pub spawn_backend: bool,
+  /// Interpreter that runs the backend (`VIDEO_ANALYZER_PYTHON`).
+  pub python: String,
+  /// Directory containing the `backend` package
+  /// (`VIDEO_ANALYZER_BACKEND_DIR`). Debug builds default to the
+  /// repository root; release builds must set it for the backend to be
+  /// launched.
+  pub backend_dir: Option<PathBuf>,
+  /// Whether the spawned backend and the agent manager listen on Unix
+  /// sockets or TCP loopback (`VIDEO_ANALYZER_TRANSPORT`, `unix` or `tcp`).
+  /// Sockets by default where available; TCP is also used when the socket
+  /// directory is unusable. `manager_addr` only applies to TCP.
+  pub transport: Transport,
+  /// Secret every gRPC call must carry; generated per launch unless
+  /// `VIDEO_ANALYZER_AUTH_TOKEN` pins a valid one.
+  pub auth_token: String,
+}
+
+impl Config {
+  pub fn from_env() -> Self {
+    let backend_addr = std::env::var("VIDEO_ANALYZER_BACKEND_ADDR").ok();
+    let spawn_backend = backend_addr.is_none();
+    let manager_addr = std::env::var("AGENT_MANAGER_ADDR")
+      .ok()
+      .and_then(|addr| resolve(&addr))
+      .unwrap_or_else(|| DEFAULT_MANAGER_ADDR.parse().unwrap());
+    Self {
+      backend_addr,
+      manager_addr,
+      agent_timeout: secs_var("AGENT_TIMEOUT_SECS").unwrap_or(DEFAULT_AGENT_TIMEOUT),
+      ack_timeout: secs_var("AGENT_ACK_TIMEOUT_SECS").unwrap_or(DEFAULT_ACK_TIMEOUT),
+      max_retries: parse_var("TASK_MAX_RETRIES").unwrap_or(DEFAULT_MAX_RETRIES),
+      data_dir: dir_var("VIDEO_ANALYZER_DATA_DIR", "data"),
+      native_chat: parse_var("VIDEO_ANALYZER_NATIVE_CHAT").unwrap_or(true),
+      spawn_backend,
+      python: std::env::var("VIDEO_ANALYZER_PYTHON").unwrap_or_else(|_| DEFAULT_PYTHON.to_string()),
+      backend_dir: dir_var("VIDEO_ANALYZER_BACKEND_DIR", ""),
+      transport: parse_var("VIDEO_ANALYZER_TRANSPORT").unwrap_or_default(),
+      auth_token: std::env::var(auth::TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .filter(|token| {
+          let valid = auth::is_valid(token);
+          if !valid {
+            log::warn!(
+              "ignoring {}: not visible ASCII; generating a token instead",
+              auth::TOKEN_ENV
+            );
+          }
+          valid
+        })
+        .unwrap_or_else(auth::generate),
+    }
+  }
+}
+
+/// Resolves `host:port`, accepting names such as `localhost` as the Python
+/// agents do.
+fn resolve(addr: &str) -> Option<SocketAddr> {
+  match addr.to_socket_addrs() {
+    Ok(mut addrs) => addrs.next(),
+    Err(err) => {
+      log::warn!("ignoring invalid address {addr:?}: {err}");
+      None
+    }
+  }
+}
+
+/// Reads a directory from `name`, falling back to `repo_path` inside the
+/// repository in debug builds.
+fn dir_var(name: &str, repo_path: &str) -> Option<PathBuf> {
+  std::env::var_os(name).map(PathBuf::from).or_else(|| {
+    let repo = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..");
+    cfg!(debug_assertions).then(|| repo.join(repo_path))
+  })
+}
+
+/// Reads a positive number of seconds from `name`.
+fn secs_var(name: &str) -> Option<Duration> {
+  let secs: f64 = parse_var(name)?;
+  match Duration::try_from_secs_f64(secs) {
+    Ok(duration) if !duration.is_zero() => Some(duration),
+    _ => {
+      log::warn!("ignoring invalid {name}={secs}: not a positive number of seconds");
+      None
+    }
+  }
+}
+
+fn parse_var<T>(name: &str) -> Option<T>
+where
+  T: std::str::FromStr,
+  T::Err: std::fmt::Display,
+{
+  let value = std::env::var(name).ok()?;
+  match value.parse() {
+    Ok(value) => Some(value),
+    Err(err) => {
+      log::warn!("ignoring invalid {name}={value:?}: {err}");
+      None
+    }
+  }
+}
@@ -0,0 +1,60 @@
+use serde::{Serialize, Serializer};
+
+/// Error returned from Tauri commands.
+///
+/// Serialized as its display string so the webview receives a readable
+/// message in the rejected promise.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error("backend unreachable: {0}")]
+  Transport(#[from] tonic::transport::Error),
+  #[error("backend is not ready")]
+  NotReady,
+  #[error("backend returned {}: {}", .0.code(), .0.message())]
+  Status(Box<tonic::Status>),
+  #[error(transparent)]
+  Tauri(#[from] tauri::Error),
+  #[error("unknown task {0}")]
+  UnknownTask(String),
+  #[error("task rejected: {0}")]
+  Rejected(String),
+  #[error("chat history: {0}")]
+  Database(#[from] rusqlite::Error),
+  #[error(transparent)]
+  Attachment(#[from] crate::history::AttachmentError),
+  #[error("unknown attachment {0}")]
+  UnknownAttachment(String),
+  #[error("chat history is unavailable")]
+  NoChatStore,
+  #[error("unknown conversation {0}")]
+  UnknownConversation(String),
+  #[error("could not write {}: {1}", .0.display())]
+  Export(std::path::PathBuf, std::io::Error),
+  #[error("could not read {}: {1}", .0.display())]
+  Import(std::path::PathBuf, std::io::Error),
+  #[error(transparent)]
+  Archive(#[from] crate::history::archive::ArchiveError),
+}
+
+impl From<tonic::Status> for Error {
+  fn from(status: tonic::Status) -> Self {
+    Self::Status(Box::new(status))
+  }
+}
+
+impl From<crate::grpc::ConnectError> for Error {
+  fn from(err: crate::grpc::ConnectError) -> Self {
+    match err {
+      crate::grpc::ConnectError::NotReady => Self::NotReady,
+      crate::grpc::ConnectError::Transport(err) => Self::Transport(err),
+    }
+  }
+}
+
+impl Serialize for Error {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.to_string())
+  }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,63 @@
+//! Bridges from backend-side broadcasts to Tauri events for the webview.
+
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::chat::ChatConversation;
+use crate::history::ChatServer;
+use crate::manager::Manager;
+
+/// Event carrying a [`crate::manager::HealthChange`] whenever an agent turns
+/// healthy, stale or is evicted.
+pub const AGENT_HEALTH: &str = "agent-health";
+
+/// Event carrying a [`crate::tasks::TaskProgress`] for every progress update
+/// of a watched task.
+pub const TASK_PROGRESS: &str = "task-progress";
+
+/// Event carrying a [`crate::health::BackendStatus`] whenever the backend's
+/// health changes.
+pub const BACKEND_STATUS: &str = "backend-status";
+
+/// Event carrying a [`ChatConversation`] whenever a conversation is given
+/// an automatic title.
+pub const CONVERSATION_UPDATED: &str = "conversation-updated";
+
+pub fn forward_agent_health(app: AppHandle, manager: &Manager) {
+  let mut changes = manager.subscribe_health();
+  tauri::async_runtime::spawn(async move {
+    loop {
+      match changes.recv().await {
+        Ok(change) => {
+          if let Err(err) = app.emit(AGENT_HEALTH, change) {
+            log::warn!("failed to emit {AGENT_HEALTH}: {err}");
+          }
+        }
+        Err(RecvError::Lagged(skipped)) => {
+          log::warn!("dropped {skipped} agent health changes");
+        }
+        Err(RecvError::Closed) => return,
+      }
+    }
+  });
+}
+
+pub fn forward_conversation_updates(app: AppHandle, chat: &ChatServer) {
+  let mut updates = chat.subscribe();
+  tauri::async_runtime::spawn(async move {
+    loop {
+      match updates.recv().await {
+        Ok(conversation) => {
+          let conversation = ChatConversation::from(conversation);
+          if let Err(err) = app.emit(CONVERSATION_UPDATED, conversation) {
+            log::warn!("failed to emit {CONVERSATION_UPDATED}: {err}");
+          }
+        }
+        Err(RecvError::Lagged(skipped)) => {
+          log::warn!("dropped {skipped} conversation updates");
+        }
+        Err(RecvError::Closed) => return,
+      }
+    }
+  });
+}
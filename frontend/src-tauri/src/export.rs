@@ -0,0 +1,505 @@
+//! Exporting a conversation, saved wherever the user picks in a native
+//! dialog: Markdown to read or paste elsewhere, a self-contained HTML page
+//! with images embedded, or the lossless JSON archive of
+//! [`crate::history::archive`] that import reads back. Markdown and HTML
+//! show the active branch; the archive keeps every branch.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+use crate::chat::{chat_store, now_ms};
+use crate::error::{Error, Result};
+use crate::history::archive::{Archive, ArchivedAttachment, ArchivedConversation};
+use crate::history::Attachment;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+  Markdown,
+  Html,
+  Json,
+}
+
+impl ExportFormat {
+  fn extension(self) -> &'static str {
+    match self {
+      ExportFormat::Markdown => "md",
+      ExportFormat::Html => "html",
+      ExportFormat::Json => "json",
+    }
+  }
+
+  /// Whether the export carries the content of `file`: the archive keeps
+  /// every file, the web page shows images inline and Markdown names files
+  /// only.
+  fn embeds(self, file: &Attachment) -> bool {
+    match self {
+      ExportFormat::Markdown => false,
+      ExportFormat::Html => file.mime_type.starts_with("image/"),
+      ExportFormat::Json => true,
+    }
+  }
+
+  fn description(self) -> &'static str {
+    match self {
+      ExportFormat::Markdown => "Markdown",
+      ExportFormat::Html => "Web page",
+      ExportFormat::Json => "Conversation archive",
+    }
+  }
+}
+
+/// Asks where to save `conversation_id` and writes it there as `format`.
+/// Resolves to the file written, or `null` if the user cancelled.
+#[tauri::command]
+pub async fn export_conversation(
+  app: AppHandle,
+  conversation_id: String,
+  format: ExportFormat,
+) -> Result<Option<PathBuf>> {
+  let store = chat_store(&app)?;
+  let id = conversation_id.clone();
+  // Reads the attached files the format embeds; keep it off the runtime.
+  let conversation = tauri::async_runtime::spawn_blocking(move || {
+    store.export_conversation(&id, |file| format.embeds(file))
+  })
+  .await??
+  .ok_or(Error::UnknownConversation(conversation_id))?;
+
+  let (tx, rx) = oneshot::channel();
+  app
+    .dialog()
+    .file()
+    .set_title("Export conversation")
+    .set_file_name(format!(
+      "{}.{}",
+      file_stem(&conversation.title),
+      format.extension()
+    ))
+    .add_filter(format.description(), &[format.extension()])
+    .save_file(move |path| {
+      let _ = tx.send(path);
+    });
+  // A dialog closed without answering counts as cancelled.
+  let Some(path) = rx
+    .await
+    .ok()
+    .flatten()
+    .and_then(|path| path.as_path().map(PathBuf::from))
+  else {
+    return Ok(None);
+  };
+
+  let target = path.clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    let contents = match format {
+      ExportFormat::Markdown => markdown(&conversation).into_bytes(),
+      ExportFormat::Html => html(&conversation).into_bytes(),
+      ExportFormat::Json => json(conversation),
+    };
+    std::fs::write(&target, contents)
+  })
+  .await?
+  .map_err(|err| Error::Export(path.clone(), err))?;
+  Ok(Some(path))
+}
+
+/// The conversation as an [`Archive`] of one.
+fn json(conversation: ArchivedConversation) -> Vec<u8> {
+  let archive = Archive::new(vec![conversation], now_ms());
+  // Plain data; serializing it cannot fail.
+  serde_json::to_vec_pretty(&archive).unwrap_or_default()
+}
+
+fn markdown(conversation: &ArchivedConversation) -> String {
+  let messages = conversation.active_branch();
+  let mut out = String::new();
+  let _ = writeln!(out, "# {}\n", title(conversation));
+  let _ = writeln!(
+    out,
+    "_Exported {} · {} messages_",
+    format_time(now_ms()),
+    messages.len()
+  );
+  for message in messages {
+    let _ = writeln!(
+      out,
+      "\n---\n\n**{}** · {}\n",
+      sender_label(&message.sender),
+      format_time(message.created_at)
+    );
+    let _ = writeln!(out, "{}", message.text.trim_end());
+    let notes = notes(message.confidence, message.needs_clarification);
+    if !notes.is_empty() {
+      let _ = writeln!(out, "\n_{}_", notes.join(" · "));
+    }
+    let others = unfiled_attachments(&message.attachments, &message.files);
+    if !message.files.is_empty() || !others.is_empty() {
+      let _ = writeln!(out, "\nAttachments:");
+      for file in &message.files {
+        let _ = writeln!(
+          out,
+          "- {} ({}, {})",
+          file.filename,
+          file.mime_type,
+          format_size(file.size)
+        );
+      }
+      for other in others {
+        let _ = writeln!(out, "- {other}");
+      }
+    }
+  }
+  out
+}
+
+const HTML_STYLE: &str = "
+  body { font: 15px/1.5 system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; color: #1f2328; }
+  header p, .meta, .notes { color: #656d76; font-size: 0.85em; }
+  .message { border-top: 1px solid #d0d7de; padding: 1rem 0; }
+  .sender { font-weight: 600; margin-right: 0.5em; }
+  .user .sender { color: #0969da; }
+  .text { white-space: pre-wrap; overflow-wrap: anywhere; margin: 0.5rem 0; }
+  .attachments { margin: 0.5rem 0; padding-left: 1.2rem; }
+  figure { margin: 0.5rem 0; }
+  img { max-width: 100%; border-radius: 4px; }
+  figcaption { color: #656d76; font-size: 0.85em; }
+";
+
+fn html(conversation: &ArchivedConversation) -> String {
+  let messages = conversation.active_branch();
+  let title = escape(&title(conversation));
+  let mut out = String::new();
+  let _ = write!(
+    out,
+    "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+     <title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n\
+     <header><h1>{title}</h1><p>Exported {} · {} messages</p></header>\n",
+    format_time(now_ms()),
+    messages.len()
+  );
+  for message in messages {
+    let _ = write!(
+      out,
+      "<section class=\"message {}\">\n<div class=\"meta\"><span class=\"sender\">{}</span>\
+       <time>{}</time></div>\n<div class=\"text\">{}</div>\n",
+      escape(&message.sender),
+      escape(&sender_label(&message.sender)),
+      format_time(message.created_at),
+      escape(message.text.trim_end())
+    );
+    let notes = notes(message.confidence, message.needs_clarification);
+    if !notes.is_empty() {
+      let _ = writeln!(out, "<p class=\"notes\">{}</p>", notes.join(" · "));
+    }
+    let mut listed = Vec::new();
+    for file in &message.files {
+      match (&file.data, file.mime_type.starts_with("image/")) {
+        (Some(data), true) => {
+          let _ = writeln!(
+            out,
+            "<figure><img src=\"data:{};base64,{data}\" alt=\"{name}\">\
+             <figcaption>{name}</figcaption></figure>",
+            escape(&file.mime_type),
+            name = escape(&file.filename)
+          );
+        }
+        _ => listed.push(format!(
+          "{} ({}, {})",
+          file.filename,
+          file.mime_type,
+          format_size(file.size)
+        )),
+      }
+    }
+    listed.extend(
+      unfiled_attachments(&message.attachments, &message.files)
+        .into_iter()
+        .map(String::from),
+    );
+    if !listed.is_empty() {
+      out.push_str("<ul class=\"attachments\">\n");
+      for item in listed {
+        let _ = writeln!(out, "<li>{}</li>", escape(&item));
+      }
+      out.push_str("</ul>\n");
+    }
+    out.push_str("</section>\n");
+  }
+  out.push_str("</body>\n</html>\n");
+  out
+}
+
+fn title(conversation: &ArchivedConversation) -> String {
+  match conversation.title.trim() {
+    "" => "Untitled conversation".into(),
+    title => title.into(),
+  }
+}
+
+/// A file name for `title` without characters that file systems reject.
+fn file_stem(title: &str) -> String {
+  let stem: String = title
+    .chars()
+    .map(|c| match c {
+      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+      c if c.is_control() => ' ',
+      c => c,
+    })
+    .collect();
+  match stem.trim().trim_matches('.') {
+    "" => "conversation".into(),
+    stem => stem.into(),
+  }
+}
+
+fn sender_label(sender: &str) -> String {
+  match sender {
+    "user" => "You".into(),
+    "agent" => "Assistant".into(),
+    other => other.into(),
+  }
+}
+
+fn notes(confidence: Option<f64>, needs_clarification: bool) -> Vec<String> {
+  let mut notes = Vec::new();
+  if let Some(confidence) = confidence {
+    notes.push(format!("Confidence {:.0}%", confidence * 100.0));
+  }
+  if needs_clarification {
+    notes.push("Needs clarification".into());
+  }
+  notes
+}
+
+/// Entries of `attachments_json` with no attachment row, such as paths
+/// written by older versions.
+fn unfiled_attachments<'a>(
+  attachments: &'a [String],
+  files: &[ArchivedAttachment],
+) -> Vec<&'a str> {
+  attachments
+    .iter()
+    .filter(|entry| !files.iter().any(|file| &file.id == *entry))
+    .map(String::as_str)
+    .collect()
+}
+
+fn escape(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&#39;"),
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+fn format_size(bytes: i64) -> String {
+  const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+  if bytes < 1024 {
+    return format!("{bytes} B");
+  }
+  let mut size = bytes as f64 / 1024.0;
+  let mut unit = 0;
+  // Compare what gets printed, so 1023.96 KB reads 1.0 MB, not 1024.0 KB.
+  while (size * 10.0).round() >= 10_240.0 && unit < UNITS.len() - 1 {
+    size /= 1024.0;
+    unit += 1;
+  }
+  format!("{size:.1} {}", UNITS[unit])
+}
+
+/// `ms` since the epoch as `YYYY-MM-DD HH:MM UTC`.
+fn format_time(ms: i64) -> String {
+  let secs = ms.div_euclid(1000);
+  let (days, secs) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+  // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z.rem_euclid(146_097);
+  let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+  format!(
+    "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+    secs / 3600,
+    secs % 3600 / 60
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  /// A question answered twice; the second answer is shown, with an image
+  /// and a document attached to the question.
+  fn conversation() -> ArchivedConversation {
+    serde_json::from_value(json!({
+      "id": "c1",
+      "title": "<b>Cats & \"dogs\"</b>",
+      "created_at": 0,
+      "active_leaf_id": "a2",
+      "messages": [
+        {
+          "id": "q1",
+          "sender": "user",
+          "text": "Which is <better>?",
+          "created_at": 1_000,
+          "attachments": ["f1", "f2", "old/path.txt"],
+          "files": [
+            {
+              "id": "f1",
+              "filename": "<cat>.png",
+              "mime_type": "image/png",
+              "created_at": 1_000,
+              "size": 2_048,
+              "data": "aGk="
+            },
+            {
+              "id": "f2",
+              "filename": "notes & \"plans\".txt",
+              "mime_type": "text/plain",
+              "created_at": 1_000,
+              "size": 12,
+              "data": "aGk="
+            }
+          ]
+        },
+        {
+          "id": "a1",
+          "parent_id": "q1",
+          "sender": "agent",
+          "text": "First answer",
+          "created_at": 2_000
+        },
+        {
+          "id": "a2",
+          "parent_id": "q1",
+          "sender": "agent",
+          "text": "Second answer",
+          "created_at": 3_000,
+          "confidence": 0.875,
+          "needs_clarification": true
+        }
+      ]
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn formats_times_in_utc() {
+    for (ms, expected) in [
+      (0, "1970-01-01 00:00 UTC"),
+      (59_999, "1970-01-01 00:00 UTC"),
+      (951_782_400_000, "2000-02-29 00:00 UTC"),
+      (1_709_210_040_000, "2024-02-29 12:34 UTC"),
+      (1_709_251_199_999, "2024-02-29 23:59 UTC"),
+      (-1, "1969-12-31 23:59 UTC"),
+      (-86_400_000, "1969-12-31 00:00 UTC"),
+      (-86_400_001, "1969-12-30 23:59 UTC"),
+    ] {
+      assert_eq!(format_time(ms), expected, "{ms}");
+    }
+  }
+
+  #[test]
+  fn file_stems_avoid_reserved_characters() {
+    for (title, expected) in [
+      ("Trip notes", "Trip notes"),
+      ("a/b\\c:d*e?f\"g<h>i|j", "a-b-c-d-e-f-g-h-i-j"),
+      ("tab\there\nnow", "tab here now"),
+      ("  .hidden.  ", "hidden"),
+      ("...", "conversation"),
+      ("", "conversation"),
+      ("   ", "conversation"),
+    ] {
+      assert_eq!(file_stem(title), expected, "{title:?}");
+    }
+  }
+
+  #[test]
+  fn escapes_html() {
+    assert_eq!(
+      escape("<a href=\"x\">Tom & Jerry's</a>"),
+      "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+    );
+    assert_eq!(escape("plain"), "plain");
+    assert_eq!(escape("&amp;"), "&amp;amp;");
+  }
+
+  #[test]
+  fn sizes_change_unit_at_1024() {
+    for (bytes, expected) in [
+      (0, "0 B"),
+      (1_023, "1023 B"),
+      (1_024, "1.0 KB"),
+      (1_536, "1.5 KB"),
+      (1_048_575, "1.0 MB"),
+      (1_048_576, "1.0 MB"),
+      (1_073_741_824, "1.0 GB"),
+      (1_099_511_627_776, "1.0 TB"),
+      (1_125_899_906_842_624, "1024.0 TB"),
+    ] {
+      assert_eq!(format_size(bytes), expected, "{bytes}");
+    }
+  }
+
+  #[test]
+  fn markdown_shows_the_active_branch() {
+    let out = markdown(&conversation());
+    assert!(out.starts_with("# <b>Cats & \"dogs\"</b>\n"));
+    assert!(out.contains("_Exported "));
+    assert!(out.contains(" · 2 messages_"));
+    assert!(out.contains("Which is <better>?"));
+    assert!(out.contains("Second answer"));
+    assert!(!out.contains("First answer"));
+    assert!(out.contains("**Assistant** · 1970-01-01 00:00 UTC"));
+    assert!(out.contains("_Confidence 88% · Needs clarification_"));
+    assert!(out.contains("- <cat>.png (image/png, 2.0 KB)\n"));
+    assert!(out.contains("- notes & \"plans\".txt (text/plain, 12 B)\n"));
+    assert!(out.contains("- old/path.txt\n"));
+    assert!(!out.contains("aGk="));
+  }
+
+  #[test]
+  fn html_shows_the_active_branch_escaped() {
+    let out = html(&conversation());
+    assert!(out.contains("<title>&lt;b&gt;Cats &amp; &quot;dogs&quot;&lt;/b&gt;</title>"));
+    assert!(out.contains(" · 2 messages</p>"));
+    assert!(out.contains("Which is &lt;better&gt;?"));
+    assert!(out.contains("Second answer"));
+    assert!(!out.contains("First answer"));
+    assert!(!out.contains("<better>"));
+    assert!(out.contains(
+      "<img src=\"data:image/png;base64,aGk=\" alt=\"&lt;cat&gt;.png\">\
+       <figcaption>&lt;cat&gt;.png</figcaption>"
+    ));
+    assert!(out.contains("<li>notes &amp; &quot;plans&quot;.txt (text/plain, 12 B)</li>"));
+    assert!(out.contains("<li>old/path.txt</li>"));
+    assert!(out.contains("<p class=\"notes\">Confidence 88% · Needs clarification</p>"));
+  }
+
+  #[test]
+  fn untitled_conversations_get_a_title() {
+    let mut untitled = conversation();
+    untitled.title = "  ".into();
+    assert!(markdown(&untitled).starts_with("# Untitled conversation\n"));
+    assert!(html(&untitled).contains("<title>Untitled conversation</title>"));
+  }
+}
@@ -0,0 +1,257 @@
+//! Typed gRPC clients for the Python backend and the agent manager.
+//!
+//! A single [`Backend`] is managed as Tauri state. For each server it owns
+//! the endpoint and lazily opens one HTTP/2 channel that every client
+//! shares; tonic channels are cheap to clone and reconnect on their own
+//! after a transport failure.
+//!
+//! Every client attaches the per-launch secret (see [`crate::auth`]).
+//!
+//! `ChatService` has a connection of its own: it follows the backend unless
+//! the shell hosts chat itself (see [`Backend::use_native_chat`]). The
+//! calls `backend/server.py` does not implement, from listing conversations
+//! to regenerating a reply, go to the shell whenever it has a chat store,
+//! whichever server hosts chat (see [`Backend::history`]).
+//!
+//! Either server may sit on a TCP port or a Unix socket (see
+//! [`crate::transport`]). The endpoints can change at runtime: the
+//! supervised backend binds afresh on every launch and reports where
+//! through [`Backend::retarget`]. Until it has, and again once it exits,
+//! backend clients fail with [`ConnectError::NotReady`] instead of dialing
+//! an address some other process may own.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use tokio::sync::OnceCell;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Channel, Error};
+use tonic_health::pb::health_client::HealthClient;
+
+use crate::auth::AttachToken;
+use crate::config::Config;
+use crate::proto::chat::chat_service_client::ChatServiceClient;
+use crate::proto::generation::generation_agent_client::GenerationAgentClient;
+use crate::proto::manager::agent_manager_client::AgentManagerClient;
+use crate::proto::transcription::transcription_agent_client::TranscriptionAgentClient;
+use crate::proto::vision::vision_agent_client::VisionAgentClient;
+use crate::transport::Address;
+
+/// Channel that authenticates each call.
+pub type AuthChannel = InterceptedService<Channel, AttachToken>;
+
+/// Why no client could be made.
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectError {
+  /// The server has not reported where it listens yet, or has exited.
+  #[error("backend is not ready")]
+  NotReady,
+  #[error(transparent)]
+  Transport(#[from] Error),
+}
+
+struct Connection {
+  /// `None` while the server's address is unknown.
+  target: RwLock<Option<Target>>,
+}
+
+#[derive(Clone)]
+struct Target {
+  address: Address,
+  channel: Arc<OnceCell<Channel>>,
+}
+
+impl Connection {
+  fn new(uri: Option<String>) -> Result<Self, Error> {
+    let target = uri.map(Address::parse).transpose()?.map(Target::new);
+    Ok(Self {
+      target: RwLock::new(target),
+    })
+  }
+
+  fn address(&self) -> Option<Address> {
+    Some(self.target.read().unwrap().as_ref()?.address.clone())
+  }
+
+  /// Points the connection at `address`, or nowhere; clients created
+  /// afterwards use a new channel, existing ones keep the old.
+  fn retarget(&self, address: Option<Address>) {
+    *self.target.write().unwrap() = address.map(Target::new);
+  }
+
+  /// Returns the shared channel, connecting on first use.
+  ///
+  /// A failed connect is not cached, so the next call tries again.
+  async fn channel(&self) -> Result<Channel, ConnectError> {
+    let target = self
+      .target
+      .read()
+      .unwrap()
+      .clone()
+      .ok_or(ConnectError::NotReady)?;
+    let channel = target
+      .channel
+      .get_or_try_init(|| target.address.connect())
+      .await?;
+    Ok(channel.clone())
+  }
+}
+
+impl Target {
+  fn new(address: Address) -> Self {
+    Self {
+      address,
+      channel: Arc::new(OnceCell::new()),
+    }
+  }
+}
+
+/// Cloning is cheap; clones share the connections and see each other's
+/// retargeting.
+#[derive(Clone)]
+pub struct Backend {
+  backend: Arc<Connection>,
+  chat: Arc<Connection>,
+  /// The shell's own `ChatService`, once it has a chat store.
+  history: Arc<Connection>,
+  manager: Arc<Connection>,
+  native_chat: Arc<AtomicBool>,
+  auth: AttachToken,
+}
+
+impl Backend {
+  pub fn new(config: &Config) -> Result<Self, Error> {
+    Ok(Self {
+      backend: Arc::new(Connection::new(config.backend_addr.clone())?),
+      chat: Arc::new(Connection::new(config.backend_addr.clone())?),
+      history: Arc::new(Connection::new(None)?),
+      manager: Arc::new(Connection::new(Some(format!(
+        "http://{}",
+        config.manager_addr
+      )))?),
+      native_chat: Arc::new(AtomicBool::new(false)),
+      auth: AttachToken::new(&config.auth_token),
+    })
+  }
+
+  /// Where the backend listens; `None` until it is known.
+  pub fn address(&self) -> Option<Address> {
+    self.backend.address()
+  }
+
+  /// Switches the backend clients to `uri`, e.g. `http://127.0.0.1:54321`
+  /// or `unix:/path/to/backend.sock`.
+  pub fn retarget(&self, uri: String) -> Result<(), Error> {
+    log::info!("backend endpoint is now {uri}");
+    let address = Address::parse(uri)?;
+    if !self.native_chat.load(Ordering::Relaxed) {
+      self.chat.retarget(Some(address.clone()));
+    }
+    self.backend.retarget(Some(address));
+    Ok(())
+  }
+
+  /// Forgets the backend's endpoint once it has exited, until it reports a
+  /// new one through [`Backend::retarget`].
+  pub fn detach(&self) {
+    log::info!("backend endpoint is unknown until it is ready again");
+    if !self.native_chat.load(Ordering::Relaxed) {
+      self.chat.retarget(None);
+    }
+    self.backend.retarget(None);
+  }
+
+  /// Sends `ChatService` calls to the shell's own server at `uri` from now
+  /// on, whatever the backend does.
+  pub fn use_native_chat(&self, uri: String) -> Result<(), Error> {
+    log::info!("chat is served by the shell at {uri}");
+    self.native_chat.store(true, Ordering::Relaxed);
+    self.chat.retarget(Some(Address::parse(uri)?));
+    Ok(())
+  }
+
+  /// Sends the calls [`Backend::history`] covers to the shell's own server
+  /// at `uri`, whichever server hosts the rest of chat.
+  pub fn use_native_history(&self, uri: String) -> Result<(), Error> {
+    self.history.retarget(Some(Address::parse(uri)?));
+    Ok(())
+  }
+
+  /// Switches the agent manager clients to `uri`, once the manager knows
+  /// where it listens.
+  pub fn retarget_manager(&self, uri: String) -> Result<(), Error> {
+    self.manager.retarget(Some(Address::parse(uri)?));
+    Ok(())
+  }
+
+  pub async fn chat(&self) -> Result<ChatServiceClient<AuthChannel>, ConnectError> {
+    Ok(ChatServiceClient::with_interceptor(
+      self.chat.channel().await?,
+      self.auth.clone(),
+    ))
+  }
+
+  /// `ChatService` client for the calls only the shell implements: listing,
+  /// renaming, archiving, deleting and searching conversations, switching
+  /// branches and regenerating replies. Falls back to the chat connection
+  /// when the shell has no chat store.
+  pub async fn history(&self) -> Result<ChatServiceClient<AuthChannel>, ConnectError> {
+    let channel = match self.history.channel().await {
+      Err(ConnectError::NotReady) => self.chat.channel().await?,
+      channel => channel?,
+    };
+    Ok(ChatServiceClient::with_interceptor(
+      channel,
+      self.auth.clone(),
+    ))
+  }
+
+  pub async fn chat_health(&self) -> Result<HealthClient<AuthChannel>, ConnectError> {
+    Ok(HealthClient::with_interceptor(
+      self.chat.channel().await?,
+      self.auth.clone(),
+    ))
+  }
+
+  pub async fn backend_health(&self) -> Result<HealthClient<AuthChannel>, ConnectError> {
+    Ok(HealthClient::with_interceptor(
+      self.backend.channel().await?,
+      self.auth.clone(),
+    ))
+  }
+
+  pub async fn manager_health(&self) -> Result<HealthClient<AuthChannel>, ConnectError> {
+    Ok(HealthClient::with_interceptor(
+      self.manager.channel().await?,
+      self.auth.clone(),
+    ))
+  }
+
+  pub async fn agent_manager(&self) -> Result<AgentManagerClient<AuthChannel>, ConnectError> {
+    Ok(AgentManagerClient::with_interceptor(
+      self.manager.channel().await?,
+      self.auth.clone(),
+    ))
+  }
+
+  pub async fn transcription(&self) -> Result<TranscriptionAgentClient<AuthChannel>, ConnectError> {
+    Ok(TranscriptionAgentClient::with_interceptor(
+      self.backend.channel().await?,
+      self.auth.clone(),
+    ))
+  }
+
+  pub async fn vision(&self) -> Result<VisionAgentClient<AuthChannel>, ConnectError> {
+    Ok(VisionAgentClient::with_interceptor(
+      self.backend.channel().await?,
+      self.auth.clone(),
+    ))
+  }
+
+  pub async fn generation(&self) -> Result<GenerationAgentClient<AuthChannel>, ConnectError> {
+    Ok(GenerationAgentClient::with_interceptor(
+      self.backend.channel().await?,
+      self.auth.clone(),
+    ))
+  }
+}
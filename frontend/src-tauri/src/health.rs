@@ -0,0 +1,172 @@
+//! Polls `grpc.health.v1.Health` on the backend and the agent manager and
+//! reports a combined [`BackendStatus`] to the webview.
+//!
+//! Whichever server hosts chat answers for `ChatService`, the backend for
+//! whether each agent service has its models; the agent manager answers
+//! for itself and for whether an agent of each type is connected.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager as _, State};
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+
+use crate::error::Result;
+use crate::events::BACKEND_STATUS;
+use crate::grpc::{AuthChannel, Backend, ConnectError};
+use crate::manager::AGENT_SERVICES;
+use crate::proto::chat::chat_service_server;
+use crate::proto::manager::agent_manager_server;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Payload of the `backend_status` command and the [`BACKEND_STATUS`]
+/// event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackendStatus {
+  pub state: BackendState,
+  pub services: Vec<ServiceStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendState {
+  /// Not checked yet.
+  Starting,
+  Up,
+  /// Chat works, but some agent service or the agent manager does not.
+  Degraded,
+  /// `ChatService` is unreachable or not serving.
+  Down,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceStatus {
+  /// Fully qualified gRPC service name, e.g. `videoanalyzer.chat.ChatService`.
+  pub service: String,
+  pub status: Check,
+  /// Why the service is not serving, when known.
+  pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+  Serving,
+  NotServing,
+  /// The server does not know the service.
+  Unknown,
+  /// The health check itself failed.
+  Unreachable,
+}
+
+/// Latest status, managed as Tauri state.
+pub struct HealthMonitor {
+  current: Mutex<BackendStatus>,
+}
+
+impl Default for HealthMonitor {
+  fn default() -> Self {
+    Self {
+      current: Mutex::new(BackendStatus {
+        state: BackendState::Starting,
+        services: Vec::new(),
+      }),
+    }
+  }
+}
+
+#[tauri::command]
+pub fn backend_status(monitor: State<'_, HealthMonitor>) -> BackendStatus {
+  monitor.current.lock().unwrap().clone()
+}
+
+/// Polls health forever, storing the result in [`HealthMonitor`] and
+/// emitting [`BACKEND_STATUS`] whenever it changes.
+pub async fn monitor(app: AppHandle) {
+  let mut ticker = tokio::time::interval(POLL_INTERVAL);
+  loop {
+    ticker.tick().await;
+    let status = poll(&app.state::<Backend>()).await;
+    let monitor = app.state::<HealthMonitor>();
+    let mut current = monitor.current.lock().unwrap();
+    if *current == status {
+      continue;
+    }
+    log::info!("backend is {:?}", status.state);
+    *current = status.clone();
+    drop(current);
+    if let Err(err) = app.emit(BACKEND_STATUS, status) {
+      log::warn!("failed to emit {BACKEND_STATUS}: {err}");
+    }
+  }
+}
+
+async fn poll(backend: &Backend) -> BackendStatus {
+  let mut chat_health = backend.chat_health().await;
+  let mut backend_health = backend.backend_health().await;
+  let mut manager_health = backend.manager_health().await;
+
+  let chat = check(&mut chat_health, chat_service_server::SERVICE_NAME).await;
+  let manager = check(&mut manager_health, agent_manager_server::SERVICE_NAME).await;
+  let mut services = vec![chat.clone(), manager.clone()];
+  for (_, service) in AGENT_SERVICES {
+    let models = check(&mut backend_health, service).await;
+    let agents = check(&mut manager_health, service).await;
+    services.push(match (models.status, agents.status) {
+      (Check::Serving, Check::Serving) => models,
+      (Check::Serving, _) => ServiceStatus {
+        detail: Some("no agent connected".into()),
+        ..agents
+      },
+      _ => ServiceStatus {
+        detail: Some("models missing".into()),
+        ..models
+      },
+    });
+  }
+
+  let state = if chat.status != Check::Serving {
+    BackendState::Down
+  } else if services
+    .iter()
+    .all(|service| service.status == Check::Serving)
+  {
+    BackendState::Up
+  } else {
+    BackendState::Degraded
+  };
+  BackendStatus { state, services }
+}
+
+async fn check(
+  client: &mut Result<HealthClient<AuthChannel>, ConnectError>,
+  service: &str,
+) -> ServiceStatus {
+  let status = |status, detail: Option<String>| ServiceStatus {
+    service: service.to_string(),
+    status,
+    detail,
+  };
+  let client = match client {
+    Ok(client) => client,
+    Err(err) => return status(Check::Unreachable, Some(err.to_string())),
+  };
+  let request = HealthCheckRequest {
+    service: service.to_string(),
+  };
+  match tokio::time::timeout(CHECK_TIMEOUT, client.check(request)).await {
+    Err(_) => status(Check::Unreachable, Some("health check timed out".into())),
+    Ok(Err(err)) if err.code() == tonic::Code::NotFound => status(Check::Unknown, None),
+    Ok(Err(err)) => status(Check::Unreachable, Some(err.message().to_string())),
+    Ok(Ok(response)) => match response.into_inner().status() {
+      ServingStatus::Serving => status(Check::Serving, None),
+      ServingStatus::NotServing => status(Check::NotServing, None),
+      ServingStatus::Unknown | ServingStatus::ServiceUnknown => status(Check::Unknown, None),
+    },
+  }
+}
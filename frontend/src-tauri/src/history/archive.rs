@@ -0,0 +1,341 @@
+//! The lossless JSON form of conversations, written by export and read by
+//! import.
+//!
+//! Every column of `conversations`, `messages` and `attachments` is kept as
+//! stored, including `metadata_json` and `attachments_json` verbatim, and
+//! attached files travel inside the archive as base64, so an archive moved
+//! to another machine restores the same history.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+/// Value of [`Archive::format`].
+pub const ARCHIVE_FORMAT: &str = "video-analyzer.conversations";
+/// Newest archive version this build writes and reads. Version 2 added
+/// branches; version 1 archives hold one linear branch.
+pub const ARCHIVE_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Archive {
+  pub format: String,
+  pub version: u32,
+  /// Unix ms.
+  pub exported_at: i64,
+  pub conversations: Vec<ArchivedConversation>,
+}
+
+impl Archive {
+  pub fn new(conversations: Vec<ArchivedConversation>, exported_at: i64) -> Self {
+    Self {
+      format: ARCHIVE_FORMAT.into(),
+      version: ARCHIVE_VERSION,
+      exported_at,
+      conversations,
+    }
+  }
+
+  /// Parses an archive, refusing other formats, newer versions and
+  /// archives whose ids do not hang together.
+  pub fn from_slice(bytes: &[u8]) -> Result<Self, ArchiveError> {
+    let mut archive: Self = serde_json::from_slice(bytes)?;
+    if archive.format != ARCHIVE_FORMAT {
+      return Err(ArchiveError::UnknownFormat(archive.format));
+    }
+    if archive.version > ARCHIVE_VERSION {
+      return Err(ArchiveError::TooNew {
+        found: archive.version,
+        supported: ARCHIVE_VERSION,
+      });
+    }
+    if archive.version < 2 {
+      for conversation in &mut archive.conversations {
+        let mut previous = None;
+        for message in &mut conversation.messages {
+          message.parent_id = previous.replace(message.id.clone());
+        }
+      }
+    }
+    archive.validate()?;
+    Ok(archive)
+  }
+
+  /// Ids must be present and unique within the archive, and a message may
+  /// only follow an earlier one of its conversation; clashes with what is
+  /// already stored are resolved on import instead.
+  fn validate(&self) -> Result<(), ArchiveError> {
+    let mut conversations = HashSet::new();
+    let mut messages = HashSet::new();
+    let mut files = HashSet::new();
+    for conversation in &self.conversations {
+      if conversation.id.is_empty() {
+        return Err(ArchiveError::Invalid("a conversation has no id".into()));
+      }
+      if !conversations.insert(conversation.id.as_str()) {
+        return Err(ArchiveError::Invalid(format!(
+          "conversation {} appears twice",
+          conversation.id
+        )));
+      }
+      let mut earlier = HashSet::new();
+      for message in &conversation.messages {
+        if message.id.is_empty() || message.sender.is_empty() {
+          return Err(ArchiveError::Invalid(format!(
+            "a message in conversation {} has no id or sender",
+            conversation.id
+          )));
+        }
+        if !messages.insert(message.id.as_str()) {
+          return Err(ArchiveError::Invalid(format!(
+            "message {} appears twice",
+            message.id
+          )));
+        }
+        if let Some(parent) = &message.parent_id {
+          if !earlier.contains(parent.as_str()) {
+            return Err(ArchiveError::Invalid(format!(
+              "message {} follows {parent}, which is not an earlier message of its conversation",
+              message.id
+            )));
+          }
+        }
+        earlier.insert(message.id.as_str());
+        if !message.metadata_json.is_empty()
+          && serde_json::from_str::<serde_json::Value>(&message.metadata_json).is_err()
+        {
+          return Err(ArchiveError::Invalid(format!(
+            "message {} has metadata that is not JSON",
+            message.id
+          )));
+        }
+        for file in &message.files {
+          if file.id.is_empty() || !files.insert(file.id.as_str()) {
+            return Err(ArchiveError::Invalid(format!(
+              "message {} has an attachment with a missing or repeated id",
+              message.id
+            )));
+          }
+        }
+      }
+      if let Some(leaf) = &conversation.active_leaf_id {
+        if !earlier.contains(leaf.as_str()) {
+          return Err(ArchiveError::Invalid(format!(
+            "conversation {} shows message {leaf}, which it does not contain",
+            conversation.id
+          )));
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+  #[error("not a conversation archive: {0}")]
+  Malformed(#[from] serde_json::Error),
+  #[error("not a conversation archive (format {0:?})")]
+  UnknownFormat(String),
+  #[error("archive version {found} is newer than this app supports ({supported}); update the app")]
+  TooNew { found: u32, supported: u32 },
+  #[error("invalid archive: {0}")]
+  Invalid(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedConversation {
+  pub id: String,
+  pub title: String,
+  pub created_at: i64,
+  #[serde(default)]
+  pub archived_at: Option<i64>,
+  /// `"user"` or `"auto"`; absent while untitled.
+  #[serde(default)]
+  pub title_source: Option<String>,
+  /// Last message of the branch shown; absent before version 2.
+  #[serde(default)]
+  pub active_leaf_id: Option<String>,
+  /// Every branch, oldest first, so parents come before their replies.
+  pub messages: Vec<ArchivedMessage>,
+}
+
+impl ArchivedConversation {
+  /// Messages of the branch shown, oldest first; all of them if the
+  /// archive does not say.
+  pub fn active_branch(&self) -> Vec<&ArchivedMessage> {
+    let Some(leaf) = &self.active_leaf_id else {
+      return self.messages.iter().collect();
+    };
+    let by_id: HashMap<&str, &ArchivedMessage> = self
+      .messages
+      .iter()
+      .map(|message| (message.id.as_str(), message))
+      .collect();
+    let mut branch = Vec::new();
+    let mut next = Some(leaf.as_str());
+    while let Some(message) = next.and_then(|id| by_id.get(id)) {
+      branch.push(*message);
+      next = message.parent_id.as_deref();
+    }
+    branch.reverse();
+    branch
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedMessage {
+  pub id: String,
+  /// The message this one follows; absent for the first of a branch.
+  #[serde(default)]
+  pub parent_id: Option<String>,
+  pub sender: String,
+  pub text: String,
+  pub created_at: i64,
+  #[serde(default)]
+  pub confidence: Option<f64>,
+  #[serde(default)]
+  pub needs_clarification: bool,
+  /// `messages.attachments_json` as stored: attachment ids, or paths from
+  /// older versions.
+  #[serde(default)]
+  pub attachments: Vec<String>,
+  #[serde(default)]
+  pub metadata_json: String,
+  /// Rows of the `attachments` table belonging to this message.
+  #[serde(default)]
+  pub files: Vec<ArchivedAttachment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedAttachment {
+  pub id: String,
+  pub filename: String,
+  pub mime_type: String,
+  pub created_at: i64,
+  pub size: i64,
+  /// Hex SHA-256 of the content; empty for rows from before attachments
+  /// were hashed.
+  #[serde(default)]
+  pub sha256: String,
+  /// The content, base64; absent if the file was missing at export time.
+  #[serde(default)]
+  pub data: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::{json, Value};
+
+  use super::*;
+
+  fn message(id: &str, parent_id: Option<&str>) -> Value {
+    json!({
+      "id": id,
+      "parent_id": parent_id,
+      "sender": "user",
+      "text": id,
+      "created_at": 1,
+    })
+  }
+
+  fn archive(version: u32, messages: Vec<Value>, active_leaf_id: Option<&str>) -> Vec<u8> {
+    let archive = json!({
+      "format": ARCHIVE_FORMAT,
+      "version": version,
+      "exported_at": 1,
+      "conversations": [{
+        "id": "c1",
+        "title": "",
+        "created_at": 1,
+        "active_leaf_id": active_leaf_id,
+        "messages": messages,
+      }],
+    });
+    serde_json::to_vec(&archive).unwrap()
+  }
+
+  fn invalid(bytes: &[u8]) -> String {
+    match Archive::from_slice(bytes) {
+      Err(ArchiveError::Invalid(reason)) => reason,
+      other => panic!("expected an invalid archive, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn version_1_reads_as_one_linear_branch() {
+    // Version 1 had no parents; any that appear are ignored.
+    let messages = vec![
+      message("a", None),
+      message("b", Some("c")),
+      message("c", None),
+    ];
+    let archive = Archive::from_slice(&archive(1, messages, None)).unwrap();
+    let conversation = &archive.conversations[0];
+    let parents: Vec<_> = conversation
+      .messages
+      .iter()
+      .map(|message| message.parent_id.as_deref())
+      .collect();
+    assert_eq!(parents, [None, Some("a"), Some("b")]);
+    let branch: Vec<_> = conversation
+      .active_branch()
+      .iter()
+      .map(|message| message.id.as_str())
+      .collect();
+    assert_eq!(branch, ["a", "b", "c"]);
+  }
+
+  #[test]
+  fn active_branch_follows_the_leaf() {
+    let messages = vec![
+      message("a", None),
+      message("b", Some("a")),
+      message("b2", Some("a")),
+      message("c", Some("b")),
+    ];
+    let archive = Archive::from_slice(&archive(2, messages, Some("b2"))).unwrap();
+    let branch: Vec<_> = archive.conversations[0]
+      .active_branch()
+      .iter()
+      .map(|message| message.id.as_str())
+      .collect();
+    assert_eq!(branch, ["a", "b2"]);
+  }
+
+  #[test]
+  fn rejects_duplicate_ids() {
+    let messages = vec![message("a", None), message("a", Some("a"))];
+    assert!(invalid(&archive(2, messages, None)).contains("appears twice"));
+
+    let mut value: Value = serde_json::from_slice(&archive(2, Vec::new(), None)).unwrap();
+    let conversation = value["conversations"][0].clone();
+    value["conversations"] = json!([conversation.clone(), conversation]);
+    assert!(invalid(&serde_json::to_vec(&value).unwrap()).contains("appears twice"));
+  }
+
+  #[test]
+  fn rejects_dangling_ids() {
+    // Parents must be earlier messages of the same conversation.
+    let messages = vec![message("a", Some("b")), message("b", None)];
+    invalid(&archive(2, messages, None));
+    let messages = vec![message("a", Some("missing"))];
+    invalid(&archive(2, messages, None));
+
+    let messages = vec![message("a", None)];
+    assert!(invalid(&archive(2, messages, Some("missing"))).contains("does not contain"));
+  }
+
+  #[test]
+  fn rejects_other_formats_and_newer_versions() {
+    let mut value: Value = serde_json::from_slice(&archive(2, Vec::new(), None)).unwrap();
+    value["format"] = json!("something.else");
+    assert!(matches!(
+      Archive::from_slice(&serde_json::to_vec(&value).unwrap()),
+      Err(ArchiveError::UnknownFormat(_))
+    ));
+    assert!(matches!(
+      Archive::from_slice(&archive(ARCHIVE_VERSION + 1, Vec::new(), None)),
+      Err(ArchiveError::TooNew { .. })
+    ));
+  }
+}
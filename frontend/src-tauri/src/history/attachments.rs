@@ -0,0 +1,172 @@
+//! Attachment files, stored once per content under `data/attachments`.
+//!
+//! A file is named after the SHA-256 of its bytes
+//! (`attachments/<first two hex digits>/<digest>`), so attaching the same
+//! file twice, or to several messages, keeps one copy. Rows in the
+//! `attachments` table carry the name the user knows it by and point at
+//! the blob; a blob is deleted with the last row referencing it.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Directory under the data directory holding the blobs.
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+/// An attachment row, with its blob resolved to a file the webview can
+/// read. `message_id` is empty until a stored message lists the id.
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+  pub id: String,
+  pub message_id: String,
+  pub filename: String,
+  pub mime_type: String,
+  pub size: i64,
+  pub sha256: String,
+  pub path: PathBuf,
+  pub created_at: i64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AttachmentError {
+  #[error("could not store attachment: {0}")]
+  Io(#[from] io::Error),
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+  #[error("attachment {0} does not match its recorded content")]
+  Corrupt(String),
+}
+
+/// A stored blob.
+pub(super) struct Blob {
+  pub sha256: String,
+  pub size: i64,
+  /// Path relative to the data directory, with `/` separators, as kept in
+  /// `attachments.path`.
+  pub path: String,
+}
+
+/// The blob directory of one data directory.
+pub(super) struct Blobs {
+  data_dir: PathBuf,
+}
+
+impl Blobs {
+  pub fn new(data_dir: &Path) -> Self {
+    Self {
+      data_dir: data_dir.to_path_buf(),
+    }
+  }
+
+  /// Copies `source` into the store, hashing it on the way.
+  pub fn put_file(&self, source: &Path) -> io::Result<Blob> {
+    self.put(File::open(source)?)
+  }
+
+  /// Stores everything `reader` yields, hashing it on the way.
+  pub fn put(&self, mut reader: impl Read) -> io::Result<Blob> {
+    let root = self.data_dir.join(ATTACHMENTS_DIR);
+    std::fs::create_dir_all(&root)?;
+    // Write under a temporary name first; the digest is only known at the
+    // end, and a half-written file must never carry a digest's name.
+    let temp = root.join(format!(".{}.part", uuid::Uuid::new_v4().simple()));
+    let result = (|| {
+      let mut file = File::create(&temp)?;
+      let mut hasher = Sha256::new();
+      let mut size = 0;
+      let mut buffer = vec![0; 64 * 1024];
+      loop {
+        let read = match reader.read(&mut buffer) {
+          Ok(0) => break,
+          Ok(read) => read,
+          Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+          Err(err) => return Err(err),
+        };
+        hasher.update(&buffer[..read]);
+        file.write_all(&buffer[..read])?;
+        size += read as i64;
+      }
+      file.sync_all()?;
+      let sha256 = hex(&hasher.finalize());
+      let path = format!("{ATTACHMENTS_DIR}/{}/{sha256}", &sha256[..2]);
+      let target = self.data_dir.join(&path);
+      if target.exists() {
+        std::fs::remove_file(&temp)?;
+      } else {
+        std::fs::create_dir_all(target.parent().unwrap_or(&root))?;
+        std::fs::rename(&temp, &target)?;
+      }
+      Ok(Blob { sha256, size, path })
+    })();
+    if result.is_err() {
+      let _ = std::fs::remove_file(&temp);
+    }
+    result
+  }
+
+  /// Absolute location of a stored `path`. Rows written by the backend may
+  /// hold absolute paths already.
+  pub fn resolve(&self, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+      path.to_path_buf()
+    } else {
+      self.data_dir.join(path)
+    }
+  }
+
+  /// Deletes the blob at `path` if it is one of ours.
+  pub fn remove(&self, path: &str) {
+    if !path.starts_with(&format!("{ATTACHMENTS_DIR}/")) {
+      return;
+    }
+    match std::fs::remove_file(self.resolve(path)) {
+      Err(err) if err.kind() != io::ErrorKind::NotFound => {
+        log::warn!("could not delete attachment {path}: {err}");
+      }
+      _ => {}
+    }
+  }
+}
+
+/// MIME type for a file name, from its extension.
+pub fn mime_type(filename: &str) -> &'static str {
+  let extension = Path::new(filename)
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or_default()
+    .to_ascii_lowercase();
+  match extension.as_str() {
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "gif" => "image/gif",
+    "webp" => "image/webp",
+    "svg" => "image/svg+xml",
+    "bmp" => "image/bmp",
+    "mp4" | "m4v" => "video/mp4",
+    "mov" => "video/quicktime",
+    "mkv" => "video/x-matroska",
+    "webm" => "video/webm",
+    "avi" => "video/x-msvideo",
+    "mp3" => "audio/mpeg",
+    "wav" => "audio/wav",
+    "m4a" => "audio/mp4",
+    "ogg" => "audio/ogg",
+    "flac" => "audio/flac",
+    "pdf" => "application/pdf",
+    "json" => "application/json",
+    "txt" | "log" => "text/plain",
+    "md" => "text/markdown",
+    "csv" => "text/csv",
+    "srt" => "application/x-subrip",
+    "vtt" => "text/vtt",
+    _ => "application/octet-stream",
+  }
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,365 @@
+//! Versioned schema migrations for `chat_history.db`.
+//!
+//! The schema version lives in SQLite's `user_version` pragma; databases
+//! created by `backend/db.py` report 0. On open, [`migrate`] applies every
+//! migration past the stored version, in order, inside one transaction,
+//! after copying the file aside. A database from a newer app version is
+//! refused rather than guessed at.
+//!
+//! Migrations are append-only: never edit one that has shipped, add a new
+//! one instead.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, TransactionBehavior};
+
+use crate::chat::now_ms;
+
+/// Migration `i` takes the schema from version `i` to `i + 1`.
+const MIGRATIONS: &[&str] = &[
+  // 1: the tables `backend/db.py` creates; a no-op on its databases.
+  "
+  CREATE TABLE IF NOT EXISTS conversations (
+    id TEXT PRIMARY KEY,
+    title TEXT,
+    created_at INTEGER
+  );
+  CREATE TABLE IF NOT EXISTS messages (
+    id TEXT PRIMARY KEY,
+    conversation_id TEXT NOT NULL,
+    sender TEXT NOT NULL,
+    text TEXT,
+    created_at INTEGER,
+    confidence REAL DEFAULT NULL,
+    needs_clarification INTEGER DEFAULT 0,
+    attachments_json TEXT DEFAULT '[]',
+    metadata_json TEXT DEFAULT '{}',
+    FOREIGN KEY(conversation_id) REFERENCES conversations(id)
+  );
+  CREATE TABLE IF NOT EXISTS attachments (
+    id TEXT PRIMARY KEY,
+    message_id TEXT NOT NULL,
+    filename TEXT,
+    path TEXT,
+    mime_type TEXT,
+    created_at INTEGER,
+    FOREIGN KEY(message_id) REFERENCES messages(id)
+  );
+  ",
+  // 2: history is read per conversation in time order.
+  "
+  CREATE INDEX IF NOT EXISTS messages_conversation ON messages(conversation_id, created_at);
+  ",
+  // 3: archiving; NULL while the conversation is active.
+  "
+  ALTER TABLE conversations ADD COLUMN archived_at INTEGER;
+  CREATE INDEX IF NOT EXISTS attachments_message ON attachments(message_id);
+  ",
+  // 4: full-text search over message text. The index shares rowids with
+  // `messages` and triggers keep it in step with writes from either side,
+  // including `backend/db.py`.
+  "
+  CREATE VIRTUAL TABLE messages_fts USING fts5(
+    text,
+    content = 'messages',
+    content_rowid = 'rowid',
+    tokenize = 'unicode61 remove_diacritics 2'
+  );
+  CREATE TRIGGER messages_fts_insert AFTER INSERT ON messages BEGIN
+    INSERT INTO messages_fts (rowid, text) VALUES (new.rowid, new.text);
+  END;
+  CREATE TRIGGER messages_fts_delete AFTER DELETE ON messages BEGIN
+    INSERT INTO messages_fts (messages_fts, rowid, text) VALUES ('delete', old.rowid, old.text);
+  END;
+  CREATE TRIGGER messages_fts_update AFTER UPDATE OF text ON messages BEGIN
+    INSERT INTO messages_fts (messages_fts, rowid, text) VALUES ('delete', old.rowid, old.text);
+    INSERT INTO messages_fts (rowid, text) VALUES (new.rowid, new.text);
+  END;
+  INSERT INTO messages_fts (messages_fts) VALUES ('rebuild');
+  ",
+  // 5: who set the title: 'user' or 'auto', NULL while untitled. Titles
+  // already set were set by hand.
+  "
+  ALTER TABLE conversations ADD COLUMN title_source TEXT;
+  UPDATE conversations SET title_source = 'user' WHERE title <> '';
+  ",
+  // 6: content-addressed attachments. `message_id` becomes nullable so a
+  // file can be stored before the message it belongs to is sent; SQLite
+  // cannot drop NOT NULL in place, hence the copy.
+  "
+  CREATE TABLE attachments_new (
+    id TEXT PRIMARY KEY,
+    message_id TEXT,
+    filename TEXT,
+    path TEXT,
+    mime_type TEXT,
+    created_at INTEGER,
+    sha256 TEXT,
+    size INTEGER,
+    FOREIGN KEY(message_id) REFERENCES messages(id)
+  );
+  INSERT INTO attachments_new (id, message_id, filename, path, mime_type, created_at)
+    SELECT id, message_id, filename, path, mime_type, created_at FROM attachments;
+  DROP TABLE attachments;
+  ALTER TABLE attachments_new RENAME TO attachments;
+  CREATE INDEX attachments_message ON attachments(message_id);
+  CREATE INDEX attachments_path ON attachments(path);
+  ",
+  // 7: branching. A message follows `parent_id` (NULL for the first), and
+  // the conversation shows the branch ending at `active_leaf_id`. History
+  // so far is linear. Inserts that leave the parent unset, as
+  // `backend/db.py` does, continue the active branch.
+  "
+  ALTER TABLE messages ADD COLUMN parent_id TEXT;
+  ALTER TABLE conversations ADD COLUMN active_leaf_id TEXT;
+  UPDATE messages SET parent_id = (
+    SELECT p.id FROM messages p
+    WHERE p.conversation_id = messages.conversation_id
+      AND (p.created_at < messages.created_at
+        OR (p.created_at = messages.created_at AND p.rowid < messages.rowid))
+    ORDER BY p.created_at DESC, p.rowid DESC LIMIT 1
+  );
+  UPDATE conversations SET active_leaf_id = (
+    SELECT id FROM messages WHERE conversation_id = conversations.id
+    ORDER BY created_at DESC, rowid DESC LIMIT 1
+  );
+  CREATE INDEX messages_parent ON messages(parent_id);
+  CREATE TRIGGER messages_thread AFTER INSERT ON messages BEGIN
+    UPDATE messages SET parent_id = (
+      SELECT active_leaf_id FROM conversations WHERE id = new.conversation_id
+    ) WHERE id = new.id AND new.parent_id IS NULL;
+    UPDATE conversations SET active_leaf_id = new.id WHERE id = new.conversation_id;
+  END;
+  ",
+];
+
+/// Schema version this build reads and writes.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+  #[error(
+    "database schema version {found} is newer than this app supports ({supported}); \
+     update the app"
+  )]
+  TooNew { found: u32, supported: u32 },
+  #[error("could not back up the database to {path} before migrating: {source}")]
+  Backup {
+    path: PathBuf,
+    source: rusqlite::Error,
+  },
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+}
+
+/// Brings the database at `path`, open as `conn`, up to [`SCHEMA_VERSION`].
+pub fn migrate(conn: &mut Connection, path: &Path) -> Result<(), MigrationError> {
+  let version = user_version(conn)?;
+  check_supported(version)?;
+  if version == SCHEMA_VERSION {
+    return Ok(());
+  }
+  if has_tables(conn)? {
+    backup(conn, path, version)?;
+  }
+
+  // Take the write lock up front; the backend or another copy of the app
+  // may be migrating at the same time.
+  let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+  let version = user_version(&tx)?;
+  check_supported(version)?;
+  for (index, sql) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+    log::info!(
+      "migrating {} to schema version {}",
+      path.display(),
+      index + 1
+    );
+    tx.execute_batch(sql)?;
+  }
+  tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+  tx.commit()?;
+  Ok(())
+}
+
+fn user_version(conn: &Connection) -> rusqlite::Result<u32> {
+  conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+fn check_supported(version: u32) -> Result<(), MigrationError> {
+  if version > SCHEMA_VERSION {
+    return Err(MigrationError::TooNew {
+      found: version,
+      supported: SCHEMA_VERSION,
+    });
+  }
+  Ok(())
+}
+
+/// Whether there is anything worth backing up.
+fn has_tables(conn: &Connection) -> rusqlite::Result<bool> {
+  conn.query_row(
+    "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table')",
+    [],
+    |row| row.get(0),
+  )
+}
+
+/// Writes a consistent copy next to the database, named after the version
+/// it holds: `chat_history.db.v1-<unix ms>.bak`.
+fn backup(conn: &Connection, path: &Path, version: u32) -> Result<(), MigrationError> {
+  let mut name = path.file_name().unwrap_or_default().to_os_string();
+  name.push(format!(".v{version}-{}.bak", now_ms()));
+  let target = path.with_file_name(name);
+  log::info!("backing up {} to {}", path.display(), target.display());
+  conn
+    .execute("VACUUM INTO ?1", [target.to_string_lossy()])
+    .map_err(|source| MigrationError::Backup {
+      path: target,
+      source,
+    })?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::history::testing::ScratchDir;
+
+  /// Opens `chat_history.db` in `dir` at schema `version`.
+  fn database_at(dir: &Path, version: u32) -> (Connection, PathBuf) {
+    let path = dir.join("chat_history.db");
+    let conn = Connection::open(&path).unwrap();
+    for sql in &MIGRATIONS[..version as usize] {
+      conn.execute_batch(sql).unwrap();
+    }
+    conn.pragma_update(None, "user_version", version).unwrap();
+    (conn, path)
+  }
+
+  #[test]
+  fn migrates_an_empty_database() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    migrate(&mut conn, Path::new(":memory:")).unwrap();
+    assert_eq!(user_version(&conn).unwrap(), SCHEMA_VERSION);
+    // Already current: nothing to do.
+    migrate(&mut conn, Path::new(":memory:")).unwrap();
+    assert_eq!(user_version(&conn).unwrap(), SCHEMA_VERSION);
+  }
+
+  #[test]
+  fn migrates_a_backend_database_and_backs_it_up() {
+    let dir = ScratchDir::new("migrate-v0");
+    let (mut conn, path) = database_at(dir.path(), 1);
+    conn.pragma_update(None, "user_version", 0).unwrap();
+    conn
+      .execute_batch(
+        "INSERT INTO conversations (id, title, created_at) VALUES ('c', 'Trip', 1);
+         INSERT INTO messages (id, conversation_id, sender, text, created_at)
+           VALUES ('m1', 'c', 'user', 'hello', 1), ('m2', 'c', 'agent', 'hi', 2);",
+      )
+      .unwrap();
+
+    migrate(&mut conn, &path).unwrap();
+
+    assert_eq!(user_version(&conn).unwrap(), SCHEMA_VERSION);
+    let backups = std::fs::read_dir(dir.path())
+      .unwrap()
+      .filter_map(|entry| entry.ok())
+      .filter(|entry| {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        name.starts_with("chat_history.db.v0-") && name.ends_with(".bak")
+      })
+      .count();
+    assert_eq!(backups, 1);
+    let parent: Option<String> = conn
+      .query_row(
+        "SELECT parent_id FROM messages WHERE id = 'm2'",
+        [],
+        |row| row.get(0),
+      )
+      .unwrap();
+    assert_eq!(parent.as_deref(), Some("m1"));
+    let (leaf, title_source): (Option<String>, Option<String>) = conn
+      .query_row(
+        "SELECT active_leaf_id, title_source FROM conversations WHERE id = 'c'",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+      )
+      .unwrap();
+    assert_eq!(leaf.as_deref(), Some("m2"));
+    assert_eq!(title_source.as_deref(), Some("user"));
+    let hits: i64 = conn
+      .query_row(
+        "SELECT count(*) FROM messages_fts WHERE messages_fts MATCH 'hello'",
+        [],
+        |row| row.get(0),
+      )
+      .unwrap();
+    assert_eq!(hits, 1);
+  }
+
+  #[test]
+  fn refuses_a_newer_database() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn
+      .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+      .unwrap();
+    let err = migrate(&mut conn, Path::new(":memory:")).unwrap_err();
+    assert!(matches!(
+      err,
+      MigrationError::TooNew { found, supported }
+        if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+    ));
+    assert_eq!(user_version(&conn).unwrap(), SCHEMA_VERSION + 1);
+  }
+
+  #[test]
+  fn attachments_rebuild_keeps_rows() {
+    let dir = ScratchDir::new("migrate-v5");
+    let (mut conn, path) = database_at(dir.path(), 5);
+    conn
+      .execute_batch(
+        "INSERT INTO conversations (id, title, created_at) VALUES ('c', '', 1);
+         INSERT INTO messages (id, conversation_id, sender, text, created_at)
+           VALUES ('m', 'c', 'user', 'see attached', 1);
+         INSERT INTO attachments (id, message_id, filename, path, mime_type, created_at)
+           VALUES ('a', 'm', 'clip.mp4', 'data/attachments/clip.mp4', 'video/mp4', 1);",
+      )
+      .unwrap();
+
+    migrate(&mut conn, &path).unwrap();
+
+    let row: (String, String, String, String, i64) = conn
+      .query_row(
+        "SELECT message_id, filename, path, mime_type, created_at FROM attachments WHERE id = 'a'",
+        [],
+        |row| {
+          Ok((
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+          ))
+        },
+      )
+      .unwrap();
+    assert_eq!(
+      row,
+      (
+        "m".to_string(),
+        "clip.mp4".to_string(),
+        "data/attachments/clip.mp4".to_string(),
+        "video/mp4".to_string(),
+        1
+      )
+    );
+    // Pending attachments have no message yet.
+    conn
+      .execute(
+        "INSERT INTO attachments (id, message_id, filename) VALUES ('b', NULL, 'x.png')",
+        [],
+      )
+      .unwrap();
+  }
+}
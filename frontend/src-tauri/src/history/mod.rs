@@ -0,0 +1,33 @@
+//! Chat history hosted by the shell itself.
+//!
+//! [`ChatStore`] keeps conversations in the same `chat_history.db` and
+//! schema as `backend/db.py`, through a small connection pool in WAL mode,
+//! and migrates it on open (see [`migrations`]). Attached files are kept
+//! beside it, stored once per content (see `attachments`).
+//! [`ChatServer`] serves `videoanalyzer.chat.ChatService` over it next to
+//! the agent manager, so chat persistence keeps working while the Python
+//! backend is down or restarting, and titles conversations after their
+//! first exchange (see `title`).
+
+pub mod archive;
+mod attachments;
+pub mod migrations;
+mod pool;
+mod search;
+mod service;
+mod store;
+#[cfg(test)]
+pub(crate) mod testing;
+mod title;
+
+pub use attachments::{Attachment, AttachmentError};
+pub use migrations::{MigrationError, SCHEMA_VERSION};
+pub use service::ChatServer;
+pub use store::{ChatStore, CHAT_DB};
+pub use title::Titler;
+
+/// Message and attachment ids in the backend's format: a UUID4 as 32 hex
+/// digits.
+pub(crate) fn new_id() -> String {
+  uuid::Uuid::new_v4().simple().to_string()
+}
@@ -0,0 +1,89 @@
+//! A small pool of connections to one SQLite database.
+//!
+//! Connections are opened on demand and kept for reuse once returned, so
+//! concurrent calls each get their own connection instead of queueing on
+//! one or reopening the file every time.
+
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::Connection;
+
+/// Idle connections kept open; more may be in use at once.
+const MAX_IDLE: usize = 4;
+
+/// How long a statement waits for another connection's write lock, e.g.
+/// one held by `backend/db.py`, before failing with `SQLITE_BUSY`.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct Pool {
+  path: PathBuf,
+  idle: Mutex<Vec<Connection>>,
+}
+
+/// A connection borrowed from a [`Pool`]; goes back to it when dropped.
+pub struct PooledConnection<'a> {
+  pool: &'a Pool,
+  conn: Option<Connection>,
+}
+
+impl Pool {
+  /// Opens the database at `path`, switching it to WAL so readers never
+  /// block the writer.
+  pub fn open(path: &Path) -> rusqlite::Result<Self> {
+    let conn = connect(path)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    Ok(Self {
+      path: path.to_path_buf(),
+      idle: Mutex::new(vec![conn]),
+    })
+  }
+
+  pub fn get(&self) -> rusqlite::Result<PooledConnection<'_>> {
+    let idle = self.idle.lock().unwrap().pop();
+    let conn = match idle {
+      Some(conn) => conn,
+      None => connect(&self.path)?,
+    };
+    Ok(PooledConnection {
+      pool: self,
+      conn: Some(conn),
+    })
+  }
+}
+
+fn connect(path: &Path) -> rusqlite::Result<Connection> {
+  let conn = Connection::open(path)?;
+  conn.busy_timeout(BUSY_TIMEOUT)?;
+  // Safe with WAL: a power loss may drop the last commits, never corrupt.
+  conn.pragma_update(None, "synchronous", "NORMAL")?;
+  Ok(conn)
+}
+
+impl Deref for PooledConnection<'_> {
+  type Target = Connection;
+
+  fn deref(&self) -> &Connection {
+    self.conn.as_ref().unwrap()
+  }
+}
+
+impl DerefMut for PooledConnection<'_> {
+  fn deref_mut(&mut self) -> &mut Connection {
+    self.conn.as_mut().unwrap()
+  }
+}
+
+impl Drop for PooledConnection<'_> {
+  fn drop(&mut self) {
+    let Some(conn) = self.conn.take() else {
+      return;
+    };
+    let mut idle = self.pool.idle.lock().unwrap();
+    if idle.len() < MAX_IDLE {
+      idle.push(conn);
+    }
+  }
+}
@@ -0,0 +1,110 @@
+//! Helpers for full-text search over `messages_fts`: turning what the user
+//! typed into an FTS5 query and FTS5 snippets into safe HTML.
+
+/// Marks FTS5 puts around matches in a snippet; private-use characters that
+/// cannot clash with message text and survive HTML escaping.
+pub const MATCH_START: char = '\u{E000}';
+pub const MATCH_END: char = '\u{E001}';
+
+/// Builds an FTS5 query matching messages that contain every word of
+/// `input`, the last one also as a prefix so results follow typing.
+///
+/// Words are quoted, so FTS5 operators and punctuation in the input are
+/// searched for literally instead of failing to parse. Returns `None` when
+/// there is nothing to search for.
+pub fn fts_query(input: &str) -> Option<String> {
+  let words: Vec<&str> = input.split_whitespace().collect();
+  let (last, rest) = words.split_last()?;
+  let mut query: Vec<String> = rest.iter().map(|word| quote(word)).collect();
+  query.push(format!("{}*", quote(last)));
+  Some(query.join(" "))
+}
+
+fn quote(word: &str) -> String {
+  format!("\"{}\"", word.replace('"', "\"\""))
+}
+
+/// Escapes a snippet for HTML and turns the match marks into `<mark>`.
+pub fn highlight(snippet: &str) -> String {
+  let mut html = String::with_capacity(snippet.len() + 16);
+  for ch in snippet.chars() {
+    match ch {
+      MATCH_START => html.push_str("<mark>"),
+      MATCH_END => html.push_str("</mark>"),
+      '&' => html.push_str("&amp;"),
+      '<' => html.push_str("&lt;"),
+      '>' => html.push_str("&gt;"),
+      '"' => html.push_str("&quot;"),
+      '\'' => html.push_str("&#39;"),
+      ch => html.push(ch),
+    }
+  }
+  html
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Rowids of `texts` matching `input`, through a real FTS5 index.
+  fn search(texts: &[&str], input: &str) -> Vec<i64> {
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn
+      .execute_batch("CREATE VIRTUAL TABLE fts USING fts5(text)")
+      .unwrap();
+    for text in texts {
+      conn
+        .execute("INSERT INTO fts (text) VALUES (?1)", [text])
+        .unwrap();
+    }
+    let mut stmt = conn
+      .prepare("SELECT rowid FROM fts WHERE fts MATCH ?1 ORDER BY rowid")
+      .unwrap();
+    let rows = stmt
+      .query_map([fts_query(input).unwrap()], |row| row.get(0))
+      .unwrap();
+    rows.collect::<rusqlite::Result<_>>().unwrap()
+  }
+
+  #[test]
+  fn quotes_every_word_and_prefixes_the_last() {
+    assert_eq!(
+      fts_query("  video  transcr ").as_deref(),
+      Some(r#""video" "transcr"*"#)
+    );
+    assert_eq!(fts_query("   "), None);
+    assert_eq!(fts_query(""), None);
+  }
+
+  #[test]
+  fn doubles_quotes_inside_words() {
+    assert_eq!(
+      fts_query(r#"say "hi""#).as_deref(),
+      Some(r#""say" """hi"""*"#)
+    );
+    assert_eq!(
+      search(&[r#"say "hi" there"#, "say bye"], r#"say "hi""#),
+      [1]
+    );
+  }
+
+  #[test]
+  fn searches_operators_literally() {
+    let texts = ["cats AND dogs", "cats dogs", "near the lake"];
+    // Every word must appear, operators included as plain words.
+    assert_eq!(search(&texts, "cats AND dogs"), [1]);
+    assert_eq!(search(&texts, "NEAR(cats dogs)"), Vec::<i64>::new());
+    assert_eq!(search(&texts, "NEAR"), [3]);
+    assert_eq!(search(&texts, "dog*"), [1, 2]);
+    assert_eq!(search(&texts, "-cats"), [1, 2]);
+  }
+
+  #[test]
+  fn highlight_escapes_html_and_marks_matches() {
+    let snippet = format!("a <b>&amp; {MATCH_START}\"it's\"{MATCH_END} done");
+    assert_eq!(
+      highlight(&snippet),
+      "a &lt;b&gt;&amp;amp; <mark>&quot;it&#39;s&quot;</mark> done"
+    );
+  }
+}
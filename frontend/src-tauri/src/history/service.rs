@@ -0,0 +1,602 @@
+//! `ChatService` over a [`ChatStore`], behaving like the servicer in
+//! `backend/server.py`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use super::new_id;
+use super::store::ChatStore;
+use super::title::Titler;
+use crate::chat::now_ms;
+use crate::grpc::Backend;
+use crate::proto::chat::chat_service_server::ChatService;
+use crate::proto::chat::stream_response::Payload;
+use crate::proto::chat::{
+  ArchiveConversationRequest, Conversation, DeleteConversationRequest, DeleteConversationResponse,
+  GetHistoryRequest, GetHistoryResponse, ListConversationsRequest, ListConversationsResponse,
+  Message, RegenerateRequest, RenameConversationRequest, SearchMessagesRequest,
+  SearchMessagesResponse, SendMessageRequest, SendMessageResponse, StreamResponse,
+  SwitchBranchRequest,
+};
+
+/// `GetHistory` page size when the request leaves it at zero.
+const DEFAULT_HISTORY_LIMIT: i64 = 100;
+
+/// `SearchMessages` page size when the request leaves it at zero.
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+
+/// Characters per `partial_text` chunk of a streamed reply, and the pause
+/// between chunks.
+const CHUNK_CHARS: usize = 40;
+const CHUNK_DELAY: Duration = Duration::from_millis(50);
+
+pub struct ChatServer {
+  store: Arc<ChatStore>,
+  titler: Titler,
+  title_updates: broadcast::Sender<Conversation>,
+}
+
+impl ChatServer {
+  /// `backend` is used to ask the generation agent for conversation
+  /// titles.
+  pub fn new(store: Arc<ChatStore>, backend: Backend) -> Self {
+    let (title_updates, _) = broadcast::channel(16);
+    let titler = Titler::new(store.clone(), backend, title_updates.clone());
+    Self {
+      store,
+      titler,
+      title_updates,
+    }
+  }
+
+  /// Conversations as they are given automatic titles.
+  pub fn subscribe(&self) -> broadcast::Receiver<Conversation> {
+    self.title_updates.subscribe()
+  }
+
+  /// Titles conversations for replies stored elsewhere, i.e. by the
+  /// backend when it hosts chat; updates go to [`ChatServer::subscribe`].
+  pub fn titler(&self) -> Titler {
+    self.titler.clone()
+  }
+
+  /// Why `message` cannot go where it asks to: the message it follows, or
+  /// the one it edits, must belong to its conversation, and only the
+  /// user's messages can be edited. `None` if it can.
+  fn branch_error(&self, message: &Message, edit_of: &str) -> Option<Status> {
+    let anchor = match edit_of {
+      "" => message.parent_id.as_str(),
+      edit_of => edit_of,
+    };
+    if anchor.is_empty() {
+      return None;
+    }
+    match self.store.message(&message.conversation_id, anchor) {
+      Ok(Some(target)) if !edit_of.is_empty() && target.sender != "user" => Some(
+        Status::invalid_argument(format!("message {anchor} is not from the user")),
+      ),
+      Ok(Some(_)) => None,
+      Ok(None) => Some(unknown_message(anchor)),
+      Err(err) => Some(internal(err)),
+    }
+  }
+
+  /// Streams a reply to `user`, stored under it once complete.
+  ///
+  /// Like the backend, the reply is simulated until local model inference
+  /// is wired in. A client that goes away mid-reply stores the partial text
+  /// itself, so nothing is stored here in that case.
+  fn reply_to(&self, user: Message) -> ReceiverStream<Result<StreamResponse, Status>> {
+    let store = self.store.clone();
+    let titler = self.titler.clone();
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+      let summary: String = user.text.chars().take(200).collect();
+      let reply_text = format!("Simulated agent reply summarizing: {summary}");
+      let chars: Vec<char> = reply_text.chars().collect();
+      for chunk in chars.chunks(CHUNK_CHARS) {
+        let partial = StreamResponse {
+          payload: Some(Payload::PartialText(chunk.iter().collect())),
+          done: false,
+        };
+        if tx.send(Ok(partial)).await.is_err() {
+          return;
+        }
+        tokio::time::sleep(CHUNK_DELAY).await;
+      }
+      if tx.is_closed() {
+        return;
+      }
+
+      let mut reply = Message {
+        id: new_id(),
+        conversation_id: user.conversation_id,
+        sender: "agent".into(),
+        text: reply_text,
+        created_at: now_ms(),
+        confidence: 0.9,
+        metadata_json: "{}".into(),
+        parent_id: user.id,
+        ..Default::default()
+      };
+      let last = match store.store_message(&mut reply, "") {
+        Ok(()) => {
+          titler.after_reply(reply.conversation_id.clone());
+          Ok(StreamResponse {
+            payload: Some(Payload::Message(reply)),
+            done: true,
+          })
+        }
+        Err(err) => Err(internal(err)),
+      };
+      let _ = tx.send(last).await;
+    });
+    ReceiverStream::new(rx)
+  }
+}
+
+#[tonic::async_trait]
+impl ChatService for ChatServer {
+  async fn send_message(
+    &self,
+    request: Request<SendMessageRequest>,
+  ) -> Result<Response<SendMessageResponse>, Status> {
+    let request = request.into_inner();
+    let mut message = complete(request.conversation_id, request.message);
+    if !valid_metadata(&message) {
+      return Err(Status::invalid_argument("metadata_json is not valid JSON"));
+    }
+    if let Some(err) = self.branch_error(&message, &request.edit_of) {
+      return Err(err);
+    }
+    self
+      .store
+      .store_message(&mut message, &request.edit_of)
+      .map_err(internal)?;
+    if message.sender != "user" {
+      self.titler.after_reply(message.conversation_id.clone());
+    }
+    Ok(Response::new(SendMessageResponse {
+      stored_message: Some(message),
+    }))
+  }
+
+  async fn get_history(
+    &self,
+    request: Request<GetHistoryRequest>,
+  ) -> Result<Response<GetHistoryResponse>, Status> {
+    let request = request.into_inner();
+    let conversation_id = match request.conversation_id.as_str() {
+      "" => "default",
+      id => id,
+    };
+    let limit = match request.limit {
+      limit if limit > 0 => i64::from(limit),
+      _ => DEFAULT_HISTORY_LIMIT,
+    };
+    let messages = self
+      .store
+      .history(conversation_id, limit, i64::from(request.offset.max(0)))
+      .map_err(internal)?;
+    Ok(Response::new(GetHistoryResponse { messages }))
+  }
+
+  type StreamResponsesStream = ReceiverStream<Result<StreamResponse, Status>>;
+
+  /// Stores the user message and streams a reply.
+  async fn stream_responses(
+    &self,
+    request: Request<SendMessageRequest>,
+  ) -> Result<Response<Self::StreamResponsesStream>, Status> {
+    let request = request.into_inner();
+    let mut user = complete(request.conversation_id, request.message);
+    if !valid_metadata(&user) {
+      return Err(Status::invalid_argument("metadata_json is not valid JSON"));
+    }
+    if let Some(err) = self.branch_error(&user, &request.edit_of) {
+      return Err(err);
+    }
+    self
+      .store
+      .store_message(&mut user, &request.edit_of)
+      .map_err(internal)?;
+    Ok(Response::new(self.reply_to(user)))
+  }
+
+  type RegenerateStream = ReceiverStream<Result<StreamResponse, Status>>;
+
+  /// Streams a new reply to a user message already stored. The reply is
+  /// stored next to the earlier ones as another version, and only then is
+  /// its branch shown, so a reply that fails leaves the conversation as it
+  /// was.
+  async fn regenerate(
+    &self,
+    request: Request<RegenerateRequest>,
+  ) -> Result<Response<Self::RegenerateStream>, Status> {
+    let request = request.into_inner();
+    let conversation_id = match request.conversation_id.as_str() {
+      "" => "default",
+      id => id,
+    };
+    let user = match request.message_id.as_str() {
+      "" => self
+        .store
+        .history(conversation_id, -1, 0)
+        .map_err(internal)?
+        .into_iter()
+        .rev()
+        .find(|message| message.sender == "user")
+        .ok_or_else(|| Status::failed_precondition("no user message to answer"))?,
+      id => self
+        .store
+        .message(conversation_id, id)
+        .map_err(internal)?
+        .ok_or_else(|| unknown_message(id))?,
+    };
+    if user.sender != "user" {
+      return Err(Status::invalid_argument(format!(
+        "message {} is not from the user",
+        user.id
+      )));
+    }
+    Ok(Response::new(self.reply_to(user)))
+  }
+
+  async fn list_conversations(
+    &self,
+    request: Request<ListConversationsRequest>,
+  ) -> Result<Response<ListConversationsResponse>, Status> {
+    let request = request.into_inner();
+    let limit = match request.limit {
+      limit if limit > 0 => i64::from(limit),
+      _ => -1,
+    };
+    let conversations = self
+      .store
+      .conversations(
+        request.include_archived,
+        limit,
+        i64::from(request.offset.max(0)),
+      )
+      .map_err(internal)?;
+    Ok(Response::new(ListConversationsResponse { conversations }))
+  }
+
+  async fn rename_conversation(
+    &self,
+    request: Request<RenameConversationRequest>,
+  ) -> Result<Response<Conversation>, Status> {
+    let request = request.into_inner();
+    let title = request.title.trim();
+    if title.is_empty() {
+      return Err(Status::invalid_argument("title is empty"));
+    }
+    let conversation = self
+      .store
+      .rename_conversation(&request.conversation_id, title)
+      .map_err(internal)?
+      .ok_or_else(|| unknown_conversation(&request.conversation_id))?;
+    Ok(Response::new(conversation))
+  }
+
+  async fn archive_conversation(
+    &self,
+    request: Request<ArchiveConversationRequest>,
+  ) -> Result<Response<Conversation>, Status> {
+    let request = request.into_inner();
+    let conversation = self
+      .store
+      .archive_conversation(&request.conversation_id, request.archived)
+      .map_err(internal)?
+      .ok_or_else(|| unknown_conversation(&request.conversation_id))?;
+    Ok(Response::new(conversation))
+  }
+
+  async fn delete_conversation(
+    &self,
+    request: Request<DeleteConversationRequest>,
+  ) -> Result<Response<DeleteConversationResponse>, Status> {
+    let deleted = self
+      .store
+      .delete_conversation(&request.into_inner().conversation_id)
+      .map_err(internal)?;
+    Ok(Response::new(DeleteConversationResponse { deleted }))
+  }
+
+  async fn search_messages(
+    &self,
+    request: Request<SearchMessagesRequest>,
+  ) -> Result<Response<SearchMessagesResponse>, Status> {
+    let request = request.into_inner();
+    if request.query.trim().is_empty() {
+      return Err(Status::invalid_argument("query is empty"));
+    }
+    let limit = match request.limit {
+      limit if limit > 0 => i64::from(limit),
+      _ => DEFAULT_SEARCH_LIMIT,
+    };
+    let hits = self.store.search(&request, limit).map_err(internal)?;
+    Ok(Response::new(SearchMessagesResponse { hits }))
+  }
+
+  async fn switch_branch(
+    &self,
+    request: Request<SwitchBranchRequest>,
+  ) -> Result<Response<GetHistoryResponse>, Status> {
+    let request = request.into_inner();
+    let switched = self
+      .store
+      .switch_branch(&request.conversation_id, &request.message_id)
+      .map_err(internal)?;
+    if !switched {
+      return Err(unknown_message(&request.message_id));
+    }
+    let messages = self
+      .store
+      .history(&request.conversation_id, -1, 0)
+      .map_err(internal)?;
+    Ok(Response::new(GetHistoryResponse { messages }))
+  }
+}
+
+fn unknown_conversation(id: &str) -> Status {
+  Status::not_found(format!("unknown conversation {id}"))
+}
+
+fn unknown_message(id: &str) -> Status {
+  Status::not_found(format!("unknown message {id}"))
+}
+
+/// Fills in what the client left unset, as the backend does.
+fn complete(conversation_id: String, message: Option<Message>) -> Message {
+  let mut message = message.unwrap_or_default();
+  if !conversation_id.is_empty() {
+    message.conversation_id = conversation_id;
+  } else if message.conversation_id.is_empty() {
+    message.conversation_id = "default".into();
+  }
+  if message.id.is_empty() {
+    message.id = new_id();
+  }
+  if message.sender.is_empty() {
+    message.sender = "user".into();
+  }
+  if message.created_at == 0 {
+    message.created_at = now_ms();
+  }
+  message
+}
+
+fn valid_metadata(message: &Message) -> bool {
+  message.metadata_json.is_empty()
+    || serde_json::from_str::<serde_json::Value>(&message.metadata_json).is_ok()
+}
+
+fn internal(err: rusqlite::Error) -> Status {
+  log::error!("chat store: {err}");
+  Status::internal(format!("chat store: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+  use tokio_stream::StreamExt;
+  use tonic::Code;
+
+  use super::*;
+  use crate::config::Config;
+  use crate::history::testing::ScratchDir;
+
+  fn server(dir: &ScratchDir) -> ChatServer {
+    let store = Arc::new(ChatStore::open(dir.path()).unwrap());
+    ChatServer::new(store, Backend::new(&Config::from_env()).unwrap())
+  }
+
+  fn message(id: &str, sender: &str, parent_id: &str) -> Message {
+    Message {
+      id: id.into(),
+      conversation_id: "c1".into(),
+      sender: sender.into(),
+      text: format!("text of {id}"),
+      parent_id: parent_id.into(),
+      ..Default::default()
+    }
+  }
+
+  async fn send(server: &ChatServer, message: Message, edit_of: &str) -> Result<Message, Status> {
+    let request = SendMessageRequest {
+      message: Some(message),
+      edit_of: edit_of.into(),
+      ..Default::default()
+    };
+    let response = server.send_message(Request::new(request)).await?;
+    Ok(response.into_inner().stored_message.unwrap())
+  }
+
+  /// q1 → a1 → q2 → a2 in conversation `c1`.
+  async fn exchange(server: &ChatServer) {
+    for (id, sender) in [
+      ("q1", "user"),
+      ("a1", "agent"),
+      ("q2", "user"),
+      ("a2", "agent"),
+    ] {
+      send(server, message(id, sender, ""), "").await.unwrap();
+    }
+  }
+
+  /// The message a reply stream ends with.
+  async fn final_message(mut stream: ReceiverStream<Result<StreamResponse, Status>>) -> Message {
+    while let Some(response) = stream.next().await {
+      let response = response.unwrap();
+      if let Some(Payload::Message(message)) = response.payload {
+        assert!(response.done);
+        return message;
+      }
+    }
+    panic!("stream ended without a message");
+  }
+
+  #[test]
+  fn completes_what_the_client_left_unset() {
+    let message = complete(String::new(), None);
+    assert_eq!(message.conversation_id, "default");
+    assert_eq!(message.id.len(), 32);
+    assert!(message.id.bytes().all(|byte| byte.is_ascii_hexdigit()));
+    assert_eq!(message.sender, "user");
+    assert!(message.created_at > 0);
+
+    let given = Message {
+      id: "m1".into(),
+      conversation_id: "c1".into(),
+      sender: "agent".into(),
+      created_at: 5,
+      ..Default::default()
+    };
+    let kept = complete(String::new(), Some(given.clone()));
+    assert_eq!(
+      (
+        kept.id.as_str(),
+        kept.conversation_id.as_str(),
+        kept.sender.as_str(),
+        kept.created_at
+      ),
+      ("m1", "c1", "agent", 5)
+    );
+    // The request's conversation wins over the message's.
+    assert_eq!(complete("c2".into(), Some(given)).conversation_id, "c2");
+  }
+
+  #[tokio::test]
+  async fn metadata_must_be_json() {
+    let dir = ScratchDir::new("service-metadata");
+    let server = server(&dir);
+    for (id, metadata) in [("m1", ""), ("m2", "{}"), ("m3", "{\"model\": \"local\"}")] {
+      let mut valid = message(id, "user", "");
+      valid.metadata_json = metadata.into();
+      send(&server, valid, "").await.unwrap();
+    }
+
+    let mut invalid = message("m4", "user", "");
+    invalid.metadata_json = "{not json".into();
+    let err = send(&server, invalid.clone(), "").await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    let request = SendMessageRequest {
+      message: Some(invalid),
+      ..Default::default()
+    };
+    let err = server
+      .stream_responses(Request::new(request))
+      .await
+      .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(server.store.message("c1", "m4").unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn messages_only_branch_within_their_conversation() {
+    let dir = ScratchDir::new("service-branch");
+    let server = server(&dir);
+    exchange(&server).await;
+
+    let err = send(&server, message("x1", "user", "missing"), "")
+      .await
+      .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let mut elsewhere = message("x2", "user", "q1");
+    elsewhere.conversation_id = "c2".into();
+    let err = send(&server, elsewhere, "").await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let err = send(&server, message("x3", "user", ""), "a1")
+      .await
+      .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let edit = send(&server, message("q1b", "user", ""), "q1")
+      .await
+      .unwrap();
+    assert_eq!(edit.parent_id, "");
+    let reply = send(&server, message("a1b", "agent", "q1"), "")
+      .await
+      .unwrap();
+    assert_eq!(reply.parent_id, "q1");
+  }
+
+  #[tokio::test]
+  async fn regenerate_answers_the_last_user_message_by_default() {
+    let dir = ScratchDir::new("service-regenerate");
+    let server = server(&dir);
+    exchange(&server).await;
+
+    let request = RegenerateRequest {
+      conversation_id: "c1".into(),
+      message_id: String::new(),
+    };
+    let reply = final_message(
+      server
+        .regenerate(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner(),
+    )
+    .await;
+    assert_eq!(reply.parent_id, "q2");
+    assert_eq!(reply.sender, "agent");
+
+    let history = server.store.history("c1", -1, 0).unwrap();
+    let last = history.last().unwrap();
+    assert_eq!(last.id, reply.id);
+    assert_eq!(last.sibling_ids, ["a2", reply.id.as_str()]);
+  }
+
+  #[tokio::test]
+  async fn regenerate_needs_a_user_message() {
+    let dir = ScratchDir::new("service-regenerate-errors");
+    let server = server(&dir);
+    let regenerate = |message_id: &str| {
+      server.regenerate(Request::new(RegenerateRequest {
+        conversation_id: "c1".into(),
+        message_id: message_id.into(),
+      }))
+    };
+
+    let err = regenerate("").await.unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    exchange(&server).await;
+    assert_eq!(
+      regenerate("a1").await.unwrap_err().code(),
+      Code::InvalidArgument
+    );
+    assert_eq!(
+      regenerate("missing").await.unwrap_err().code(),
+      Code::NotFound
+    );
+  }
+
+  #[tokio::test]
+  async fn switching_to_an_unknown_message_is_not_found() {
+    let dir = ScratchDir::new("service-switch");
+    let server = server(&dir);
+    exchange(&server).await;
+    let switch = |conversation_id: &str, message_id: &str| {
+      server.switch_branch(Request::new(SwitchBranchRequest {
+        conversation_id: conversation_id.into(),
+        message_id: message_id.into(),
+      }))
+    };
+
+    assert_eq!(
+      switch("c1", "missing").await.unwrap_err().code(),
+      Code::NotFound
+    );
+    assert_eq!(switch("c2", "q1").await.unwrap_err().code(), Code::NotFound);
+    let history = switch("c1", "q1").await.unwrap().into_inner().messages;
+    assert_eq!(history.first().unwrap().id, "q1");
+  }
+}
@@ -0,0 +1,1099 @@
+//! Chat history in `chat_history.db`, in the schema `backend/db.py`
+//! creates, so the shell and the Python backend can share the file. The
+//! schema is kept current by [`super::migrations`].
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{params, OptionalExtension, Row, Transaction};
+
+use base64::Engine as _;
+
+use super::archive::{ArchivedAttachment, ArchivedConversation, ArchivedMessage};
+use super::attachments::{self, Attachment, AttachmentError, Blob, Blobs};
+use super::migrations::{self, MigrationError};
+use super::pool::Pool;
+use super::{new_id, search};
+use crate::chat::now_ms;
+use crate::proto::chat::{Conversation, Message, SearchHit, SearchMessagesRequest};
+
+pub const CHAT_DB: &str = "chat_history.db";
+
+/// Characters of the last message shown in a conversation's preview.
+const PREVIEW_CHARS: usize = 120;
+
+/// Attachments added but never sent are deleted after this long.
+const PENDING_ATTACHMENT_TTL_MS: i64 = 24 * 60 * 60 * 1000;
+
+const MESSAGE_COLUMNS: &str = "id, conversation_id, sender, text, created_at, confidence,
+  needs_clarification, attachments_json, metadata_json, parent_id";
+
+const ATTACHMENT_COLUMNS: &str =
+  "id, message_id, filename, path, mime_type, created_at, sha256, size";
+
+pub struct ChatStore {
+  pool: Pool,
+  blobs: Blobs,
+}
+
+impl ChatStore {
+  /// Opens (creating if needed) the chat database in `data_dir` and
+  /// migrates it to the current schema.
+  pub fn open(data_dir: &Path) -> Result<Self, MigrationError> {
+    if let Err(err) = std::fs::create_dir_all(data_dir) {
+      log::warn!("could not create {}: {err}", data_dir.display());
+    }
+    let path = data_dir.join(CHAT_DB);
+    let pool = Pool::open(&path)?;
+    migrations::migrate(&mut *pool.get()?, &path)?;
+    let store = Self {
+      pool,
+      blobs: Blobs::new(data_dir),
+    };
+    if let Err(err) = store.prune_pending_attachments() {
+      log::warn!("could not prune unsent attachments: {err}");
+    }
+    Ok(store)
+  }
+
+  /// Inserts `message`, creating its conversation on first use, and links
+  /// the attachments it lists by id. The message must already carry its id,
+  /// conversation and timestamp.
+  ///
+  /// The message follows its `parent_id` if set and otherwise ends the
+  /// active branch; with `edit_of` it becomes a new version of that message
+  /// instead, starting a branch. Either way it becomes the active leaf, and
+  /// `parent_id` is filled in.
+  pub fn store_message(&self, message: &mut Message, edit_of: &str) -> rusqlite::Result<()> {
+    let mut conn = self.pool.get()?;
+    let tx = conn.transaction()?;
+    ensure_conversation(&tx, &message.conversation_id, message.created_at)?;
+    let parent: Option<String> = if !edit_of.is_empty() {
+      tx.query_row(
+        "SELECT parent_id FROM messages WHERE id = ?1",
+        [edit_of],
+        |row| row.get(0),
+      )?
+    } else if !message.parent_id.is_empty() {
+      Some(message.parent_id.clone())
+    } else {
+      tx.query_row(
+        "SELECT active_leaf_id FROM conversations WHERE id = ?1",
+        [&message.conversation_id],
+        |row| row.get(0),
+      )?
+    };
+    message.parent_id = parent.unwrap_or_default();
+    insert_message(&tx, message)?;
+    for attachment in &message.attachments {
+      // Entries that are not pending attachment ids (paths from older
+      // versions, or ids already linked) are left as they are.
+      tx.execute(
+        "UPDATE attachments SET message_id = ?1 WHERE id = ?2 AND message_id IS NULL",
+        params![message.id, attachment],
+      )?;
+    }
+    tx.commit()
+  }
+
+  /// Copies the file at `source` into the attachment store and records it
+  /// as pending until a message lists its id.
+  pub fn add_attachment(&self, source: &Path) -> Result<Attachment, AttachmentError> {
+    let filename = source
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    let blob = self.blobs.put_file(source)?;
+    let attachment = Attachment {
+      id: new_id(),
+      message_id: String::new(),
+      mime_type: attachments::mime_type(&filename).into(),
+      filename,
+      size: blob.size,
+      sha256: blob.sha256,
+      path: self.blobs.resolve(&blob.path),
+      created_at: now_ms(),
+    };
+    self.pool.get()?.execute(
+      &format!(
+        "INSERT INTO attachments ({ATTACHMENT_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+      ),
+      params![
+        attachment.id,
+        None::<String>,
+        attachment.filename,
+        blob.path,
+        attachment.mime_type,
+        attachment.created_at,
+        attachment.sha256,
+        attachment.size,
+      ],
+    )?;
+    Ok(attachment)
+  }
+
+  /// The attachment with `id`, its path resolved to a file on disk.
+  pub fn attachment(&self, id: &str) -> rusqlite::Result<Option<Attachment>> {
+    let conn = self.pool.get()?;
+    conn
+      .query_row(
+        &format!("SELECT {ATTACHMENT_COLUMNS} FROM attachments WHERE id = ?1"),
+        [id],
+        |row| self.attachment_from_row(row),
+      )
+      .optional()
+  }
+
+  /// Everything stored for conversation `id`; `None` if there is no such
+  /// conversation. The content of the attached files `embed` picks is read
+  /// in as well.
+  pub fn export_conversation(
+    &self,
+    id: &str,
+    embed: impl Fn(&Attachment) -> bool,
+  ) -> Result<Option<ArchivedConversation>, AttachmentError> {
+    let conn = self.pool.get()?;
+    let conversation = conn
+      .query_row(
+        "SELECT id, title, created_at, archived_at, title_source, active_leaf_id
+         FROM conversations WHERE id = ?1",
+        [id],
+        |row| {
+          Ok(ArchivedConversation {
+            id: row.get(0)?,
+            title: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            created_at: row.get::<_, Option<i64>>(2)?.unwrap_or_default(),
+            archived_at: row.get(3)?,
+            title_source: row.get(4)?,
+            active_leaf_id: row.get(5)?,
+            messages: Vec::new(),
+          })
+        },
+      )
+      .optional()?;
+    let Some(mut conversation) = conversation else {
+      return Ok(None);
+    };
+
+    let mut stmt = conn.prepare(&format!(
+      "SELECT {ATTACHMENT_COLUMNS} FROM attachments
+       WHERE message_id IN (SELECT id FROM messages WHERE conversation_id = ?1)
+       ORDER BY created_at ASC"
+    ))?;
+    let files = stmt
+      .query_map([id], |row| self.attachment_from_row(row))?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    drop(stmt);
+    // Every branch, parents before their replies.
+    let mut stmt = conn.prepare(&format!(
+      "SELECT {MESSAGE_COLUMNS} FROM messages WHERE conversation_id = ?1
+       ORDER BY created_at ASC, rowid ASC"
+    ))?;
+    let messages = stmt
+      .query_map([id], message_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    drop(stmt);
+    drop(conn);
+
+    conversation.messages = messages
+      .into_iter()
+      .map(|message| ArchivedMessage {
+        files: files
+          .iter()
+          .filter(|file| file.message_id == message.id)
+          .map(|file| archived_attachment(file, embed(file)))
+          .collect(),
+        id: message.id,
+        parent_id: (!message.parent_id.is_empty()).then_some(message.parent_id),
+        sender: message.sender,
+        text: message.text,
+        created_at: message.created_at,
+        confidence: (message.confidence != 0.0).then_some(message.confidence),
+        needs_clarification: message.needs_clarification,
+        attachments: message.attachments,
+        metadata_json: message.metadata_json,
+      })
+      .collect();
+    Ok(Some(conversation))
+  }
+
+  /// Stores the conversations of an imported archive, copying their files
+  /// into the attachment store and inserting every row in one transaction.
+  /// Conversations, messages and attachments whose ids are already taken
+  /// get new ones. Returns the ids the conversations were stored under.
+  pub fn import_conversations(
+    &self,
+    conversations: &[ArchivedConversation],
+  ) -> Result<Vec<String>, AttachmentError> {
+    let blobs = self.put_archived_files(conversations)?;
+    let result = self.insert_archived(conversations, &blobs);
+    if result.is_err() {
+      self.discard_blobs(blobs);
+    }
+    result.map_err(Into::into)
+  }
+
+  /// Writes the files carried by `conversations` to the store, keyed by
+  /// attachment id. Nothing written is left behind on failure.
+  fn put_archived_files(
+    &self,
+    conversations: &[ArchivedConversation],
+  ) -> Result<HashMap<String, Blob>, AttachmentError> {
+    let mut blobs = HashMap::new();
+    let result = (|| -> Result<(), AttachmentError> {
+      let files = conversations
+        .iter()
+        .flat_map(|conversation| &conversation.messages)
+        .flat_map(|message| &message.files);
+      for file in files {
+        let Some(data) = &file.data else {
+          log::warn!("attachment {} has no content in the archive", file.id);
+          continue;
+        };
+        let bytes = base64::engine::general_purpose::STANDARD
+          .decode(data)
+          .map_err(|_| AttachmentError::Corrupt(file.id.clone()))?;
+        let blob = self.blobs.put(bytes.as_slice())?;
+        let intact = file.sha256.is_empty() || blob.sha256 == file.sha256;
+        blobs.insert(file.id.clone(), blob);
+        if !intact {
+          return Err(AttachmentError::Corrupt(file.id.clone()));
+        }
+      }
+      Ok(())
+    })();
+    if let Err(err) = result {
+      self.discard_blobs(blobs);
+      return Err(err);
+    }
+    Ok(blobs)
+  }
+
+  /// Deletes blobs written for an import that did not go through, unless
+  /// stored attachments share them.
+  fn discard_blobs(&self, blobs: HashMap<String, Blob>) {
+    let paths = blobs.into_values().map(|blob| blob.path).collect();
+    if let Err(err) = self.remove_unreferenced(paths) {
+      log::warn!("could not clean up after a failed import: {err}");
+    }
+  }
+
+  fn insert_archived(
+    &self,
+    conversations: &[ArchivedConversation],
+    blobs: &HashMap<String, Blob>,
+  ) -> rusqlite::Result<Vec<String>> {
+    let mut conn = self.pool.get()?;
+    let tx = conn.transaction()?;
+    let mut ids = Vec::with_capacity(conversations.len());
+    let mut message_ids = HashMap::new();
+    for conversation in conversations {
+      let conversation_id = unused_id(&tx, "conversations", &conversation.id)?;
+      tx.execute(
+        "INSERT INTO conversations (id, title, created_at, archived_at, title_source)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+          conversation_id,
+          conversation.title,
+          conversation.created_at,
+          conversation.archived_at,
+          conversation.title_source,
+        ],
+      )?;
+      for message in &conversation.messages {
+        let message_id = unused_id(&tx, "messages", &message.id)?;
+        let mut file_ids = HashMap::new();
+        for file in &message.files {
+          if blobs.contains_key(&file.id) {
+            file_ids.insert(file.id.as_str(), unused_id(&tx, "attachments", &file.id)?);
+          }
+        }
+        let attachments = message
+          .attachments
+          .iter()
+          .map(|entry| file_ids.get(entry.as_str()).unwrap_or(entry).clone())
+          .collect();
+        // The archive lists parents before their replies.
+        let parent_id = message
+          .parent_id
+          .as_deref()
+          .and_then(|parent| message_ids.get(parent))
+          .cloned()
+          .unwrap_or_default();
+        insert_message(
+          &tx,
+          &Message {
+            id: message_id.clone(),
+            conversation_id: conversation_id.clone(),
+            sender: message.sender.clone(),
+            text: message.text.clone(),
+            created_at: message.created_at,
+            confidence: message.confidence.unwrap_or_default(),
+            needs_clarification: message.needs_clarification,
+            attachments,
+            metadata_json: message.metadata_json.clone(),
+            parent_id,
+            sibling_ids: Vec::new(),
+          },
+        )?;
+        message_ids.insert(message.id.as_str(), message_id.clone());
+        for file in &message.files {
+          let (Some(file_id), Some(blob)) = (file_ids.get(file.id.as_str()), blobs.get(&file.id))
+          else {
+            continue;
+          };
+          tx.execute(
+            &format!(
+              "INSERT INTO attachments ({ATTACHMENT_COLUMNS})
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+            ),
+            params![
+              file_id,
+              message_id,
+              file.filename,
+              blob.path,
+              file.mime_type,
+              file.created_at,
+              blob.sha256,
+              blob.size,
+            ],
+          )?;
+        }
+      }
+      if let Some(leaf) = conversation
+        .active_leaf_id
+        .as_deref()
+        .and_then(|leaf| message_ids.get(leaf))
+      {
+        tx.execute(
+          "UPDATE conversations SET active_leaf_id = ?2 WHERE id = ?1",
+          params![conversation_id, leaf],
+        )?;
+      }
+      ids.push(conversation_id);
+    }
+    tx.commit()?;
+    Ok(ids)
+  }
+
+  /// Deletes attachments added more than a day ago but never sent. Ones a
+  /// message lists without being linked to it, as backends from before
+  /// they linked attachments stored them, are linked instead.
+  fn prune_pending_attachments(&self) -> rusqlite::Result<()> {
+    let mut conn = self.pool.get()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+      "UPDATE attachments SET message_id = (
+         SELECT m.id FROM messages m, json_each(
+           CASE WHEN json_valid(m.attachments_json) THEN m.attachments_json ELSE '[]' END
+         ) entry
+         WHERE entry.value = attachments.id
+         ORDER BY m.created_at ASC LIMIT 1
+       )
+       WHERE message_id IS NULL",
+      [],
+    )?;
+    let cutoff = now_ms() - PENDING_ATTACHMENT_TTL_MS;
+    let paths = attachment_paths(&tx, "WHERE message_id IS NULL AND created_at < ?1", cutoff)?;
+    tx.execute(
+      "DELETE FROM attachments WHERE message_id IS NULL AND created_at < ?1",
+      [cutoff],
+    )?;
+    tx.commit()?;
+    drop(conn);
+    self.remove_unreferenced(paths)
+  }
+
+  /// Deletes the blobs among `paths` that no row points at any more.
+  fn remove_unreferenced(&self, paths: Vec<String>) -> rusqlite::Result<()> {
+    let conn = self.pool.get()?;
+    for path in paths {
+      let referenced: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM attachments WHERE path = ?1)",
+        [&path],
+        |row| row.get(0),
+      )?;
+      if !referenced {
+        self.blobs.remove(&path);
+      }
+    }
+    Ok(())
+  }
+
+  fn attachment_from_row(&self, row: &Row) -> rusqlite::Result<Attachment> {
+    let path: Option<String> = row.get(3)?;
+    Ok(Attachment {
+      id: row.get(0)?,
+      message_id: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+      filename: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+      path: self.blobs.resolve(path.as_deref().unwrap_or_default()),
+      mime_type: row
+        .get::<_, Option<String>>(4)?
+        .unwrap_or_else(|| "application/octet-stream".into()),
+      created_at: row.get::<_, Option<i64>>(5)?.unwrap_or_default(),
+      sha256: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+      size: row.get::<_, Option<i64>>(7)?.unwrap_or_default(),
+    })
+  }
+
+  /// Messages on the active branch of `conversation_id`, oldest first,
+  /// each with the ids of its versions. A negative `limit` means no limit.
+  pub fn history(
+    &self,
+    conversation_id: &str,
+    limit: i64,
+    offset: i64,
+  ) -> rusqlite::Result<Vec<Message>> {
+    let conn = self.pool.get()?;
+    let mut stmt = conn.prepare(&format!(
+      "WITH RECURSIVE branch(message_id, depth) AS (
+         SELECT active_leaf_id, 0 FROM conversations WHERE id = ?1
+         UNION ALL
+         SELECT m.parent_id, branch.depth + 1
+         FROM branch JOIN messages m ON m.id = branch.message_id
+         WHERE m.parent_id IS NOT NULL
+       )
+       SELECT {MESSAGE_COLUMNS}, (
+         SELECT json_group_array(id) FROM (
+           SELECT s.id FROM messages s
+           WHERE s.conversation_id = messages.conversation_id
+             AND s.parent_id IS messages.parent_id
+           ORDER BY s.created_at ASC, s.rowid ASC
+         )
+       )
+       FROM branch JOIN messages ON messages.id = branch.message_id
+       ORDER BY branch.depth DESC LIMIT ?2 OFFSET ?3"
+    ))?;
+    let rows = stmt.query_map(params![conversation_id, limit, offset], |row| {
+      let mut message = message_from_row(row)?;
+      message.sibling_ids = serde_json::from_str(&row.get::<_, String>(10)?).unwrap_or_default();
+      Ok(message)
+    })?;
+    rows.collect()
+  }
+
+  /// The message with `id`, if it belongs to `conversation_id`.
+  pub fn message(&self, conversation_id: &str, id: &str) -> rusqlite::Result<Option<Message>> {
+    let conn = self.pool.get()?;
+    conn
+      .query_row(
+        &format!("SELECT {MESSAGE_COLUMNS} FROM messages WHERE id = ?1 AND conversation_id = ?2"),
+        [id, conversation_id],
+        message_from_row,
+      )
+      .optional()
+  }
+
+  /// Makes the branch through `message_id` active, down to its most
+  /// recent message. Returns `false` if the message is not part of
+  /// `conversation_id`.
+  pub fn switch_branch(&self, conversation_id: &str, message_id: &str) -> rusqlite::Result<bool> {
+    let changed = self.pool.get()?.execute(
+      "WITH RECURSIVE below(id) AS (
+         SELECT id FROM messages WHERE id = ?2 AND conversation_id = ?1
+         UNION ALL
+         SELECT m.id FROM below JOIN messages m ON m.parent_id = below.id
+       )
+       UPDATE conversations SET active_leaf_id = (
+         SELECT m.id FROM below JOIN messages m ON m.id = below.id
+         ORDER BY m.created_at DESC, m.rowid DESC LIMIT 1
+       )
+       WHERE id = ?1 AND EXISTS (SELECT 1 FROM below)",
+      params![conversation_id, message_id],
+    )?;
+    Ok(changed > 0)
+  }
+
+  /// Conversations, most recently active first; archived ones only when
+  /// `include_archived` is set. A negative `limit` means no limit.
+  pub fn conversations(
+    &self,
+    include_archived: bool,
+    limit: i64,
+    offset: i64,
+  ) -> rusqlite::Result<Vec<Conversation>> {
+    let conn = self.pool.get()?;
+    let mut stmt = conn.prepare(&format!(
+      "{CONVERSATION_QUERY} WHERE ?1 OR c.archived_at IS NULL
+       ORDER BY updated_at DESC, c.created_at DESC LIMIT ?2 OFFSET ?3"
+    ))?;
+    let rows = stmt.query_map(
+      params![include_archived, limit, offset],
+      conversation_from_row,
+    )?;
+    rows.collect()
+  }
+
+  pub fn conversation(&self, id: &str) -> rusqlite::Result<Option<Conversation>> {
+    let conn = self.pool.get()?;
+    conn
+      .query_row(
+        &format!("{CONVERSATION_QUERY} WHERE c.id = ?1"),
+        [id],
+        conversation_from_row,
+      )
+      .optional()
+  }
+
+  /// Sets the title; `None` if there is no such conversation. The title is
+  /// never replaced by an automatic one afterwards.
+  pub fn rename_conversation(
+    &self,
+    id: &str,
+    title: &str,
+  ) -> rusqlite::Result<Option<Conversation>> {
+    let changed = self.pool.get()?.execute(
+      "UPDATE conversations SET title = ?2, title_source = 'user' WHERE id = ?1",
+      params![id, title],
+    )?;
+    if changed == 0 {
+      return Ok(None);
+    }
+    self.conversation(id)
+  }
+
+  /// Text of the first user message and the first reply after it, if the
+  /// conversation has both and has not been titled yet.
+  pub fn first_exchange(&self, id: &str) -> rusqlite::Result<Option<(String, String)>> {
+    let conn = self.pool.get()?;
+    conn
+      .query_row(
+        "SELECT q.text, a.text
+         FROM conversations c
+         JOIN messages q ON q.id = (
+           SELECT id FROM messages WHERE conversation_id = c.id AND sender = 'user'
+           ORDER BY created_at ASC LIMIT 1
+         )
+         JOIN messages a ON a.id = (
+           SELECT id FROM messages
+           WHERE conversation_id = c.id AND sender <> 'user' AND created_at >= q.created_at
+           ORDER BY created_at ASC LIMIT 1
+         )
+         WHERE c.id = ?1 AND c.title_source IS NULL",
+        [id],
+        |row| {
+          Ok((
+            row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+            row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+          ))
+        },
+      )
+      .optional()
+  }
+
+  /// Sets a derived title unless the conversation has been titled in the
+  /// meantime; `None` if nothing changed.
+  pub fn set_auto_title(&self, id: &str, title: &str) -> rusqlite::Result<Option<Conversation>> {
+    let changed = self.pool.get()?.execute(
+      "UPDATE conversations SET title = ?2, title_source = 'auto'
+       WHERE id = ?1 AND title_source IS NULL",
+      params![id, title],
+    )?;
+    if changed == 0 {
+      return Ok(None);
+    }
+    self.conversation(id)
+  }
+
+  /// Archives or restores; `None` if there is no such conversation.
+  pub fn archive_conversation(
+    &self,
+    id: &str,
+    archived: bool,
+  ) -> rusqlite::Result<Option<Conversation>> {
+    // Archiving twice keeps the original time.
+    let changed = self.pool.get()?.execute(
+      "UPDATE conversations
+       SET archived_at = CASE WHEN ?2 THEN COALESCE(archived_at, ?3) END
+       WHERE id = ?1",
+      params![id, archived, now_ms()],
+    )?;
+    if changed == 0 {
+      return Ok(None);
+    }
+    self.conversation(id)
+  }
+
+  /// Messages matching `request.query` and its filters, best match first.
+  /// A query without words matches nothing.
+  pub fn search(
+    &self,
+    request: &SearchMessagesRequest,
+    limit: i64,
+  ) -> rusqlite::Result<Vec<SearchHit>> {
+    let Some(query) = search::fts_query(&request.query) else {
+      return Ok(Vec::new());
+    };
+    let conn = self.pool.get()?;
+    let mut stmt = conn.prepare(SEARCH_QUERY)?;
+    let rows = stmt.query_map(
+      params![
+        query,
+        request.sender,
+        request.from,
+        request.to,
+        request.conversation_id,
+        limit,
+        request.offset.max(0),
+        search::MATCH_START.to_string(),
+        search::MATCH_END.to_string(),
+      ],
+      |row| {
+        Ok(SearchHit {
+          message_id: row.get(0)?,
+          conversation_id: row.get(1)?,
+          conversation_title: row.get(2)?,
+          sender: row.get(3)?,
+          created_at: row.get::<_, Option<i64>>(4)?.unwrap_or_default(),
+          snippet: search::highlight(&row.get::<_, String>(5)?),
+          // bm25 is lower for better matches.
+          score: -row.get::<_, f64>(6)?,
+        })
+      },
+    )?;
+    rows.collect()
+  }
+
+  /// Deletes the conversation with its messages and their attachments,
+  /// keeping files other conversations still use. Returns whether anything
+  /// was deleted.
+  pub fn delete_conversation(&self, id: &str) -> rusqlite::Result<bool> {
+    let mut conn = self.pool.get()?;
+    let tx = conn.transaction()?;
+    let paths = attachment_paths(
+      &tx,
+      "WHERE message_id IN (SELECT id FROM messages WHERE conversation_id = ?1)",
+      id,
+    )?;
+    tx.execute(
+      "DELETE FROM attachments
+       WHERE message_id IN (SELECT id FROM messages WHERE conversation_id = ?1)",
+      [id],
+    )?;
+    let messages = tx.execute("DELETE FROM messages WHERE conversation_id = ?1", [id])?;
+    let conversations = tx.execute("DELETE FROM conversations WHERE id = ?1", [id])?;
+    tx.commit()?;
+    drop(conn);
+    // The conversation is gone either way; leftover files only waste space.
+    if let Err(err) = self.remove_unreferenced(paths) {
+      log::warn!("could not clean up attachments of conversation {id}: {err}");
+    }
+    Ok(messages + conversations > 0)
+  }
+}
+
+/// Selects a conversation `c` with its counts and last message, for
+/// [`conversation_from_row`]; append a `WHERE` clause.
+const CONVERSATION_QUERY: &str = "
+  SELECT c.id, c.title, c.created_at, c.archived_at,
+    (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id),
+    (SELECT COUNT(*) FROM attachments a JOIN messages m ON m.id = a.message_id
+     WHERE m.conversation_id = c.id),
+    last.text, last.sender,
+    COALESCE(last.created_at, c.created_at, 0) AS updated_at
+  FROM conversations c
+  LEFT JOIN messages last ON last.id = (
+    SELECT id FROM messages WHERE conversation_id = c.id
+    ORDER BY created_at DESC LIMIT 1
+  )";
+
+/// Full-text search; empty filters (`''` or 0) match everything.
+const SEARCH_QUERY: &str = "
+  SELECT m.id, m.conversation_id, COALESCE(c.title, ''), m.sender, m.created_at,
+    snippet(messages_fts, 0, ?8, ?9, '…', 16), bm25(messages_fts)
+  FROM messages_fts
+  JOIN messages m ON m.rowid = messages_fts.rowid
+  LEFT JOIN conversations c ON c.id = m.conversation_id
+  WHERE messages_fts MATCH ?1
+    AND (?2 = '' OR m.sender = ?2)
+    AND (?3 = 0 OR m.created_at >= ?3)
+    AND (?4 = 0 OR m.created_at <= ?4)
+    AND (?5 = '' OR m.conversation_id = ?5)
+  ORDER BY bm25(messages_fts)
+  LIMIT ?6 OFFSET ?7";
+
+fn conversation_from_row(row: &Row) -> rusqlite::Result<Conversation> {
+  let text: Option<String> = row.get(6)?;
+  Ok(Conversation {
+    id: row.get(0)?,
+    title: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+    created_at: row.get::<_, Option<i64>>(2)?.unwrap_or_default(),
+    archived: row.get::<_, Option<i64>>(3)?.is_some(),
+    message_count: row.get(4)?,
+    attachment_count: row.get(5)?,
+    last_message_preview: text.as_deref().map(preview).unwrap_or_default(),
+    last_message_sender: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
+    updated_at: row.get(8)?,
+  })
+}
+
+/// First [`PREVIEW_CHARS`] characters of `text` on one line.
+fn preview(text: &str) -> String {
+  let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  match line.char_indices().nth(PREVIEW_CHARS) {
+    Some((end, _)) => format!("{}…", &line[..end]),
+    None => line,
+  }
+}
+
+/// `attachment`, with its file read in if `embed` is set; a missing file is
+/// left out.
+fn archived_attachment(attachment: &Attachment, embed: bool) -> ArchivedAttachment {
+  let data = if embed {
+    match std::fs::read(&attachment.path) {
+      Ok(bytes) => Some(base64::engine::general_purpose::STANDARD.encode(bytes)),
+      Err(err) => {
+        log::warn!(
+          "attachment {} is missing from {}: {err}",
+          attachment.id,
+          attachment.path.display()
+        );
+        None
+      }
+    }
+  } else {
+    None
+  };
+  ArchivedAttachment {
+    id: attachment.id.clone(),
+    filename: attachment.filename.clone(),
+    mime_type: attachment.mime_type.clone(),
+    created_at: attachment.created_at,
+    size: attachment.size,
+    sha256: attachment.sha256.clone(),
+    data,
+  }
+}
+
+/// Distinct blob paths of the attachment rows selected by `filter`.
+fn attachment_paths(
+  tx: &Transaction,
+  filter: &str,
+  param: impl rusqlite::ToSql,
+) -> rusqlite::Result<Vec<String>> {
+  let mut stmt = tx.prepare(&format!(
+    "SELECT DISTINCT path FROM attachments {filter} AND path IS NOT NULL"
+  ))?;
+  let rows = stmt.query_map([param], |row| row.get(0))?;
+  rows.collect()
+}
+
+/// `id`, or a fresh one if `table` already has a row with it.
+fn unused_id(tx: &Transaction, table: &str, id: &str) -> rusqlite::Result<String> {
+  let taken: bool = tx.query_row(
+    &format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE id = ?1)"),
+    [id],
+    |row| row.get(0),
+  )?;
+  Ok(if taken {
+    new_id()
+  } else {
+    id.to_string()
+  })
+}
+
+/// Inserts `message` under its `parent_id`, or as the first of a branch if
+/// that is empty. The insert trigger makes it the active leaf.
+fn insert_message(tx: &Transaction, message: &Message) -> rusqlite::Result<()> {
+  if message.parent_id.is_empty() {
+    // The trigger attaches a message without a parent to the active leaf.
+    tx.execute(
+      "UPDATE conversations SET active_leaf_id = NULL WHERE id = ?1",
+      [&message.conversation_id],
+    )?;
+  }
+  tx.execute(
+    &format!(
+      "INSERT INTO messages ({MESSAGE_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+    ),
+    params![
+      message.id,
+      message.conversation_id,
+      message.sender,
+      message.text,
+      message.created_at,
+      (message.confidence != 0.0).then_some(message.confidence),
+      message.needs_clarification,
+      serde_json::to_string(&message.attachments).unwrap_or_else(|_| "[]".into()),
+      if message.metadata_json.is_empty() {
+        "{}"
+      } else {
+        &message.metadata_json
+      },
+      (!message.parent_id.is_empty()).then_some(&message.parent_id),
+    ],
+  )?;
+  Ok(())
+}
+
+fn ensure_conversation(tx: &Transaction, id: &str, created_at: i64) -> rusqlite::Result<()> {
+  tx.execute(
+    "INSERT OR IGNORE INTO conversations (id, title, created_at) VALUES (?1, '', ?2)",
+    params![id, created_at],
+  )?;
+  Ok(())
+}
+
+/// Reads a row selected with [`MESSAGE_COLUMNS`]. Rows written by older
+/// backend versions may have NULLs or malformed JSON; those read as unset.
+fn message_from_row(row: &Row) -> rusqlite::Result<Message> {
+  let attachments: Option<String> = row.get(7)?;
+  Ok(Message {
+    id: row.get(0)?,
+    conversation_id: row.get(1)?,
+    sender: row.get(2)?,
+    text: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+    created_at: row.get::<_, Option<i64>>(4)?.unwrap_or_default(),
+    confidence: row.get::<_, Option<f64>>(5)?.unwrap_or_default(),
+    needs_clarification: row.get::<_, Option<bool>>(6)?.unwrap_or_default(),
+    attachments: attachments
+      .and_then(|json| serde_json::from_str(&json).ok())
+      .unwrap_or_default(),
+    metadata_json: row
+      .get::<_, Option<String>>(8)?
+      .unwrap_or_else(|| "{}".into()),
+    parent_id: row.get::<_, Option<String>>(9)?.unwrap_or_default(),
+    sibling_ids: Vec::new(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::history::testing::ScratchDir;
+
+  /// Stores a message in conversation `c1`, following the active branch or
+  /// `parent_id`, or as a new version of `edit_of`.
+  fn say(store: &ChatStore, id: &str, sender: &str, parent_id: &str, edit_of: &str) -> Message {
+    let mut message = Message {
+      id: id.into(),
+      conversation_id: "c1".into(),
+      sender: sender.into(),
+      text: id.into(),
+      created_at: now_ms(),
+      parent_id: parent_id.into(),
+      ..Default::default()
+    };
+    store.store_message(&mut message, edit_of).unwrap();
+    message
+  }
+
+  fn ids(messages: &[Message]) -> Vec<&str> {
+    messages.iter().map(|message| message.id.as_str()).collect()
+  }
+
+  /// `q1 → a1 → q2 → a2` in conversation `c1`.
+  fn exchange(store: &ChatStore) {
+    say(store, "q1", "user", "", "");
+    say(store, "a1", "agent", "", "");
+    say(store, "q2", "user", "", "");
+    say(store, "a2", "agent", "", "");
+  }
+
+  #[test]
+  fn messages_follow_the_active_branch() {
+    let dir = ScratchDir::new("chat-linear");
+    let store = ChatStore::open(dir.path()).unwrap();
+    exchange(&store);
+    let history = store.history("c1", -1, 0).unwrap();
+    assert_eq!(ids(&history), ["q1", "a1", "q2", "a2"]);
+    let parents: Vec<_> = history
+      .iter()
+      .map(|message| message.parent_id.as_str())
+      .collect();
+    assert_eq!(parents, ["", "q1", "a1", "q2"]);
+    assert_eq!(history[2].sibling_ids, ["q2"]);
+    // Paging counts from the start of the branch.
+    assert_eq!(ids(&store.history("c1", 2, 1).unwrap()), ["a1", "q2"]);
+  }
+
+  #[test]
+  fn editing_starts_a_branch() {
+    let dir = ScratchDir::new("chat-edit");
+    let store = ChatStore::open(dir.path()).unwrap();
+    exchange(&store);
+
+    let edit = say(&store, "q2b", "user", "", "q2");
+    assert_eq!(edit.parent_id, "a1");
+    let history = store.history("c1", -1, 0).unwrap();
+    assert_eq!(ids(&history), ["q1", "a1", "q2b"]);
+    assert_eq!(history[2].sibling_ids, ["q2", "q2b"]);
+    // The next message continues the edited branch.
+    say(&store, "a2b", "agent", "", "");
+    assert_eq!(
+      ids(&store.history("c1", -1, 0).unwrap()),
+      ["q1", "a1", "q2b", "a2b"]
+    );
+
+    // Editing the first message starts over without a parent.
+    let edit = say(&store, "q1b", "user", "", "q1");
+    assert_eq!(edit.parent_id, "");
+    let history = store.history("c1", -1, 0).unwrap();
+    assert_eq!(ids(&history), ["q1b"]);
+    assert_eq!(history[0].sibling_ids, ["q1", "q1b"]);
+  }
+
+  #[test]
+  fn switching_shows_a_sibling_down_to_its_latest_message() {
+    let dir = ScratchDir::new("chat-switch");
+    let store = ChatStore::open(dir.path()).unwrap();
+    exchange(&store);
+    say(&store, "q2b", "user", "", "q2");
+    say(&store, "a2b", "agent", "", "");
+
+    assert!(store.switch_branch("c1", "q2").unwrap());
+    assert_eq!(
+      ids(&store.history("c1", -1, 0).unwrap()),
+      ["q1", "a1", "q2", "a2"]
+    );
+    assert!(store.switch_branch("c1", "q2b").unwrap());
+    assert_eq!(
+      ids(&store.history("c1", -1, 0).unwrap()),
+      ["q1", "a1", "q2b", "a2b"]
+    );
+    // Switching at the root follows the latest message below it.
+    assert!(store.switch_branch("c1", "q1").unwrap());
+    assert_eq!(
+      ids(&store.history("c1", -1, 0).unwrap()),
+      ["q1", "a1", "q2b", "a2b"]
+    );
+
+    assert!(!store.switch_branch("c1", "missing").unwrap());
+    assert!(!store.switch_branch("other", "q2").unwrap());
+    assert_eq!(store.history("c1", -1, 0).unwrap().len(), 4);
+  }
+
+  #[test]
+  fn regenerated_replies_are_versions_of_each_other() {
+    let dir = ScratchDir::new("chat-regenerate");
+    let store = ChatStore::open(dir.path()).unwrap();
+    exchange(&store);
+    // Another branch is shown while the reply to `q1` is generated.
+    say(&store, "q2b", "user", "", "q2");
+
+    let reply = say(&store, "a1b", "agent", "q1", "");
+    assert_eq!(reply.parent_id, "q1");
+    let history = store.history("c1", -1, 0).unwrap();
+    assert_eq!(ids(&history), ["q1", "a1b"]);
+    assert_eq!(history[1].sibling_ids, ["a1", "a1b"]);
+
+    // The earlier version and everything after it are still there.
+    assert!(store.switch_branch("c1", "a1").unwrap());
+    assert_eq!(
+      ids(&store.history("c1", -1, 0).unwrap()),
+      ["q1", "a1", "q2b"]
+    );
+  }
+
+  #[test]
+  fn automatic_titles_never_replace_a_rename() {
+    let dir = ScratchDir::new("chat-titles");
+    let store = ChatStore::open(dir.path()).unwrap();
+    exchange(&store);
+    assert_eq!(
+      store.first_exchange("c1").unwrap(),
+      Some(("q1".to_string(), "a1".to_string()))
+    );
+    store.rename_conversation("c1", "Mine").unwrap().unwrap();
+    assert_eq!(store.first_exchange("c1").unwrap(), None);
+    assert!(store.set_auto_title("c1", "Derived").unwrap().is_none());
+    assert_eq!(store.conversation("c1").unwrap().unwrap().title, "Mine");
+
+    // An automatic title is set once, and a rename still replaces it.
+    let mut message = Message {
+      id: "x".into(),
+      conversation_id: "c2".into(),
+      sender: "user".into(),
+      created_at: now_ms(),
+      ..Default::default()
+    };
+    store.store_message(&mut message, "").unwrap();
+    let titled = store.set_auto_title("c2", "Derived").unwrap().unwrap();
+    assert_eq!(titled.title, "Derived");
+    assert!(store.set_auto_title("c2", "Again").unwrap().is_none());
+    let renamed = store.rename_conversation("c2", "Mine").unwrap().unwrap();
+    assert_eq!(renamed.title, "Mine");
+  }
+
+  #[test]
+  fn attachment_referenced_by_a_message_is_not_pruned() {
+    let dir = ScratchDir::new("chat-prune");
+    let store = ChatStore::open(dir.path()).unwrap();
+    let file = dir.path().join("notes.txt");
+    std::fs::write(&file, "notes").unwrap();
+    let sent = store.add_attachment(&file).unwrap();
+    let unsent = store.add_attachment(&file).unwrap();
+    let old = now_ms() - PENDING_ATTACHMENT_TTL_MS - 1;
+    {
+      let conn = store.pool.get().unwrap();
+      conn
+        .execute("UPDATE attachments SET created_at = ?1", [old])
+        .unwrap();
+      // Listed but not linked, as older backends stored messages.
+      conn
+        .execute(
+          "INSERT INTO conversations (id, title, created_at) VALUES ('c1', '', 1)",
+          [],
+        )
+        .unwrap();
+      conn
+        .execute(
+          "INSERT INTO messages (id, conversation_id, sender, text, created_at, attachments_json)
+           VALUES ('q', 'c1', 'user', 'q', 1, ?1)",
+          [format!(r#"["{}"]"#, sent.id)],
+        )
+        .unwrap();
+    }
+    drop(store);
+
+    let store = ChatStore::open(dir.path()).unwrap();
+    let kept = store.attachment(&sent.id).unwrap().unwrap();
+    assert_eq!(kept.message_id, "q");
+    assert_eq!(std::fs::read(kept.path).unwrap(), b"notes");
+    assert!(store.attachment(&unsent.id).unwrap().is_none());
+  }
+
+  #[test]
+  fn import_renames_ids_already_taken() {
+    let dir = ScratchDir::new("chat-import");
+    let store = ChatStore::open(dir.path()).unwrap();
+    let file = dir.path().join("notes.txt");
+    std::fs::write(&file, "notes").unwrap();
+    let attachment = store.add_attachment(&file).unwrap();
+    let mut question = Message {
+      id: "q".into(),
+      conversation_id: "c1".into(),
+      sender: "user".into(),
+      text: "q".into(),
+      created_at: now_ms(),
+      attachments: vec![attachment.id.clone()],
+      ..Default::default()
+    };
+    store.store_message(&mut question, "").unwrap();
+    say(&store, "a", "assistant", "", "");
+
+    let exported = store.export_conversation("c1", |_| true).unwrap().unwrap();
+    let imported = store.import_conversations(&[exported]).unwrap();
+    let [copy] = imported.as_slice() else {
+      panic!("expected one conversation, got {imported:?}");
+    };
+    assert_ne!(copy, "c1");
+
+    let history = store.history(copy, -1, 0).unwrap();
+    let [question, answer] = history.as_slice() else {
+      panic!("expected two messages, got {history:?}");
+    };
+    assert!(question.id != "q" && answer.id != "a");
+    assert_eq!(question.parent_id, "");
+    assert_eq!(answer.parent_id, question.id);
+    let [file_id] = question.attachments.as_slice() else {
+      panic!("expected one attachment, got {:?}", question.attachments);
+    };
+    assert_ne!(file_id, &attachment.id);
+    let copied = store.attachment(file_id).unwrap().unwrap();
+    assert_eq!(copied.message_id, question.id);
+    assert_eq!(std::fs::read(copied.path).unwrap(), b"notes");
+    // The original is untouched.
+    assert_eq!(ids(&store.history("c1", -1, 0).unwrap()), ["q", "a"]);
+  }
+}
@@ -0,0 +1,30 @@
+//! Helpers shared by tests that need files on disk.
+
+use std::path::{Path, PathBuf};
+
+use crate::chat::now_ms;
+
+/// A fresh directory under the system temp dir, removed when dropped.
+pub struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+  pub fn new(name: &str) -> Self {
+    let dir = std::env::temp_dir().join(format!(
+      "video-analyzer-{name}-{}-{}",
+      std::process::id(),
+      now_ms()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    Self(dir)
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.0
+  }
+}
+
+impl Drop for ScratchDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
@@ -0,0 +1,293 @@
+//! Titles for conversations, derived once the first user/agent exchange is
+//! stored.
+//!
+//! `GenerationAgent.GenerateSummary` is asked first; when no generation
+//! agent answers in time, a keyword pass over the exchange names the
+//! conversation instead. Titles are only ever written to untitled
+//! conversations, so a rename by the user always wins.
+//!
+//! `ChatServer` titles the replies it stores; when the backend hosts chat,
+//! the chat commands call [`Titler::after_reply`] for the replies it stores.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use super::store::ChatStore;
+use crate::grpc::Backend;
+use crate::proto::chat::Conversation;
+use crate::proto::common::TaskRequest;
+use crate::proto::generation::SummaryRequest;
+
+/// How long to wait for a model-written title before using keywords.
+const GENERATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MAX_TITLE_CHARS: usize = 60;
+/// Quoting and markup models wrap titles in.
+const QUOTES: [char; 5] = ['"', '\'', '*', '#', '`'];
+/// Keywords in a keyword title.
+const MAX_KEYWORDS: usize = 4;
+/// Words taken from the start of the message when it has no keywords.
+const FALLBACK_WORDS: usize = 6;
+
+/// Words that say nothing about what a conversation is about.
+const STOPWORDS: &[&str] = &[
+  "about", "above", "after", "again", "all", "also", "and", "any", "are", "because", "been",
+  "before", "being", "below", "between", "both", "but", "can", "could", "did", "does", "doing",
+  "down", "during", "each", "few", "for", "from", "further", "get", "had", "has", "have", "having",
+  "hello", "help", "her", "here", "hers", "him", "his", "how", "into", "its", "just", "know",
+  "like", "let", "more", "most", "much", "need", "not", "now", "off", "once", "only", "other",
+  "our", "ours", "out", "over", "own", "please", "same", "she", "should", "some", "such", "tell",
+  "than", "thank", "thanks", "that", "the", "their", "theirs", "them", "then", "there", "these",
+  "they", "this", "those", "through", "too", "under", "until", "very", "want", "was", "way",
+  "were", "what", "when", "where", "which", "while", "who", "whom", "why", "will", "with", "would",
+  "yes", "you", "your", "yours",
+];
+
+/// Names conversations after their first exchange; cheap to clone into
+/// tasks.
+#[derive(Clone)]
+pub struct Titler {
+  store: Arc<ChatStore>,
+  backend: Backend,
+  updates: broadcast::Sender<Conversation>,
+}
+
+impl Titler {
+  pub fn new(
+    store: Arc<ChatStore>,
+    backend: Backend,
+    updates: broadcast::Sender<Conversation>,
+  ) -> Self {
+    Self {
+      store,
+      backend,
+      updates,
+    }
+  }
+
+  /// Titles `conversation_id` in the background if it is untitled and now
+  /// holds a user message and a reply.
+  pub fn after_reply(&self, conversation_id: String) {
+    let titler = self.clone();
+    tokio::spawn(async move {
+      if let Err(err) = titler.name(&conversation_id).await {
+        log::warn!("could not title conversation {conversation_id}: {err}");
+      }
+    });
+  }
+
+  async fn name(&self, conversation_id: &str) -> rusqlite::Result<()> {
+    let Some((user, agent)) = self.store.first_exchange(conversation_id)? else {
+      return Ok(());
+    };
+    let title = match self.generated_title(&user, &agent).await {
+      Some(title) => title,
+      None => match keyword_title(&user, &agent) {
+        Some(title) => title,
+        None => return Ok(()),
+      },
+    };
+    if let Some(conversation) = self.store.set_auto_title(conversation_id, &title)? {
+      // Nobody listening is fine.
+      let _ = self.updates.send(conversation);
+    }
+    Ok(())
+  }
+
+  /// Asks the generation agent for a title; `None` if it is unavailable,
+  /// slow or answers with nothing usable.
+  async fn generated_title(&self, user: &str, agent: &str) -> Option<String> {
+    let request = SummaryRequest {
+      task: Some(TaskRequest {
+        task_id: uuid::Uuid::new_v4().to_string(),
+        agent_type_hint: "generation".into(),
+        ..Default::default()
+      }),
+      prompt: format!(
+        "Write a short title, at most six words, for a conversation that starts:\n\n\
+         User: {user}\n\nAssistant: {agent}\n\nAnswer with the title only."
+      ),
+      max_tokens: 16,
+    };
+    let call = async {
+      let mut client = self.backend.generation().await.ok()?;
+      client.generate_summary(request).await.ok()
+    };
+    let response = tokio::time::timeout(GENERATION_TIMEOUT, call)
+      .await
+      .ok()??;
+    clean_title(&response.into_inner().summary_text)
+  }
+}
+
+/// First line of a model answer without quotes, a `Title:` label or
+/// trailing punctuation, cut to [`MAX_TITLE_CHARS`].
+fn clean_title(text: &str) -> Option<String> {
+  let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+  let line = line
+    .strip_prefix("Title:")
+    .or_else(|| line.strip_prefix("title:"))
+    .unwrap_or(line);
+  let title = line
+    .trim()
+    .trim_start_matches(QUOTES)
+    .trim_end_matches(|c| QUOTES.contains(&c) || ".!:;,".contains(c))
+    .trim();
+  (!title.is_empty()).then(|| truncate(title))
+}
+
+/// Builds a title from the words that best describe the exchange: frequent,
+/// not stopwords, and favouring what the user wrote. Falls back to the
+/// start of the user's message; `None` if that is empty too.
+pub fn keyword_title(user: &str, agent: &str) -> Option<String> {
+  // (score, position of first use) per keyword
+  let mut keywords: HashMap<String, (u32, usize)> = HashMap::new();
+  let user_words = words(user);
+  let agent_words = words(agent);
+  for (position, word) in user_words.iter().chain(&agent_words).enumerate() {
+    if !is_keyword(word) {
+      continue;
+    }
+    let weight = if position < user_words.len() { 2 } else { 1 };
+    keywords
+      .entry(word.to_lowercase())
+      .and_modify(|(score, _)| *score += weight)
+      .or_insert((weight, position));
+  }
+
+  let mut ranked: Vec<_> = keywords.into_iter().collect();
+  ranked.sort_by(|(_, (a_score, a_pos)), (_, (b_score, b_pos))| {
+    b_score.cmp(a_score).then(a_pos.cmp(b_pos))
+  });
+  ranked.truncate(MAX_KEYWORDS);
+  // Read in the order the words were used.
+  ranked.sort_by_key(|(_, (_, position))| *position);
+
+  let title = if ranked.is_empty() {
+    user_words
+      .iter()
+      .take(FALLBACK_WORDS)
+      .copied()
+      .collect::<Vec<_>>()
+      .join(" ")
+  } else {
+    ranked
+      .iter()
+      .map(|(word, _)| capitalize(word))
+      .collect::<Vec<_>>()
+      .join(" ")
+  };
+  (!title.is_empty()).then(|| truncate(&title))
+}
+
+fn words(text: &str) -> Vec<&str> {
+  text
+    .split(|c: char| !c.is_alphanumeric() && c != '\'')
+    .map(|word| word.trim_matches('\''))
+    .filter(|word| !word.is_empty())
+    .collect()
+}
+
+fn is_keyword(word: &str) -> bool {
+  word.chars().count() >= 3
+    && !word.chars().all(|c| c.is_numeric())
+    && !STOPWORDS.contains(&word.to_lowercase().as_str())
+}
+
+fn capitalize(word: &str) -> String {
+  let mut chars = word.chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => String::new(),
+  }
+}
+
+fn truncate(title: &str) -> String {
+  match title.char_indices().nth(MAX_TITLE_CHARS) {
+    Some((end, _)) => format!("{}…", title[..end].trim_end()),
+    None => title.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn clean_title_strips_labels_quotes_and_punctuation() {
+    let cases = [
+      (
+        "Title: \"Cats and Dogs\".\nSecond line",
+        Some("Cats and Dogs"),
+      ),
+      ("\n  **Video Summary**!  ", Some("Video Summary")),
+      ("title: 'Trip notes';", Some("Trip notes")),
+      ("`Release plan`", Some("Release plan")),
+      ("", None),
+      ("  \n \n", None),
+      ("\"\"", None),
+      ("Title:", None),
+    ];
+    for (text, title) in cases {
+      assert_eq!(clean_title(text).as_deref(), title, "{text:?}");
+    }
+  }
+
+  #[test]
+  fn titles_are_capped() {
+    let long = "word ".repeat(30);
+    let title = clean_title(&long).unwrap();
+    assert!(title.chars().count() <= MAX_TITLE_CHARS + 1);
+    assert!(title.ends_with("word…"));
+    let exact = "x".repeat(MAX_TITLE_CHARS);
+    assert_eq!(clean_title(&exact).unwrap(), exact);
+  }
+
+  #[test]
+  fn keyword_title_skips_stopwords_and_favours_the_user() {
+    let cases = [
+      (
+        "Can you tell me about the transcription of this video?",
+        "The transcription of the video is ready.",
+        Some("Transcription Video Ready"),
+      ),
+      // At most four keywords, the user's first, read in order.
+      (
+        "Compare rainfall and temperature",
+        "Humidity, rainfall, temperature and wind",
+        Some("Compare Rainfall Temperature Humidity"),
+      ),
+      // Quotes and punctuation are not part of a word.
+      (
+        "'Quoted' words, don't \"panic\"!",
+        "",
+        Some("Quoted Words Don't Panic"),
+      ),
+      // Numbers and short words are no keywords.
+      ("Is it ok in 2024?", "", Some("Is it ok in 2024")),
+      ("?!", "Whatever works", Some("Whatever Works")),
+      ("", "", None),
+      ("  ...  ", "!!", None),
+    ];
+    for (user, agent, title) in cases {
+      assert_eq!(
+        keyword_title(user, agent).as_deref(),
+        title,
+        "{user:?} / {agent:?}"
+      );
+    }
+  }
+
+  #[test]
+  fn keyword_title_is_capped() {
+    let user = "Supercalifragilisticexpialidocious Pneumonoultramicroscopic \
+                Antidisestablishmentarianism Floccinaucinihilipilification";
+    let title = keyword_title(user, "").unwrap();
+    assert!(title.chars().count() <= MAX_TITLE_CHARS + 1);
+    assert!(title.starts_with("Supercalifragilisticexpialidocious "));
+    assert!(title.ends_with('…'));
+  }
+}
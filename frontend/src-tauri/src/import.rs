@@ -0,0 +1,56 @@
+//! Importing conversations from an archive written by
+//! [`crate::export`], picked in a native dialog, so history can move
+//! between machines.
+
+use std::path::PathBuf;
+
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+use crate::chat::{chat_store, ChatConversation};
+use crate::error::{Error, Result};
+use crate::history::archive::Archive;
+
+/// Asks for an archive and adds its conversations to the history, under
+/// new ids where theirs are already taken. Resolves to the conversations
+/// imported, or `null` if the user cancelled.
+#[tauri::command]
+pub async fn import_conversations(app: AppHandle) -> Result<Option<Vec<ChatConversation>>> {
+  let store = chat_store(&app)?;
+
+  let (tx, rx) = oneshot::channel();
+  app
+    .dialog()
+    .file()
+    .set_title("Import conversations")
+    .add_filter("Conversation archive", &["json"])
+    .pick_file(move |path| {
+      let _ = tx.send(path);
+    });
+  // A dialog closed without answering counts as cancelled.
+  let Some(path) = rx
+    .await
+    .ok()
+    .flatten()
+    .and_then(|path| path.as_path().map(PathBuf::from))
+  else {
+    return Ok(None);
+  };
+
+  // Decodes and copies every attached file; keep it off the runtime.
+  let conversations = tauri::async_runtime::spawn_blocking(move || {
+    let bytes = std::fs::read(&path).map_err(|err| Error::Import(path.clone(), err))?;
+    let archive = Archive::from_slice(&bytes)?;
+    let ids = store.import_conversations(&archive.conversations)?;
+    let mut conversations: Vec<ChatConversation> = Vec::with_capacity(ids.len());
+    for id in ids {
+      if let Some(conversation) = store.conversation(&id)? {
+        conversations.push(conversation.into());
+      }
+    }
+    Ok::<_, Error>(conversations)
+  })
+  .await??;
+  Ok(Some(conversations))
+}
@@ -1,16 +1,182 @@
+use std::sync::Arc;
+
+use tauri::Manager as _;
+
+pub mod attachments;
+pub mod auth;
+pub mod chat;
+pub mod config;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod grpc;
+pub mod health;
+pub mod history;
+pub mod import;
+pub mod manager;
+pub mod proto;
+pub mod sidecar;
+pub mod tasks;
+pub mod transport;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
+    .plugin(tauri_plugin_dialog::init())
+    .manage(chat::ActiveStreams::default())
+    .manage(tasks::TaskWatchers::default())
+    .manage(health::HealthMonitor::default())
     .setup(|app| {
-      if cfg!(debug_assertions) {
-        app.handle().plugin(
-          tauri_plugin_log::Builder::default()
-            .level(log::LevelFilter::Info)
-            .build(),
-        )?;
+      // Before reading the configuration, so warnings about it are kept.
+      app.handle().plugin(
+        tauri_plugin_log::Builder::default()
+          .level(if cfg!(debug_assertions) {
+            log::LevelFilter::Info
+          } else {
+            log::LevelFilter::Warn
+          })
+          .build(),
+      )?;
+      let config = config::Config::from_env();
+      app.manage(grpc::Backend::new(&config)?);
+
+      let data_dir = match config.data_dir.clone() {
+        Some(dir) => dir,
+        None => app.path().app_data_dir()?,
+      };
+      if let Err(err) = auth::write_token_file(&data_dir, &config.auth_token) {
+        log::error!("could not write the auth token for agents: {err}");
       }
+
+      if config.spawn_backend {
+        match config.backend_dir.clone() {
+          Some(dir) => {
+            let launch = sidecar::Launch {
+              python: config.python.clone(),
+              dir,
+              data_dir: data_dir.clone(),
+              auth_token: config.auth_token.clone(),
+              socket: transport::socket_path(
+                config.transport,
+                &data_dir,
+                transport::BACKEND_SOCKET,
+              ),
+            };
+            let app_handle = app.handle().clone();
+            app.manage(sidecar::Sidecar::spawn(launch, move |uri| {
+              let backend = app_handle.state::<grpc::Backend>();
+              match uri {
+                Some(uri) => {
+                  if let Err(err) = backend.retarget(uri) {
+                    log::error!("backend reported an unusable address: {err}");
+                  }
+                }
+                None => backend.detach(),
+              }
+            }));
+          }
+          None => log::warn!("VIDEO_ANALYZER_BACKEND_DIR is not set; not starting the backend"),
+        }
+      }
+
+      let store = manager::TaskStore::open(&data_dir).or_else(|err| {
+        log::error!("task store unavailable, tasks will not persist: {err}");
+        manager::TaskStore::in_memory()
+      })?;
+      let manager = Arc::new(manager::Manager::new(
+        manager::Settings {
+          agent_timeout: config.agent_timeout,
+          ack_timeout: config.ack_timeout,
+          max_retries: config.max_retries,
+        },
+        store,
+      ));
+      app.manage(manager.clone());
+
+      events::forward_agent_health(app.handle().clone(), &manager);
+      tauri::async_runtime::spawn(health::monitor(app.handle().clone()));
+      tauri::async_runtime::spawn(manager::maintain(manager.clone()));
+      let listen = transport::Listen::choose(
+        config.transport,
+        &data_dir,
+        transport::MANAGER_SOCKET,
+        config.manager_addr,
+      );
+      app
+        .state::<grpc::Backend>()
+        .retarget_manager(listen.uri())?;
+      // Attachments live in the shell's store even when the backend serves
+      // chat; both sides share the database, and the backend links the
+      // attachments a message lists as it stores the message.
+      let chat_store = match history::ChatStore::open(&data_dir) {
+        Ok(store) => {
+          let store = Arc::new(store);
+          app.manage(store.clone());
+          Some(store)
+        }
+        Err(err) => {
+          log::error!("chat history unavailable, leaving chat to the backend: {err}");
+          None
+        }
+      };
+      // The shell serves the calls the backend lacks even when the backend
+      // hosts chat.
+      let chat = match chat_store {
+        Some(store) => {
+          let backend = app.state::<grpc::Backend>();
+          backend.use_native_history(listen.uri())?;
+          if config.native_chat {
+            backend.use_native_chat(listen.uri())?;
+          }
+          let chat = history::ChatServer::new(store, backend.inner().clone());
+          events::forward_conversation_updates(app.handle().clone(), &chat);
+          if !config.native_chat {
+            // The backend stores replies without titling conversations;
+            // the chat commands hand them to the shell's titler instead.
+            app.manage(chat.titler());
+          }
+          Some(chat)
+        }
+        None => None,
+      };
+      let auth_token = config.auth_token.clone();
+      tauri::async_runtime::spawn(async move {
+        if let Err(err) = manager::serve(manager, chat, listen, &auth_token).await {
+          log::error!("agent manager stopped: {err}");
+        }
+      });
       Ok(())
     })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .invoke_handler(tauri::generate_handler![
+      chat::send_message,
+      chat::get_history,
+      chat::switch_branch,
+      chat::stream_responses,
+      chat::regenerate_reply,
+      chat::cancel_stream,
+      chat::list_conversations,
+      chat::rename_conversation,
+      chat::archive_conversation,
+      chat::delete_conversation,
+      chat::search_messages,
+      attachments::add_attachment,
+      attachments::get_attachment,
+      attachments::read_attachment,
+      export::export_conversation,
+      import::import_conversations,
+      tasks::submit_task,
+      tasks::watch_task,
+      tasks::cancel_task,
+      health::backend_status,
+    ])
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|app, event| {
+      // Tauri exits once the last window closes; take the backend with it.
+      if let tauri::RunEvent::Exit = event {
+        if let Some(sidecar) = app.try_state::<sidecar::Sidecar>() {
+          sidecar.shutdown();
+        }
+      }
+    });
 }
@@ -0,0 +1,288 @@
+//! Rust implementation of `genai.manager.AgentManager`.
+//!
+//! Agents register, keep a `Heartbeat` stream open and receive `run-task`
+//! commands on it; clients submit work with `AssignTask` and watch it with
+//! `StreamProgress`, and stop it with `CancelTask`. All state lives in a
+//! shared [`Manager`].
+//!
+//! Liveness comes from the heartbeat streams: an agent that stops sending
+//! heartbeats for [`Manager::timeout`], or whose stream closes, turns stale,
+//! stops receiving tasks and has its unfinished tasks requeued.
+//!
+//! Tasks are persisted in a [`TaskStore`], dispatched by priority and
+//! retried with backoff when they fail.
+//!
+//! The server also answers `grpc.health.v1.Health`: `AgentManager` is
+//! always serving, and each agent service is serving while a healthy agent
+//! of its type is registered. When the shell hosts chat, `ChatService` (see
+//! [`crate::history`]) is served alongside.
+
+mod state;
+mod store;
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+
+use crate::auth::RequireToken;
+use crate::history::ChatServer;
+use crate::proto::chat::chat_service_server::ChatServiceServer;
+use crate::proto::common::{AgentInfo, AgentType, ProgressUpdate, TaskRequest};
+use crate::proto::generation::generation_agent_server;
+use crate::proto::manager::agent_manager_server::{AgentManager, AgentManagerServer};
+use crate::proto::manager::{
+  AgentList, AssignResponse, CancelTaskRequest, CancelTaskResponse, HeartbeatRequest,
+  HeartbeatResponse, RegisterResponse, TaskProgressRequest,
+};
+use crate::proto::transcription::transcription_agent_server;
+use crate::proto::vision::vision_agent_server;
+use crate::transport::Listen;
+
+pub use state::{is_terminal, HealthChange, Liveness, Manager, Settings, Task};
+pub(crate) use store::timestamp_ms;
+pub use store::{TaskStore, TaskStoreError};
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Agent types with a gRPC service of their own, and that service's name.
+pub const AGENT_SERVICES: [(AgentType, &str); 3] = [
+  (
+    AgentType::AgentTranscription,
+    transcription_agent_server::SERVICE_NAME,
+  ),
+  (AgentType::AgentVision, vision_agent_server::SERVICE_NAME),
+  (
+    AgentType::AgentGeneration,
+    generation_agent_server::SERVICE_NAME,
+  ),
+];
+
+/// Serves `manager`, and `chat` if given, on `listen` until the server
+/// fails. Calls must carry `auth_token`.
+pub async fn serve(
+  manager: Arc<Manager>,
+  chat: Option<ChatServer>,
+  listen: Listen,
+  auth_token: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+  let auth = RequireToken::new(auth_token);
+  let (mut reporter, health) = tonic_health::server::health_reporter();
+  reporter
+    .set_serving::<AgentManagerServer<AgentManagerService>>()
+    .await;
+  if chat.is_some() {
+    reporter
+      .set_serving::<ChatServiceServer<ChatServer>>()
+      .await;
+  }
+  tokio::spawn(report_agent_health(manager.clone(), reporter));
+
+  let router = Server::builder()
+    .add_service(InterceptedService::new(health, auth.clone()))
+    .add_service(AgentManagerServer::with_interceptor(
+      AgentManagerService { manager },
+      auth.clone(),
+    ))
+    .add_optional_service(chat.map(|chat| ChatServiceServer::with_interceptor(chat, auth)));
+  log::info!("agent manager listening on {}", listen.uri());
+  match listen {
+    Listen::Tcp(addr) => router.serve(addr).await?,
+    #[cfg(unix)]
+    Listen::Unix(path) => {
+      let listener = crate::transport::bind_unix(&path)?;
+      router
+        .serve_with_incoming(tokio_stream::wrappers::UnixListenerStream::new(listener))
+        .await?
+    }
+    #[cfg(not(unix))]
+    Listen::Unix(_) => return Err("Unix sockets are not supported on this platform".into()),
+  }
+  Ok(())
+}
+
+/// Keeps the health status of each agent service in step with the agents
+/// registered for it.
+async fn report_agent_health(manager: Arc<Manager>, mut reporter: HealthReporter) {
+  let mut changes = manager.subscribe_health();
+  loop {
+    for (agent_type, service) in AGENT_SERVICES {
+      let status = if manager.has_healthy_agent(agent_type) {
+        ServingStatus::Serving
+      } else {
+        ServingStatus::NotServing
+      };
+      reporter.set_service_status(service, status).await;
+    }
+    // Any change may flip a service; lagging just means recomputing.
+    if let Err(RecvError::Closed) = changes.recv().await {
+      return;
+    }
+  }
+}
+
+/// Periodically applies [`Manager::check_liveness`] and dispatches retries
+/// that are due; runs forever.
+pub async fn maintain(manager: Arc<Manager>) {
+  // Check a few times per timeout so staleness is noticed promptly.
+  let period = (manager.timeout() / 4).max(Duration::from_millis(250));
+  let mut ticker = tokio::time::interval(period);
+  loop {
+    ticker.tick().await;
+    manager.check_liveness();
+    manager.dispatch_pending();
+  }
+}
+
+pub struct AgentManagerService {
+  manager: Arc<Manager>,
+}
+
+#[tonic::async_trait]
+impl AgentManager for AgentManagerService {
+  async fn register_agent(
+    &self,
+    request: Request<AgentInfo>,
+  ) -> Result<Response<RegisterResponse>, Status> {
+    let assigned_id = self.manager.register(request.into_inner());
+    Ok(Response::new(RegisterResponse {
+      ok: true,
+      assigned_id,
+      message: "Registered".into(),
+    }))
+  }
+
+  type HeartbeatStream = ResponseStream<HeartbeatResponse>;
+
+  async fn heartbeat(
+    &self,
+    request: Request<Streaming<HeartbeatRequest>>,
+  ) -> Result<Response<Self::HeartbeatStream>, Status> {
+    let mut incoming = request.into_inner();
+    let manager = self.manager.clone();
+    let (tx, rx) = mpsc::channel(4);
+    tokio::spawn(async move {
+      // Answer every heartbeat, even with no commands, so the agent's
+      // response loop keeps turning.
+      let stream = manager.open_stream();
+      let mut agent_id = None;
+      while let Ok(Some(heartbeat)) = incoming.message().await {
+        let Some(agent) = heartbeat.agent else {
+          continue;
+        };
+        agent_id = Some(agent.id.clone());
+        let commands = manager.heartbeat(agent, stream);
+        if tx.send(Ok(HeartbeatResponse { commands })).await.is_err() {
+          break;
+        }
+      }
+      if let Some(agent_id) = agent_id {
+        manager.disconnected(&agent_id, stream);
+      }
+    });
+    Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+  }
+
+  async fn assign_task(
+    &self,
+    request: Request<TaskRequest>,
+  ) -> Result<Response<AssignResponse>, Status> {
+    let response = match self.manager.assign(request.into_inner()) {
+      Ok((_, assigned_agent_id)) => AssignResponse {
+        accepted: true,
+        message: if assigned_agent_id.is_empty() {
+          "Queued until an agent is available".into()
+        } else {
+          "Assigned".into()
+        },
+        assigned_agent_id,
+      },
+      Err(message) => AssignResponse {
+        accepted: false,
+        assigned_agent_id: String::new(),
+        message,
+      },
+    };
+    Ok(Response::new(response))
+  }
+
+  async fn list_agents(&self, _request: Request<()>) -> Result<Response<AgentList>, Status> {
+    Ok(Response::new(AgentList {
+      agents: self.manager.agents(),
+    }))
+  }
+
+  type StreamProgressStream = ResponseStream<ProgressUpdate>;
+
+  /// Sends the task's current state first, then every change until it
+  /// reaches a terminal status.
+  async fn stream_progress(
+    &self,
+    request: Request<TaskProgressRequest>,
+  ) -> Result<Response<Self::StreamProgressStream>, Status> {
+    let task_id = request.into_inner().task_id;
+    // Subscribe before reading the snapshot so no update falls in between.
+    let mut updates = self.manager.subscribe();
+    let task = self
+      .manager
+      .task(&task_id)
+      .ok_or_else(|| Status::not_found(format!("unknown task {task_id}")))?;
+
+    let manager = self.manager.clone();
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+      let mut current = task.progress();
+      loop {
+        let done = is_terminal(current.status());
+        if tx.send(Ok(current)).await.is_err() || done {
+          return;
+        }
+        current = loop {
+          match updates.recv().await {
+            Ok(update) if update.task_id == task_id => break update,
+            Ok(_) => {}
+            Err(RecvError::Lagged(skipped)) => {
+              log::warn!("progress stream for {task_id} skipped {skipped} updates");
+              // One of them may have been the terminal update; catch up from
+              // the task itself.
+              match manager.task(&task_id) {
+                Some(task) => break task.progress(),
+                None => return,
+              }
+            }
+            Err(RecvError::Closed) => return,
+          }
+        };
+      }
+    });
+    Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+  }
+
+  async fn cancel_task(
+    &self,
+    request: Request<CancelTaskRequest>,
+  ) -> Result<Response<CancelTaskResponse>, Status> {
+    let request = request.into_inner();
+    let cancelled = self
+      .manager
+      .cancel(&request.task_id, &request.reason)
+      .ok_or_else(|| Status::not_found(format!("unknown task {}", request.task_id)))?;
+    Ok(Response::new(CancelTaskResponse {
+      cancelled,
+      message: if cancelled {
+        "Cancelled".into()
+      } else {
+        "Task already finished".into()
+      },
+    }))
+  }
+}
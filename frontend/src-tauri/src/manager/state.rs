@@ -0,0 +1,1217 @@
+//! Agent registry and task table behind the agent manager. Agents live in
+//! memory only; tasks are mirrored to a [`TaskStore`] so queued work
+//! survives a restart.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use prost_types::Timestamp;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::store::{StoredTask, TaskStore, TaskWriter};
+use crate::proto::common::{
+  AgentHealth, AgentInfo, AgentType, ProgressUpdate, TaskRequest, TaskResult, TaskStatus,
+};
+
+/// Capacity of the progress and health fan-outs; slow subscribers past
+/// this lag and skip ahead rather than block agents.
+const BROADCAST_CAPACITY: usize = 256;
+
+/// A stale agent is dropped from the registry once it has been silent for
+/// this many liveness timeouts.
+const EVICT_AFTER_TIMEOUTS: u32 = 10;
+
+/// How often a `run-task` command is sent without acknowledgement before
+/// the attempt counts as failed.
+const MAX_DELIVERIES: u32 = 3;
+
+/// Backoff before the first retry of a failed task; doubles per retry.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Settings {
+  /// Silence after which an agent is marked stale.
+  pub agent_timeout: Duration,
+  /// Time a `run-task` command may go unacknowledged before it is resent.
+  pub ack_timeout: Duration,
+  /// How many times a failed task is retried before it stays failed.
+  pub max_retries: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Task {
+  pub request: TaskRequest,
+  /// Agent currently responsible for the task; empty while it waits for
+  /// one to become available.
+  pub agent_id: String,
+  pub status: TaskStatus,
+  pub percent: i32,
+  pub message: String,
+  pub updated_at: Timestamp,
+  /// Failed attempts that have been retried so far.
+  pub retries: u32,
+  /// While backing off after a failure, when the task may run again.
+  retry_at: Option<SystemTime>,
+  delivery: Delivery,
+}
+
+/// Delivery state of a task's `run-task` command to its agent.
+#[derive(Debug, Clone, Default)]
+struct Delivery {
+  /// How many times the command has been sent.
+  attempts: u32,
+  /// When it was last sent; `None` while the task waits in the agent's
+  /// queue.
+  sent_at: Option<Instant>,
+  /// Set once the agent reports anything about the task.
+  acked: bool,
+}
+
+impl Task {
+  pub fn progress(&self) -> ProgressUpdate {
+    ProgressUpdate {
+      task_id: self.request.task_id.clone(),
+      status: self.status.into(),
+      percent: self.percent,
+      message: self.message.clone(),
+      updated_at: Some(self.updated_at),
+    }
+  }
+
+  fn is_active(&self) -> bool {
+    !is_terminal(self.status)
+  }
+
+  /// Dispatch order: highest priority first, then oldest.
+  fn rank(&self) -> (Reverse<i32>, Option<(i64, i32)>) {
+    let created_at = self.request.created_at.map(|at| (at.seconds, at.nanos));
+    (Reverse(self.request.priority), created_at)
+  }
+
+  fn stored(&self) -> StoredTask {
+    StoredTask {
+      request: self.request.clone(),
+      status: self.status,
+      percent: self.percent,
+      message: self.message.clone(),
+      agent_id: self.agent_id.clone(),
+      retries: self.retries,
+      retry_at: self.retry_at,
+    }
+  }
+
+  fn command(&self) -> String {
+    let uri = self
+      .request
+      .input
+      .as_ref()
+      .map(|input| input.uri.as_str())
+      .unwrap_or_default();
+    format!("run-task:{}:{uri}", self.request.task_id)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Liveness {
+  Healthy,
+  /// Missed heartbeats for longer than the timeout, or its heartbeat
+  /// stream closed.
+  Stale,
+  /// Stale for long enough to be dropped from the registry.
+  Evicted,
+}
+
+/// Published whenever an agent's [`Liveness`] changes.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthChange {
+  pub agent_id: String,
+  pub name: String,
+  pub agent_type: String,
+  pub health: Liveness,
+}
+
+struct Agent {
+  info: AgentInfo,
+  seen: Instant,
+  health: Liveness,
+  /// Heartbeat stream the agent last reported on, from
+  /// [`Manager::open_stream`].
+  stream: Option<u64>,
+}
+
+impl Agent {
+  fn change(&self) -> HealthChange {
+    HealthChange {
+      agent_id: self.info.id.clone(),
+      name: self.info.name.clone(),
+      agent_type: self.info.r#type().as_str_name().to_string(),
+      health: self.health,
+    }
+  }
+}
+
+struct Inner {
+  /// Registered agents keyed by `AgentInfo.id`.
+  agents: HashMap<String, Agent>,
+  tasks: HashMap<String, Task>,
+  /// `cancel-task` commands waiting for each agent's next heartbeat.
+  cancels: HashMap<String, Vec<String>>,
+  store: TaskWriter,
+  max_retries: u32,
+  progress: broadcast::Sender<ProgressUpdate>,
+  health: broadcast::Sender<HealthChange>,
+}
+
+pub struct Manager {
+  inner: Mutex<Inner>,
+  settings: Settings,
+  next_stream: AtomicU64,
+}
+
+impl Manager {
+  /// Creates a manager backed by `store`, requeueing the tasks that were
+  /// still queued or running when it was last used. The requeue is recorded
+  /// as a `TASK_QUEUED` update like any other.
+  pub fn new(settings: Settings, store: TaskStore) -> Self {
+    let restored = store.unfinished().unwrap_or_else(|err| {
+      log::error!("could not load unfinished tasks: {err}");
+      Vec::new()
+    });
+    let mut inner = Inner {
+      agents: HashMap::new(),
+      tasks: HashMap::new(),
+      cancels: HashMap::new(),
+      store: TaskWriter::spawn(store),
+      max_retries: settings.max_retries,
+      progress: broadcast::channel(BROADCAST_CAPACITY).0,
+      health: broadcast::channel(BROADCAST_CAPACITY).0,
+    };
+    if !restored.is_empty() {
+      log::info!("restoring {} unfinished task(s)", restored.len());
+    }
+    for stored in restored {
+      let task = Task {
+        request: stored.request,
+        agent_id: String::new(),
+        status: TaskStatus::TaskQueued,
+        percent: 0,
+        message: "Requeued after restart".into(),
+        updated_at: now(),
+        retries: stored.retries,
+        retry_at: stored.retry_at,
+        delivery: Delivery::default(),
+      };
+      let task_id = task.request.task_id.clone();
+      let progress = task.progress();
+      inner.tasks.insert(task_id.clone(), task);
+      inner.persist(&task_id);
+      inner.record_progress(&progress);
+      let _ = inner.progress.send(progress);
+    }
+    Self {
+      inner: Mutex::new(inner),
+      settings,
+      next_stream: AtomicU64::new(0),
+    }
+  }
+
+  pub fn timeout(&self) -> Duration {
+    self.settings.agent_timeout
+  }
+
+  /// Adds or replaces an agent, assigning an id if it has none.
+  pub fn register(&self, mut info: AgentInfo) -> String {
+    if info.id.is_empty() {
+      info.id = uuid::Uuid::new_v4().to_string();
+    }
+    info.last_seen = Some(now());
+    let id = info.id.clone();
+    log::info!(
+      "agent registered: {id} name={} type={:?}",
+      info.name,
+      info.r#type()
+    );
+
+    let mut inner = self.inner.lock().unwrap();
+    let previous = inner.agents.insert(
+      id.clone(),
+      Agent {
+        info,
+        seen: Instant::now(),
+        health: Liveness::Healthy,
+        stream: None,
+      },
+    );
+    if previous.map_or(true, |agent| agent.health != Liveness::Healthy) {
+      inner.publish_health(&id);
+      inner.dispatch_pending();
+    }
+    id
+  }
+
+  /// Records a heartbeat and returns the commands for the agent to run.
+  ///
+  /// Agents report task state through `capabilities` using the same tokens
+  /// `backend/mcp_server.py` understands: `progress:<task_id>:<percent>` and
+  /// `result:<task_id>:<output_uri>`, separated by `;`. They acknowledge a
+  /// `run-task` command with `ack:<task_id>` (or by reporting on the task).
+  /// Pending `cancel-task` commands come before any new `run-task`.
+  ///
+  /// Dispatch is pull-based: each agent has at most one task in flight and
+  /// receives its next one, by priority, on the first heartbeat after that
+  /// task finishes. An unacknowledged command is resent after the ack
+  /// timeout, and the attempt fails after [`MAX_DELIVERIES`] sends.
+  ///
+  /// `stream` identifies the heartbeat stream it arrived on (see
+  /// [`Manager::open_stream`]).
+  pub fn heartbeat(&self, info: AgentInfo, stream: u64) -> Vec<String> {
+    let mut inner = self.inner.lock().unwrap();
+    let agent = inner.agents.entry(info.id.clone()).or_insert_with(|| {
+      log::info!("agent {} sent a heartbeat before registering", info.id);
+      Agent {
+        info: AgentInfo {
+          capabilities: String::new(),
+          ..info.clone()
+        },
+        seen: Instant::now(),
+        health: Liveness::Stale,
+        stream: None,
+      }
+    });
+    agent.seen = Instant::now();
+    agent.stream = Some(stream);
+    agent.info.last_seen = Some(now());
+    if agent.health != Liveness::Healthy {
+      agent.health = Liveness::Healthy;
+      log::info!("agent {} is healthy", info.id);
+      inner.publish_health(&info.id);
+      inner.dispatch_pending();
+    }
+
+    for report in info.capabilities.split(';').filter_map(Report::parse) {
+      inner.apply_report(&info.id, report);
+    }
+    let mut commands = inner.cancels.remove(&info.id).unwrap_or_default();
+    commands.extend(inner.next_command(&info.id, self.settings.ack_timeout));
+    commands
+  }
+
+  /// Returns a fresh id for a heartbeat stream.
+  pub fn open_stream(&self) -> u64 {
+    self.next_stream.fetch_add(1, Ordering::Relaxed)
+  }
+
+  /// Marks an agent stale as soon as its heartbeat stream closes, rather
+  /// than waiting for the timeout. Ignored if the agent has since moved to
+  /// another stream, e.g. after reconnecting before the old one was torn
+  /// down.
+  pub fn disconnected(&self, agent_id: &str, stream: u64) {
+    let mut inner = self.inner.lock().unwrap();
+    if inner
+      .agents
+      .get(agent_id)
+      .is_some_and(|agent| agent.health == Liveness::Healthy && agent.stream == Some(stream))
+    {
+      inner.mark_stale(agent_id, "heartbeat stream closed");
+    }
+  }
+
+  /// Marks agents stale once they have been silent for the timeout and
+  /// evicts those silent for much longer. Called periodically.
+  pub fn check_liveness(&self) {
+    let timeout = self.settings.agent_timeout;
+    let evict_after = timeout * EVICT_AFTER_TIMEOUTS;
+    let mut inner = self.inner.lock().unwrap();
+    let silent: Vec<(String, Liveness, Duration)> = inner
+      .agents
+      .values()
+      .map(|agent| (agent.info.id.clone(), agent.health, agent.seen.elapsed()))
+      .collect();
+
+    for (agent_id, health, elapsed) in silent {
+      if health == Liveness::Healthy && elapsed > timeout {
+        inner.mark_stale(&agent_id, "missed heartbeats");
+      } else if health == Liveness::Stale && elapsed > evict_after {
+        if let Some(mut agent) = inner.agents.remove(&agent_id) {
+          log::info!("evicting agent {agent_id}");
+          inner.cancels.remove(&agent_id);
+          agent.health = Liveness::Evicted;
+          let _ = inner.health.send(agent.change());
+        }
+      }
+    }
+  }
+
+  /// Hands queued tasks, including retries whose backoff has elapsed, to
+  /// available agents. Called periodically.
+  pub fn dispatch_pending(&self) {
+    self.inner.lock().unwrap().dispatch_pending();
+  }
+
+  /// Records `request` and routes it to the least busy healthy agent
+  /// matching `agent_type_hint`, which receives a `run-task` command once
+  /// higher-priority work ahead of it is done.
+  ///
+  /// Returns the task id and the chosen agent, which is empty if none is
+  /// available yet and the task waits in the queue.
+  pub fn assign(&self, mut request: TaskRequest) -> Result<(String, String), String> {
+    let wanted = wanted_type(&request.agent_type_hint)?;
+    if request.task_id.is_empty() {
+      request.task_id = uuid::Uuid::new_v4().to_string();
+    }
+    if request.created_at.is_none() {
+      request.created_at = Some(now());
+    }
+
+    let mut inner = self.inner.lock().unwrap();
+    if inner.tasks.contains_key(&request.task_id) {
+      return Err(format!("Task {} already exists", request.task_id));
+    }
+    let task_id = request.task_id.clone();
+    let task = Task {
+      request,
+      agent_id: String::new(),
+      status: TaskStatus::TaskQueued,
+      percent: 0,
+      message: "Queued".into(),
+      updated_at: now(),
+      retries: 0,
+      retry_at: None,
+      delivery: Delivery::default(),
+    };
+    let progress = task.progress();
+    inner.tasks.insert(task_id.clone(), task);
+    inner.persist(&task_id);
+    inner.record_progress(&progress);
+
+    let agent_id = inner.pick_agent(wanted).unwrap_or_default();
+    if agent_id.is_empty() {
+      log::info!("task {task_id} queued until an agent is available");
+    } else {
+      inner.dispatch(&task_id, &agent_id);
+    }
+    Ok((task_id, agent_id))
+  }
+
+  /// Cancels a queued or running task, publishing a final `TASK_CANCELLED`
+  /// update. An agent that was already sent the task gets a
+  /// `cancel-task:<task_id>` command to stop it and remove partial output.
+  ///
+  /// Returns `None` for an unknown task and `Some(false)` if it had already
+  /// finished.
+  pub fn cancel(&self, task_id: &str, reason: &str) -> Option<bool> {
+    let mut inner = self.inner.lock().unwrap();
+    let task = inner.tasks.get(task_id)?;
+    if !task.is_active() {
+      return Some(false);
+    }
+    let percent = task.percent;
+    if task.delivery.sent_at.is_some() {
+      let agent_id = task.agent_id.clone();
+      log::info!("telling agent {agent_id} to cancel task {task_id}");
+      inner
+        .cancels
+        .entry(agent_id)
+        .or_default()
+        .push(format!("cancel-task:{task_id}"));
+    }
+
+    let message = match reason.trim() {
+      "" => "Cancelled".to_string(),
+      reason => reason.to_string(),
+    };
+    inner.update(task_id, TaskStatus::TaskCancelled, percent, message.clone());
+    inner.record_result(task_id, false, String::new(), message);
+    Some(true)
+  }
+
+  /// Lists registered agents with their current health.
+  pub fn agents(&self) -> Vec<AgentInfo> {
+    let inner = self.inner.lock().unwrap();
+    let mut agents: Vec<_> = inner
+      .agents
+      .values()
+      .map(|agent| {
+        let mut info = agent.info.clone();
+        info.set_health(match agent.health {
+          Liveness::Healthy => AgentHealth::AgentHealthy,
+          Liveness::Stale | Liveness::Evicted => AgentHealth::AgentStale,
+        });
+        info
+      })
+      .collect();
+    agents.sort_by(|a, b| a.id.cmp(&b.id));
+    agents
+  }
+
+  pub fn has_healthy_agent(&self, agent_type: AgentType) -> bool {
+    self
+      .inner
+      .lock()
+      .unwrap()
+      .agents
+      .values()
+      .any(|agent| agent.health == Liveness::Healthy && agent.info.r#type() == agent_type)
+  }
+
+  pub fn task(&self, task_id: &str) -> Option<Task> {
+    self.inner.lock().unwrap().tasks.get(task_id).cloned()
+  }
+
+  /// Subscribes to progress for every task; callers filter by `task_id`.
+  pub fn subscribe(&self) -> broadcast::Receiver<ProgressUpdate> {
+    self.inner.lock().unwrap().progress.subscribe()
+  }
+
+  /// Subscribes to agent health changes.
+  pub fn subscribe_health(&self) -> broadcast::Receiver<HealthChange> {
+    self.inner.lock().unwrap().health.subscribe()
+  }
+}
+
+impl Inner {
+  /// Picks the healthy agent of type `wanted` (any type if `None`) with the
+  /// fewest unfinished tasks, breaking ties by id.
+  fn pick_agent(&self, wanted: Option<AgentType>) -> Option<String> {
+    self
+      .agents
+      .values()
+      .filter(|agent| agent.health == Liveness::Healthy)
+      .filter(|agent| wanted.map_or(true, |wanted| agent.info.r#type() == wanted))
+      .map(|agent| {
+        let load = self
+          .tasks
+          .values()
+          .filter(|task| task.agent_id == agent.info.id && task.is_active())
+          .count();
+        (load, &agent.info.id)
+      })
+      .min()
+      .map(|(_, id)| id.clone())
+  }
+
+  /// Gives `task_id` to `agent_id`; it is sent on a later heartbeat.
+  fn dispatch(&mut self, task_id: &str, agent_id: &str) {
+    let Some(task) = self.tasks.get_mut(task_id) else {
+      return;
+    };
+    task.agent_id = agent_id.to_string();
+    task.retry_at = None;
+    task.delivery = Delivery::default();
+    log::info!("assigned task {task_id} to agent {agent_id}");
+    self.persist(task_id);
+  }
+
+  /// Picks the `run-task` command to send on this heartbeat: a resend of the
+  /// in-flight task if its ack is overdue, otherwise the agent's
+  /// highest-priority unsent task once nothing is in flight.
+  fn next_command(&mut self, agent_id: &str, ack_timeout: Duration) -> Option<String> {
+    loop {
+      let in_flight = self
+        .tasks
+        .values()
+        .find(|task| {
+          task.agent_id == agent_id && task.is_active() && task.delivery.sent_at.is_some()
+        })
+        .map(|task| (task.request.task_id.clone(), task.delivery.clone()));
+
+      let task_id = match in_flight {
+        Some((_, delivery))
+          if delivery.acked
+            || delivery
+              .sent_at
+              .is_some_and(|at| at.elapsed() < ack_timeout) =>
+        {
+          return None;
+        }
+        Some((task_id, delivery)) if delivery.attempts >= MAX_DELIVERIES => {
+          let message = format!("Agent {agent_id} did not acknowledge the task");
+          self.fail(&task_id, message);
+          // The task no longer blocks the agent; look again.
+          continue;
+        }
+        Some((task_id, _)) => {
+          log::warn!("redelivering task {task_id} to agent {agent_id}");
+          task_id
+        }
+        None => self
+          .tasks
+          .values()
+          .filter(|task| task.agent_id == agent_id && task.is_active())
+          .min_by_key(|task| task.rank())?
+          .request
+          .task_id
+          .clone(),
+      };
+
+      let task = self.tasks.get_mut(&task_id)?;
+      task.delivery.attempts += 1;
+      task.delivery.sent_at = Some(Instant::now());
+      return Some(task.command());
+    }
+  }
+
+  /// Hands tasks without an agent to whichever healthy agents can take
+  /// them, highest priority first. Retries wait out their backoff.
+  fn dispatch_pending(&mut self) {
+    let now = SystemTime::now();
+    let mut pending: Vec<&Task> = self
+      .tasks
+      .values()
+      .filter(|task| task.agent_id.is_empty() && task.is_active())
+      .filter(|task| task.retry_at.map_or(true, |at| at <= now))
+      .collect();
+    pending.sort_by_key(|task| task.rank());
+    let pending: Vec<String> = pending
+      .into_iter()
+      .map(|task| task.request.task_id.clone())
+      .collect();
+
+    for task_id in pending {
+      let wanted = wanted_type(&self.tasks[&task_id].request.agent_type_hint).unwrap_or(None);
+      if let Some(agent_id) = self.pick_agent(wanted) {
+        self.dispatch(&task_id, &agent_id);
+      }
+    }
+  }
+
+  /// Stops routing to `agent_id` and puts its unfinished tasks back in the
+  /// queue for another agent.
+  fn mark_stale(&mut self, agent_id: &str, reason: &str) {
+    let Some(agent) = self.agents.get_mut(agent_id) else {
+      return;
+    };
+    agent.health = Liveness::Stale;
+    log::warn!("agent {agent_id} is stale: {reason}");
+    self.publish_health(agent_id);
+
+    let orphaned: Vec<String> = self
+      .tasks
+      .values()
+      .filter(|task| task.agent_id == agent_id && task.is_active())
+      .map(|task| task.request.task_id.clone())
+      .collect();
+    for task_id in orphaned {
+      if let Some(task) = self.tasks.get_mut(&task_id) {
+        task.agent_id.clear();
+        task.delivery = Delivery::default();
+      }
+      let message = format!("Requeued: agent {agent_id} stopped responding");
+      self.update(&task_id, TaskStatus::TaskQueued, 0, message);
+    }
+    self.dispatch_pending();
+  }
+
+  fn apply_report(&mut self, agent_id: &str, report: Report) {
+    // A task requeued away from a stale agent belongs to its new agent now.
+    let Some(task) = self
+      .tasks
+      .get_mut(report.task_id())
+      .filter(|task| task.agent_id == agent_id)
+    else {
+      return;
+    };
+    task.delivery.acked = true;
+
+    match report {
+      Report::Ack { task_id } => {
+        if task.status == TaskStatus::TaskQueued {
+          let message = format!("Agent {agent_id} started");
+          self.update(&task_id, TaskStatus::TaskRunning, 0, message);
+        }
+      }
+      Report::Progress { task_id, percent } => {
+        let message = format!("Agent {agent_id} at {percent}%");
+        self.update(&task_id, TaskStatus::TaskRunning, percent, message);
+      }
+      // Agents report failures as result tokens with an `ERROR` output,
+      // e.g. `result:<id>:ERROR_NO_TRANSCRIPT`.
+      Report::Result { task_id, output } if output.starts_with("ERROR") => {
+        self.fail(&task_id, output);
+      }
+      Report::Result { task_id, output } => {
+        if self.update(&task_id, TaskStatus::TaskSucceeded, 100, "Completed".into()) {
+          self.record_result(&task_id, true, output, String::new());
+        }
+      }
+    }
+  }
+
+  /// Handles a failed attempt: requeues the task after a backoff while it
+  /// has retries left, otherwise fails it for good.
+  fn fail(&mut self, task_id: &str, error: String) {
+    let max_retries = self.max_retries;
+    let Some(task) = self.tasks.get_mut(task_id).filter(|task| task.is_active()) else {
+      return;
+    };
+    if task.retries >= max_retries {
+      if self.update(task_id, TaskStatus::TaskFailed, 100, error.clone()) {
+        self.record_result(task_id, false, String::new(), error);
+      }
+      return;
+    }
+
+    task.retries += 1;
+    let delay = retry_delay(task.retries);
+    task.retry_at = Some(SystemTime::now() + delay);
+    task.agent_id.clear();
+    task.delivery = Delivery::default();
+    log::warn!(
+      "task {task_id} failed ({error}), retry {} of {max_retries} in {delay:?}",
+      task.retries
+    );
+    let message = format!(
+      "{error}; retry {} of {max_retries} in {}s",
+      task.retries,
+      delay.as_secs()
+    );
+    self.update(task_id, TaskStatus::TaskQueued, 0, message);
+  }
+
+  /// Moves a task to `status`, records it and notifies subscribers.
+  /// Updates for unknown or already finished tasks, and repeats of the
+  /// current state, are dropped; returns whether the update was applied.
+  fn update(&mut self, task_id: &str, status: TaskStatus, percent: i32, message: String) -> bool {
+    let Some(task) = self.tasks.get_mut(task_id) else {
+      return false;
+    };
+    if !task.is_active()
+      || (task.status == status && task.percent == percent && task.message == message)
+    {
+      return false;
+    }
+    task.status = status;
+    task.percent = percent;
+    task.message = message;
+    task.updated_at = now();
+    let progress = task.progress();
+    self.persist(task_id);
+    self.record_progress(&progress);
+    // No receivers is fine: nobody is watching this task.
+    let _ = self.progress.send(progress);
+    true
+  }
+
+  // Store failures are logged rather than surfaced: the in-memory state
+  // stays authoritative and agents keep working without persistence.
+
+  fn persist(&self, task_id: &str) {
+    let Some(task) = self.tasks.get(task_id) else {
+      return;
+    };
+    self.store.save(task.stored());
+  }
+
+  fn record_progress(&self, progress: &ProgressUpdate) {
+    self.store.record_progress(progress.clone());
+  }
+
+  fn record_result(&self, task_id: &str, success: bool, output_uri: String, error: String) {
+    let result = TaskResult {
+      task_id: task_id.to_string(),
+      success,
+      output_uri,
+      result_json: String::new(),
+      error_message: error,
+      completed_at: Some(now()),
+    };
+    self.store.record_result(result);
+  }
+
+  fn publish_health(&self, agent_id: &str) {
+    if let Some(agent) = self.agents.get(agent_id) {
+      let _ = self.health.send(agent.change());
+    }
+  }
+}
+
+enum Report {
+  Ack { task_id: String },
+  Progress { task_id: String, percent: i32 },
+  Result { task_id: String, output: String },
+}
+
+impl Report {
+  fn parse(token: &str) -> Option<Self> {
+    let token = token.trim();
+    if let Some(task_id) = token.strip_prefix("ack:") {
+      return Some(Self::Ack {
+        task_id: task_id.to_string(),
+      });
+    }
+    if let Some(rest) = token.strip_prefix("progress:") {
+      let (task_id, percent) = rest.split_once(':')?;
+      let percent = percent.parse().ok()?;
+      return Some(Self::Progress {
+        task_id: task_id.to_string(),
+        percent,
+      });
+    }
+    let (task_id, output) = token.strip_prefix("result:")?.split_once(':')?;
+    Some(Self::Result {
+      task_id: task_id.to_string(),
+      output: output.to_string(),
+    })
+  }
+
+  fn task_id(&self) -> &str {
+    match self {
+      Self::Ack { task_id } | Self::Progress { task_id, .. } | Self::Result { task_id, .. } => {
+        task_id
+      }
+    }
+  }
+}
+
+/// Backoff before retry number `retry` (1-based).
+fn retry_delay(retry: u32) -> Duration {
+  let factor = 1u32 << retry.saturating_sub(1).min(16);
+  RETRY_BASE_DELAY.saturating_mul(factor).min(RETRY_MAX_DELAY)
+}
+
+pub fn is_terminal(status: TaskStatus) -> bool {
+  matches!(
+    status,
+    TaskStatus::TaskSucceeded | TaskStatus::TaskFailed | TaskStatus::TaskCancelled
+  )
+}
+
+/// Parses `agent_type_hint`; `None` means any agent will do.
+fn wanted_type(hint: &str) -> Result<Option<AgentType>, String> {
+  match hint.trim() {
+    "" => Ok(None),
+    hint => parse_agent_type(hint)
+      .map(Some)
+      .ok_or_else(|| format!("Unknown agent type {hint:?}")),
+  }
+}
+
+/// Accepts both the short hints documented in `common.proto`
+/// (`"transcription"`) and enum names (`"AGENT_TRANSCRIPTION"`).
+fn parse_agent_type(hint: &str) -> Option<AgentType> {
+  let name = hint.to_ascii_uppercase();
+  AgentType::from_str_name(&name).or_else(|| AgentType::from_str_name(&format!("AGENT_{name}")))
+}
+
+pub fn now() -> Timestamp {
+  SystemTime::now().into()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::history::testing::ScratchDir;
+  use crate::manager::store::TASKS_DB;
+  use crate::proto::common::FileRef;
+
+  fn settings() -> Settings {
+    Settings {
+      agent_timeout: Duration::from_secs(60),
+      ack_timeout: Duration::from_secs(60),
+      max_retries: 2,
+    }
+  }
+
+  fn manager(settings: Settings) -> Manager {
+    Manager::new(settings, TaskStore::in_memory().unwrap())
+  }
+
+  /// A heartbeat from agent `id` reporting `capabilities`.
+  fn agent(id: &str, capabilities: &str) -> AgentInfo {
+    AgentInfo {
+      id: id.into(),
+      name: id.into(),
+      r#type: AgentType::AgentGeneration.into(),
+      capabilities: capabilities.into(),
+      ..Default::default()
+    }
+  }
+
+  /// A task created `created_at` seconds after the epoch.
+  fn request(task_id: &str, priority: i32, created_at: i64) -> TaskRequest {
+    TaskRequest {
+      task_id: task_id.into(),
+      priority,
+      created_at: Some(Timestamp {
+        seconds: created_at,
+        nanos: 0,
+      }),
+      ..Default::default()
+    }
+  }
+
+  /// Waits for the task store's writer thread to make `check` pass.
+  fn eventually<T>(mut check: impl FnMut() -> Option<T>) -> T {
+    for _ in 0..200 {
+      if let Some(value) = check() {
+        return value;
+      }
+      std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("the task store did not catch up");
+  }
+
+  #[test]
+  fn dispatches_by_priority_then_age() {
+    let manager = manager(settings());
+    manager.assign(request("low", 0, 1)).unwrap();
+    manager.assign(request("high-new", 5, 3)).unwrap();
+    manager.assign(request("high-old", 5, 2)).unwrap();
+    manager.register(agent("a", ""));
+
+    // One task at a time; the next goes out once the last one finishes.
+    let mut order = Vec::new();
+    let mut report = String::new();
+    while let [command] = manager.heartbeat(agent("a", &report), 0).as_slice() {
+      let task_id = command.split(':').nth(1).unwrap().to_string();
+      report = format!("result:{task_id}:out");
+      order.push(task_id);
+    }
+    assert_eq!(order, ["high-old", "high-new", "low"]);
+    assert_eq!(
+      manager.task("low").unwrap().status,
+      TaskStatus::TaskSucceeded
+    );
+  }
+
+  #[test]
+  fn failed_task_is_retried_after_a_backoff_then_fails() {
+    let manager = manager(settings());
+    manager.register(agent("a", ""));
+    manager.assign(request("t", 0, 1)).unwrap();
+
+    for retry in 1..=2 {
+      assert_eq!(manager.heartbeat(agent("a", ""), 0), ["run-task:t:"]);
+      let failed_at = SystemTime::now();
+      assert!(manager
+        .heartbeat(agent("a", "result:t:ERROR_BOOM"), 0)
+        .is_empty());
+      let task = manager.task("t").unwrap();
+      assert_eq!(task.status, TaskStatus::TaskQueued);
+      assert_eq!(task.retries, retry);
+      assert!(task.agent_id.is_empty());
+      let backoff = task.retry_at.unwrap().duration_since(failed_at).unwrap();
+      assert!(backoff >= retry_delay(retry));
+      assert!(backoff < retry_delay(retry) + Duration::from_secs(1));
+
+      // Not sent again before the backoff is over.
+      manager.dispatch_pending();
+      assert!(manager.heartbeat(agent("a", ""), 0).is_empty());
+      manager
+        .inner
+        .lock()
+        .unwrap()
+        .tasks
+        .get_mut("t")
+        .unwrap()
+        .retry_at = Some(SystemTime::now());
+      manager.dispatch_pending();
+    }
+
+    assert_eq!(manager.heartbeat(agent("a", ""), 0), ["run-task:t:"]);
+    manager.heartbeat(agent("a", "result:t:ERROR_BOOM"), 0);
+    let task = manager.task("t").unwrap();
+    assert_eq!(task.status, TaskStatus::TaskFailed);
+    assert_eq!(task.retries, 2);
+    assert_eq!(task.message, "ERROR_BOOM");
+    manager.dispatch_pending();
+    assert!(manager.heartbeat(agent("a", ""), 0).is_empty());
+  }
+
+  #[test]
+  fn retry_delay_doubles_up_to_a_cap() {
+    let delays: Vec<u64> = (1..=7).map(|retry| retry_delay(retry).as_secs()).collect();
+    assert_eq!(delays, [2, 4, 8, 16, 32, 60, 60]);
+    assert_eq!(retry_delay(u32::MAX), RETRY_MAX_DELAY);
+  }
+
+  #[test]
+  fn restores_unfinished_tasks_as_queued() {
+    let dir = ScratchDir::new("manager-restore");
+    let manager = Manager::new(settings(), TaskStore::open(dir.path()).unwrap());
+    manager.register(agent("a", ""));
+    manager.assign(request("running", 0, 1)).unwrap();
+    manager.assign(request("queued", 0, 2)).unwrap();
+    manager.assign(request("cancelled", 0, 3)).unwrap();
+    manager.heartbeat(agent("a", ""), 0);
+    manager.heartbeat(agent("a", "progress:running:30"), 0);
+    manager.cancel("cancelled", "").unwrap();
+    drop(manager);
+    eventually(|| {
+      let unfinished = TaskStore::open(dir.path()).unwrap().unfinished().unwrap();
+      let running = unfinished
+        .iter()
+        .find(|task| task.request.task_id == "running")?;
+      (unfinished.len() == 2 && running.percent == 30).then_some(())
+    });
+
+    let manager = Manager::new(settings(), TaskStore::open(dir.path()).unwrap());
+    assert!(manager.task("cancelled").is_none());
+    for task_id in ["running", "queued"] {
+      let task = manager.task(task_id).unwrap();
+      assert_eq!(task.status, TaskStatus::TaskQueued);
+      assert_eq!(task.percent, 0);
+      assert!(task.agent_id.is_empty());
+    }
+    // The restore is recorded like any other update.
+    let conn = rusqlite::Connection::open(dir.path().join(TASKS_DB)).unwrap();
+    eventually(|| {
+      let message: String = conn
+        .query_row(
+          "SELECT message FROM task_progress WHERE task_id = 'running' ORDER BY id DESC LIMIT 1",
+          [],
+          |row| row.get(0),
+        )
+        .ok()?;
+      (message == "Requeued after restart").then_some(())
+    });
+
+    manager.register(agent("b", ""));
+    assert_eq!(manager.heartbeat(agent("b", ""), 0), ["run-task:running:"]);
+  }
+
+  /// Settings whose agent timeout passes within a test.
+  fn quick() -> Settings {
+    Settings {
+      agent_timeout: Duration::from_millis(50),
+      ..settings()
+    }
+  }
+
+  fn health(manager: &Manager, agent_id: &str) -> Option<AgentHealth> {
+    manager
+      .agents()
+      .into_iter()
+      .find(|agent| agent.id == agent_id)
+      .map(|agent| agent.health())
+  }
+
+  #[test]
+  fn silent_agent_turns_stale_then_is_evicted() {
+    let manager = manager(quick());
+    let mut changes = manager.subscribe_health();
+    manager.register(agent("a", ""));
+    assert_eq!(changes.try_recv().unwrap().health, Liveness::Healthy);
+
+    manager.check_liveness();
+    assert_eq!(health(&manager, "a"), Some(AgentHealth::AgentHealthy));
+    std::thread::sleep(quick().agent_timeout * 2);
+    manager.check_liveness();
+    assert_eq!(health(&manager, "a"), Some(AgentHealth::AgentStale));
+    assert_eq!(changes.try_recv().unwrap().health, Liveness::Stale);
+    assert!(!manager.has_healthy_agent(AgentType::AgentGeneration));
+
+    // A heartbeat brings it back.
+    manager.heartbeat(agent("a", ""), 0);
+    assert_eq!(health(&manager, "a"), Some(AgentHealth::AgentHealthy));
+    assert_eq!(changes.try_recv().unwrap().health, Liveness::Healthy);
+
+    std::thread::sleep(quick().agent_timeout * 2);
+    manager.check_liveness();
+    assert_eq!(changes.try_recv().unwrap().health, Liveness::Stale);
+    std::thread::sleep(quick().agent_timeout * EVICT_AFTER_TIMEOUTS);
+    manager.check_liveness();
+    assert_eq!(health(&manager, "a"), None);
+    assert_eq!(changes.try_recv().unwrap().health, Liveness::Evicted);
+  }
+
+  #[test]
+  fn stale_agent_tasks_go_to_another_agent() {
+    let manager = manager(quick());
+    manager.register(agent("a", ""));
+    manager.assign(request("t", 0, 1)).unwrap();
+    assert_eq!(manager.heartbeat(agent("a", ""), 0), ["run-task:t:"]);
+    manager.heartbeat(agent("a", "progress:t:50"), 0);
+    assert_eq!(manager.task("t").unwrap().percent, 50);
+
+    std::thread::sleep(quick().agent_timeout * 2);
+    manager.register(agent("b", ""));
+    manager.check_liveness();
+    let task = manager.task("t").unwrap();
+    assert_eq!(task.status, TaskStatus::TaskQueued);
+    assert_eq!(task.percent, 0);
+    assert_eq!(task.agent_id, "b");
+    assert_eq!(manager.heartbeat(agent("b", ""), 1), ["run-task:t:"]);
+
+    // Late reports from the stale agent are ignored.
+    manager.heartbeat(agent("a", "result:t:out"), 0);
+    assert_eq!(manager.task("t").unwrap().status, TaskStatus::TaskQueued);
+  }
+
+  #[test]
+  fn closing_a_stream_marks_its_agent_stale() {
+    let manager = manager(settings());
+    manager.register(agent("a", ""));
+    let old = manager.open_stream();
+    manager.heartbeat(agent("a", ""), old);
+    // The agent reconnects before the old stream is torn down.
+    let new = manager.open_stream();
+    assert_ne!(old, new);
+    manager.heartbeat(agent("a", ""), new);
+
+    manager.disconnected("a", old);
+    assert_eq!(health(&manager, "a"), Some(AgentHealth::AgentHealthy));
+    manager.disconnected("a", new);
+    assert_eq!(health(&manager, "a"), Some(AgentHealth::AgentStale));
+  }
+
+  #[test]
+  fn unacknowledged_task_is_resent_then_fails() {
+    let manager = manager(Settings {
+      ack_timeout: Duration::ZERO,
+      max_retries: 0,
+      ..settings()
+    });
+    manager.register(agent("a", ""));
+    manager.assign(request("t", 0, 1)).unwrap();
+    for _ in 0..MAX_DELIVERIES {
+      assert_eq!(manager.heartbeat(agent("a", ""), 0), ["run-task:t:"]);
+    }
+    assert!(manager.heartbeat(agent("a", ""), 0).is_empty());
+    let task = manager.task("t").unwrap();
+    assert_eq!(task.status, TaskStatus::TaskFailed);
+    assert_eq!(task.message, "Agent a did not acknowledge the task");
+  }
+
+  #[test]
+  fn acknowledged_task_is_not_resent() {
+    let manager = manager(Settings {
+      ack_timeout: Duration::ZERO,
+      ..settings()
+    });
+    manager.register(agent("a", ""));
+    let mut task = request("t", 0, 1);
+    task.input = Some(FileRef {
+      uri: "file:///videos/a:b.mp4".into(),
+      ..Default::default()
+    });
+    manager.assign(task).unwrap();
+    assert_eq!(
+      manager.heartbeat(agent("a", ""), 0),
+      ["run-task:t:file:///videos/a:b.mp4"]
+    );
+    assert!(manager.heartbeat(agent("a", "ack:t"), 0).is_empty());
+    assert!(manager.heartbeat(agent("a", ""), 0).is_empty());
+    assert_eq!(manager.task("t").unwrap().status, TaskStatus::TaskRunning);
+  }
+
+  #[test]
+  fn tasks_go_to_the_least_busy_agent_of_their_type() {
+    let manager = manager(settings());
+    manager.register(agent("a", ""));
+    manager.register(agent("b", ""));
+    manager.register(AgentInfo {
+      r#type: AgentType::AgentVision.into(),
+      ..agent("v", "")
+    });
+    let mut assigned = Vec::new();
+    for task_id in ["t1", "t2", "t3"] {
+      let mut task = request(task_id, 0, 1);
+      task.agent_type_hint = "generation".into();
+      assigned.push(manager.assign(task).unwrap().1);
+    }
+    // Ties go to the lowest id.
+    assert_eq!(assigned, ["a", "b", "a"]);
+    let mut task = request("t4", 0, 1);
+    task.agent_type_hint = "AGENT_VISION".into();
+    assert_eq!(manager.assign(task).unwrap().1, "v");
+
+    let mut task = request("t5", 0, 1);
+    task.agent_type_hint = "teleport".into();
+    assert!(manager.assign(task).is_err());
+  }
+
+  #[test]
+  fn parses_report_tokens() {
+    let Some(Report::Ack { task_id }) = Report::parse(" ack:t ") else {
+      panic!("expected an ack");
+    };
+    assert_eq!(task_id, "t");
+    let Some(Report::Progress { task_id, percent }) = Report::parse("progress:t:42") else {
+      panic!("expected progress");
+    };
+    assert_eq!((task_id.as_str(), percent), ("t", 42));
+    let Some(Report::Result { task_id, output }) = Report::parse("result:t:file:///out:1") else {
+      panic!("expected a result");
+    };
+    assert_eq!((task_id.as_str(), output.as_str()), ("t", "file:///out:1"));
+
+    for malformed in [
+      "",
+      "models:whisper",
+      "progress:t",
+      "progress:t:most",
+      "progress:t:99999999999",
+      "result:t",
+      "RESULT:t:out",
+    ] {
+      assert!(Report::parse(malformed).is_none(), "{malformed:?}");
+    }
+  }
+
+  #[test]
+  fn malformed_reports_are_ignored() {
+    let manager = manager(settings());
+    manager.register(agent("a", ""));
+    manager.assign(request("t", 0, 1)).unwrap();
+    manager.heartbeat(agent("a", ""), 0);
+    manager.heartbeat(agent("a", "progress:t:half;;result:t;progress:t:10"), 0);
+    let task = manager.task("t").unwrap();
+    assert_eq!(task.status, TaskStatus::TaskRunning);
+    assert_eq!(task.percent, 10);
+  }
+
+  #[test]
+  fn cancelling_a_queued_task_never_sends_it() {
+    let manager = manager(settings());
+    let mut updates = manager.subscribe();
+    manager.register(agent("a", ""));
+    manager.assign(request("t", 0, 1)).unwrap();
+    assert_eq!(manager.cancel("t", " "), Some(true));
+    let update = updates.try_recv().unwrap();
+    assert_eq!(update.status(), TaskStatus::TaskCancelled);
+    assert_eq!(update.message, "Cancelled");
+    // Neither a run-task nor a cancel-task for it.
+    assert!(manager.heartbeat(agent("a", ""), 0).is_empty());
+  }
+
+  #[test]
+  fn cancelling_a_sent_task_tells_its_agent_first() {
+    let manager = manager(settings());
+    manager.register(agent("a", ""));
+    manager.assign(request("t1", 0, 1)).unwrap();
+    manager.assign(request("t2", 0, 2)).unwrap();
+    assert_eq!(manager.heartbeat(agent("a", ""), 0), ["run-task:t1:"]);
+    manager.heartbeat(agent("a", "progress:t1:20"), 0);
+
+    assert_eq!(manager.cancel("t1", "Stop"), Some(true));
+    let task = manager.task("t1").unwrap();
+    assert_eq!(task.status, TaskStatus::TaskCancelled);
+    assert_eq!((task.percent, task.message.as_str()), (20, "Stop"));
+    assert_eq!(
+      manager.heartbeat(agent("a", ""), 0),
+      ["cancel-task:t1", "run-task:t2:"]
+    );
+    // Reports for a cancelled task change nothing.
+    manager.heartbeat(agent("a", "result:t1:out"), 0);
+    assert_eq!(
+      manager.task("t1").unwrap().status,
+      TaskStatus::TaskCancelled
+    );
+  }
+
+  #[test]
+  fn cancelling_a_finished_or_unknown_task() {
+    let manager = manager(settings());
+    manager.assign(request("t", 0, 1)).unwrap();
+    assert_eq!(manager.cancel("t", ""), Some(true));
+    assert_eq!(manager.cancel("t", ""), Some(false));
+    assert_eq!(manager.cancel("missing", ""), None);
+  }
+}
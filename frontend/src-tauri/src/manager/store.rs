@@ -0,0 +1,442 @@
+//! SQLite persistence for the agent manager's tasks.
+//!
+//! Lives next to `chat_history.db` in the data directory. Every task keeps
+//! its latest state in `tasks`, each `ProgressUpdate` it went through in
+//! `task_progress` and its final `TaskResult` in `task_results`.
+//!
+//! The schema is versioned through `user_version` like the chat database's
+//! (see [`crate::history::migrations`]). The manager writes through a
+//! [`TaskWriter`], which applies writes on a thread of its own.
+
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use prost::Message as _;
+use prost_types::Timestamp;
+use rusqlite::{params, Connection, TransactionBehavior};
+
+use crate::proto::common::{ProgressUpdate, TaskRequest, TaskResult, TaskStatus};
+
+pub const TASKS_DB: &str = "tasks.db";
+
+/// Migration `i` takes the schema from version `i` to `i + 1`. Append-only,
+/// as for the chat database.
+const MIGRATIONS: &[&str] = &[
+  // 1: the schema from before versioning; a no-op on those databases.
+  "
+  CREATE TABLE IF NOT EXISTS tasks (
+    task_id TEXT PRIMARY KEY,
+    request BLOB NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    status INTEGER NOT NULL,
+    percent INTEGER NOT NULL DEFAULT 0,
+    message TEXT NOT NULL DEFAULT '',
+    agent_id TEXT NOT NULL DEFAULT '',
+    retries INTEGER NOT NULL DEFAULT 0,
+    retry_at INTEGER,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+  );
+  CREATE INDEX IF NOT EXISTS tasks_status ON tasks(status);
+  CREATE TABLE IF NOT EXISTS task_progress (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    percent INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY(task_id) REFERENCES tasks(task_id)
+  );
+  CREATE INDEX IF NOT EXISTS task_progress_task ON task_progress(task_id);
+  CREATE TABLE IF NOT EXISTS task_results (
+    task_id TEXT PRIMARY KEY,
+    success INTEGER NOT NULL,
+    output_uri TEXT NOT NULL DEFAULT '',
+    result_json TEXT NOT NULL DEFAULT '',
+    error_message TEXT NOT NULL DEFAULT '',
+    completed_at INTEGER NOT NULL,
+    FOREIGN KEY(task_id) REFERENCES tasks(task_id)
+  );
+  ",
+];
+
+/// Schema version this build reads and writes.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TaskStoreError {
+  #[error(
+    "task database schema version {found} is newer than this app supports ({supported}); \
+     update the app"
+  )]
+  TooNew { found: u32, supported: u32 },
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+}
+
+/// A task row as persisted.
+#[derive(Debug, Clone)]
+pub struct StoredTask {
+  pub request: TaskRequest,
+  pub status: TaskStatus,
+  pub percent: i32,
+  pub message: String,
+  pub agent_id: String,
+  /// Failed runs that have been retried so far.
+  pub retries: u32,
+  /// Earliest time the next retry may be dispatched.
+  pub retry_at: Option<SystemTime>,
+}
+
+pub struct TaskStore {
+  conn: Connection,
+}
+
+impl TaskStore {
+  /// Opens (creating if needed) the task database in `data_dir`.
+  pub fn open(data_dir: &Path) -> Result<Self, TaskStoreError> {
+    if let Err(err) = std::fs::create_dir_all(data_dir) {
+      log::warn!("could not create {}: {err}", data_dir.display());
+    }
+    Self::with_connection(Connection::open(data_dir.join(TASKS_DB))?)
+  }
+
+  /// A throwaway store, for when the data directory cannot be opened.
+  pub fn in_memory() -> Result<Self, TaskStoreError> {
+    Self::with_connection(Connection::open_in_memory()?)
+  }
+
+  fn with_connection(mut conn: Connection) -> Result<Self, TaskStoreError> {
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    migrate(&mut conn)?;
+    Ok(Self { conn })
+  }
+
+  pub fn save(&self, task: &StoredTask) -> rusqlite::Result<()> {
+    let request = &task.request;
+    self.conn.execute(
+      "INSERT INTO tasks (task_id, request, priority, status, percent, message, agent_id,
+                          retries, retry_at, created_at, updated_at)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+       ON CONFLICT(task_id) DO UPDATE SET
+         status = excluded.status,
+         percent = excluded.percent,
+         message = excluded.message,
+         agent_id = excluded.agent_id,
+         retries = excluded.retries,
+         retry_at = excluded.retry_at,
+         updated_at = excluded.updated_at",
+      params![
+        request.task_id,
+        request.encode_to_vec(),
+        request.priority,
+        task.status as i32,
+        task.percent,
+        task.message,
+        task.agent_id,
+        task.retries,
+        task.retry_at.map(system_ms),
+        request.created_at.as_ref().map_or(0, timestamp_ms),
+        system_ms(SystemTime::now()),
+      ],
+    )?;
+    Ok(())
+  }
+
+  pub fn record_progress(&self, update: &ProgressUpdate) -> rusqlite::Result<()> {
+    self.conn.execute(
+      "INSERT INTO task_progress (task_id, status, percent, message, updated_at)
+       VALUES (?1, ?2, ?3, ?4, ?5)",
+      params![
+        update.task_id,
+        update.status,
+        update.percent,
+        update.message,
+        update.updated_at.as_ref().map_or(0, timestamp_ms),
+      ],
+    )?;
+    Ok(())
+  }
+
+  pub fn record_result(&self, result: &TaskResult) -> rusqlite::Result<()> {
+    self.conn.execute(
+      "INSERT OR REPLACE INTO task_results
+         (task_id, success, output_uri, result_json, error_message, completed_at)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+      params![
+        result.task_id,
+        result.success,
+        result.output_uri,
+        result.result_json,
+        result.error_message,
+        result.completed_at.as_ref().map_or(0, timestamp_ms),
+      ],
+    )?;
+    Ok(())
+  }
+
+  /// Tasks that had not finished when the app last stopped.
+  pub fn unfinished(&self) -> rusqlite::Result<Vec<StoredTask>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT request, status, percent, message, agent_id, retries, retry_at
+       FROM tasks WHERE status IN (?1, ?2, ?3)
+       ORDER BY priority DESC, created_at",
+    )?;
+    let rows = stmt.query_map(
+      params![
+        TaskStatus::TaskUnknown as i32,
+        TaskStatus::TaskQueued as i32,
+        TaskStatus::TaskRunning as i32,
+      ],
+      |row| {
+        let request: Vec<u8> = row.get(0)?;
+        let request = TaskRequest::decode(request.as_slice()).map_err(|err| {
+          rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(err))
+        })?;
+        Ok(StoredTask {
+          request,
+          status: TaskStatus::try_from(row.get::<_, i32>(1)?).unwrap_or_default(),
+          percent: row.get(2)?,
+          message: row.get(3)?,
+          agent_id: row.get(4)?,
+          retries: row.get(5)?,
+          retry_at: row
+            .get::<_, Option<i64>>(6)?
+            .map(|ms| UNIX_EPOCH + Duration::from_millis(ms.max(0) as u64)),
+        })
+      },
+    )?;
+    rows.collect()
+  }
+}
+
+/// Brings the database up to [`SCHEMA_VERSION`], refusing one from a newer
+/// app version.
+fn migrate(conn: &mut Connection) -> Result<(), TaskStoreError> {
+  let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+  let version: u32 = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+  if version > SCHEMA_VERSION {
+    return Err(TaskStoreError::TooNew {
+      found: version,
+      supported: SCHEMA_VERSION,
+    });
+  }
+  for (index, sql) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+    log::info!("migrating {TASKS_DB} to schema version {}", index + 1);
+    tx.execute_batch(sql)?;
+  }
+  tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+  tx.commit()?;
+  Ok(())
+}
+
+enum Write {
+  Save(StoredTask),
+  Progress(ProgressUpdate),
+  Result(TaskResult),
+}
+
+/// Queues writes for a [`TaskStore`] owned by a writer thread, so callers
+/// never wait on disk. Writes are applied in the order they were queued;
+/// failures are logged there. The thread exits once every handle is gone.
+#[derive(Clone)]
+pub struct TaskWriter {
+  queue: mpsc::Sender<Write>,
+}
+
+impl TaskWriter {
+  /// Moves `store` to a new writer thread.
+  pub fn spawn(store: TaskStore) -> Self {
+    let (queue, writes) = mpsc::channel();
+    std::thread::Builder::new()
+      .name("task-store".into())
+      .spawn(move || {
+        for write in writes {
+          store.apply(write);
+        }
+      })
+      .expect("could not start the task store writer");
+    Self { queue }
+  }
+
+  pub fn save(&self, task: StoredTask) {
+    self.queue(Write::Save(task));
+  }
+
+  pub fn record_progress(&self, update: ProgressUpdate) {
+    self.queue(Write::Progress(update));
+  }
+
+  pub fn record_result(&self, result: TaskResult) {
+    self.queue(Write::Result(result));
+  }
+
+  fn queue(&self, write: Write) {
+    if self.queue.send(write).is_err() {
+      log::error!("task store writer has stopped; dropping a write");
+    }
+  }
+}
+
+impl TaskStore {
+  fn apply(&self, write: Write) {
+    match write {
+      Write::Save(task) => {
+        if let Err(err) = self.save(&task) {
+          log::error!("could not save task {}: {err}", task.request.task_id);
+        }
+      }
+      Write::Progress(update) => {
+        if let Err(err) = self.record_progress(&update) {
+          log::error!(
+            "could not record progress of task {}: {err}",
+            update.task_id
+          );
+        }
+      }
+      Write::Result(result) => {
+        if let Err(err) = self.record_result(&result) {
+          log::error!("could not record result of task {}: {err}", result.task_id);
+        }
+      }
+    }
+  }
+}
+
+fn system_ms(time: SystemTime) -> i64 {
+  time
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+/// `ts` as Unix ms, the unit the stores keep times in.
+pub(crate) fn timestamp_ms(ts: &Timestamp) -> i64 {
+  ts.seconds * 1000 + i64::from(ts.nanos) / 1_000_000
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::history::testing::ScratchDir;
+
+  fn task(task_id: &str, priority: i32, created_at: i64, status: TaskStatus) -> StoredTask {
+    StoredTask {
+      request: TaskRequest {
+        task_id: task_id.into(),
+        priority,
+        created_at: Some(Timestamp {
+          seconds: created_at,
+          nanos: 0,
+        }),
+        ..Default::default()
+      },
+      status,
+      percent: 0,
+      message: String::new(),
+      agent_id: String::new(),
+      retries: 0,
+      retry_at: None,
+    }
+  }
+
+  #[test]
+  fn unfinished_tasks_survive_a_reopen() {
+    let dir = ScratchDir::new("tasks-reopen");
+    let store = TaskStore::open(dir.path()).unwrap();
+    let retry_at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+    store
+      .save(&StoredTask {
+        retries: 2,
+        retry_at: Some(retry_at),
+        ..task("old", 0, 1, TaskStatus::TaskQueued)
+      })
+      .unwrap();
+    store
+      .save(&task("urgent", 5, 3, TaskStatus::TaskQueued))
+      .unwrap();
+    store
+      .save(&task("newer", 0, 2, TaskStatus::TaskQueued))
+      .unwrap();
+    store
+      .save(&task("done", 9, 1, TaskStatus::TaskQueued))
+      .unwrap();
+
+    // The task moves on; only the latest state counts.
+    store
+      .save(&StoredTask {
+        percent: 40,
+        agent_id: "agent".into(),
+        ..task("newer", 0, 2, TaskStatus::TaskRunning)
+      })
+      .unwrap();
+    store
+      .record_progress(&ProgressUpdate {
+        task_id: "newer".into(),
+        status: TaskStatus::TaskRunning.into(),
+        percent: 40,
+        message: "Agent agent at 40%".into(),
+        updated_at: None,
+      })
+      .unwrap();
+    store
+      .save(&task("done", 9, 1, TaskStatus::TaskSucceeded))
+      .unwrap();
+    store
+      .record_result(&TaskResult {
+        task_id: "done".into(),
+        success: true,
+        output_uri: "file:///out".into(),
+        ..Default::default()
+      })
+      .unwrap();
+    drop(store);
+
+    let store = TaskStore::open(dir.path()).unwrap();
+    let unfinished = store.unfinished().unwrap();
+    let ids: Vec<_> = unfinished
+      .iter()
+      .map(|task| task.request.task_id.as_str())
+      .collect();
+    // Highest priority first, then oldest.
+    assert_eq!(ids, ["urgent", "old", "newer"]);
+    assert_eq!(unfinished[1].retries, 2);
+    assert_eq!(unfinished[1].retry_at, Some(retry_at));
+    assert_eq!(unfinished[2].status, TaskStatus::TaskRunning);
+    assert_eq!(unfinished[2].percent, 40);
+    assert_eq!(unfinished[2].agent_id, "agent");
+
+    let progress: i64 = store
+      .conn
+      .query_row(
+        "SELECT COUNT(*) FROM task_progress WHERE task_id = 'newer'",
+        [],
+        |row| row.get(0),
+      )
+      .unwrap();
+    assert_eq!(progress, 1);
+    let output: String = store
+      .conn
+      .query_row(
+        "SELECT output_uri FROM task_results WHERE task_id = 'done'",
+        [],
+        |row| row.get(0),
+      )
+      .unwrap();
+    assert_eq!(output, "file:///out");
+  }
+
+  #[test]
+  fn refuses_a_newer_database() {
+    let dir = ScratchDir::new("tasks-too-new");
+    let conn = Connection::open(dir.path().join(TASKS_DB)).unwrap();
+    conn
+      .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+      .unwrap();
+    drop(conn);
+    assert!(matches!(
+      TaskStore::open(dir.path()),
+      Err(TaskStoreError::TooNew { .. })
+    ));
+  }
+}
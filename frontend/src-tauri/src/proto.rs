@@ -0,0 +1,36 @@
+//! Rust bindings for the contracts in `protos/*.proto`.
+//!
+//! Modules mirror the protobuf packages so cross-package references
+//! (e.g. `genai.common.TaskRequest` inside `genai.vision`) resolve. The
+//! short aliases at the bottom are what the rest of the crate uses.
+
+pub mod genai {
+  pub mod common {
+    tonic::include_proto!("genai.common");
+  }
+
+  pub mod manager {
+    tonic::include_proto!("genai.manager");
+  }
+
+  pub mod transcription {
+    tonic::include_proto!("genai.transcription");
+  }
+
+  pub mod vision {
+    tonic::include_proto!("genai.vision");
+  }
+
+  pub mod generation {
+    tonic::include_proto!("genai.generation");
+  }
+}
+
+pub mod videoanalyzer {
+  pub mod chat {
+    tonic::include_proto!("videoanalyzer.chat");
+  }
+}
+
+pub use genai::{common, generation, manager, transcription, vision};
+pub use videoanalyzer::chat;
@@ -0,0 +1,254 @@
+//! Runs the Python chat backend as a child process of the desktop shell.
+//!
+//! [`Sidecar::spawn`] starts a supervisor that launches
+//! `python -m backend.server` on a Unix socket or a free loopback port,
+//! waits for its [`HANDSHAKE`] line, forwards its stdout/stderr to the app
+//! log and relaunches it with backoff whenever it exits.
+//! [`Sidecar::shutdown`] stops it when the app exits.
+
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::async_runtime::JoinHandle;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, ChildStdout, Command};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use crate::auth;
+use crate::transport;
+
+/// Line prefix the backend prints on stdout once it is serving, followed by
+/// the address it bound: `VIDEO_ANALYZER_READY 127.0.0.1:54321` or
+/// `VIDEO_ANALYZER_READY unix:/path/to/backend.sock`. Must match
+/// `READY_PREFIX` in `backend/server.py`.
+pub const HANDSHAKE: &str = "VIDEO_ANALYZER_READY";
+
+/// How long a fresh backend may take to complete the handshake; model
+/// loading makes the first start slow.
+const READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Backoff before relaunching a crashed backend; doubles per crash.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+/// A backend that stayed up this long counts as healthy again, resetting
+/// the backoff.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// Grace period between asking the backend to stop and killing it.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How to launch the backend.
+#[derive(Debug, Clone)]
+pub struct Launch {
+  /// Python interpreter.
+  pub python: String,
+  /// Directory containing the `backend` package, used as the working
+  /// directory.
+  pub dir: PathBuf,
+  /// Data directory the backend shares with the shell, passed as
+  /// `VIDEO_ANALYZER_DATA_DIR`.
+  pub data_dir: PathBuf,
+  /// Secret the backend requires on every call.
+  pub auth_token: String,
+  /// Socket to serve on instead of a TCP port.
+  pub socket: Option<PathBuf>,
+}
+
+/// Handle to the supervisor, managed as Tauri state.
+pub struct Sidecar {
+  shutdown: CancellationToken,
+  supervisor: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Called with the backend's endpoint URI each time a launch completes the
+/// handshake, and with `None` each time a launch ends.
+type OnReady = Box<dyn Fn(Option<String>) + Send + Sync>;
+
+impl Sidecar {
+  /// Starts supervising a backend launched per `launch`.
+  pub fn spawn(launch: Launch, on_ready: impl Fn(Option<String>) + Send + Sync + 'static) -> Self {
+    let shutdown = CancellationToken::new();
+    let supervisor =
+      tauri::async_runtime::spawn(supervise(launch, Box::new(on_ready), shutdown.clone()));
+    Self {
+      shutdown,
+      supervisor: Mutex::new(Some(supervisor)),
+    }
+  }
+
+  /// Stops the backend and blocks until it has exited.
+  pub fn shutdown(&self) {
+    self.shutdown.cancel();
+    if let Some(supervisor) = self.supervisor.lock().unwrap().take() {
+      let _ = tauri::async_runtime::block_on(supervisor);
+    }
+  }
+}
+
+enum Exit {
+  /// Stopped on request.
+  Shutdown,
+  Exited(ExitStatus),
+  /// Never completed the handshake and was killed.
+  NotReady,
+}
+
+async fn supervise(launch: Launch, on_ready: OnReady, shutdown: CancellationToken) {
+  let mut delay = RESTART_DELAY;
+  loop {
+    let started = Instant::now();
+    let exit = run(&launch, &on_ready, &shutdown).await;
+    on_ready(None);
+    match exit {
+      Ok(Exit::Shutdown) => return,
+      Ok(Exit::Exited(status)) => log::error!("backend exited with {status}"),
+      Ok(Exit::NotReady) => {
+        log::error!("backend did not report ready within {READY_TIMEOUT:?}")
+      }
+      Err(err) => log::error!("could not start backend with {:?}: {err}", launch.python),
+    }
+    if started.elapsed() > STABLE_AFTER {
+      delay = RESTART_DELAY;
+    }
+
+    log::info!("restarting backend in {delay:?}");
+    tokio::select! {
+      _ = tokio::time::sleep(delay) => {}
+      _ = shutdown.cancelled() => return,
+    }
+    delay = (delay * 2).min(MAX_RESTART_DELAY);
+  }
+}
+
+/// Runs the backend once, until it exits or `shutdown` fires.
+async fn run(
+  launch: &Launch,
+  on_ready: &OnReady,
+  shutdown: &CancellationToken,
+) -> std::io::Result<Exit> {
+  let mut command = Command::new(&launch.python);
+  // Unbuffered, so log lines arrive as they are printed.
+  command.args(["-u", "-m", "backend.server"]);
+  match &launch.socket {
+    Some(path) => {
+      command.arg("--unix").arg(path);
+    }
+    None => {
+      // A fresh port per launch: the previous one may linger in TIME_WAIT,
+      // and another copy of the app may own the default.
+      let port = free_port()?;
+      command
+        .args(["--host", "127.0.0.1", "--port"])
+        .arg(port.to_string());
+    }
+  }
+  let mut child = command
+    .current_dir(&launch.dir)
+    // Passed in the environment rather than argv, which other users can read.
+    .env(auth::TOKEN_ENV, &launch.auth_token)
+    // Made absolute, as the backend runs in another working directory.
+    .env(
+      "VIDEO_ANALYZER_DATA_DIR",
+      std::env::current_dir()?.join(&launch.data_dir),
+    )
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true)
+    .spawn()?;
+  log::info!(
+    "started backend (pid {}) in {}",
+    child.id().unwrap_or_default(),
+    launch.dir.display()
+  );
+  let (handshake, ready) = oneshot::channel();
+  if let Some(stdout) = child.stdout.take() {
+    tauri::async_runtime::spawn(read_stdout(stdout, handshake));
+  }
+  // Python logging and tracebacks both go to stderr.
+  if let Some(stderr) = child.stderr.take() {
+    tauri::async_runtime::spawn(forward_output(stderr, log::Level::Warn));
+  }
+
+  let ready = tokio::time::timeout(READY_TIMEOUT, ready);
+  tokio::pin!(ready);
+  let mut waiting = true;
+  loop {
+    tokio::select! {
+      status = child.wait() => return Ok(Exit::Exited(status?)),
+      _ = shutdown.cancelled() => {
+        stop(&mut child).await;
+        return Ok(Exit::Shutdown);
+      }
+      result = &mut ready, if waiting => {
+        waiting = false;
+        let Ok(Ok(addr)) = result else {
+          stop(&mut child).await;
+          return Ok(Exit::NotReady);
+        };
+        log::info!("backend is serving on {addr}");
+        if addr.starts_with(transport::UNIX_PREFIX) {
+          on_ready(Some(addr));
+        } else {
+          on_ready(Some(format!("http://{addr}")));
+        }
+      }
+    }
+  }
+}
+
+/// Asks the OS for an unused loopback port. The backend binds it right
+/// after, so the window for another process to take it is small.
+fn free_port() -> std::io::Result<u16> {
+  Ok(
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?
+      .local_addr()?
+      .port(),
+  )
+}
+
+/// Logs the backend's stdout, passing the address from the first
+/// [`HANDSHAKE`] line to `handshake`.
+async fn read_stdout(stdout: ChildStdout, handshake: oneshot::Sender<String>) {
+  let mut handshake = Some(handshake);
+  let mut lines = BufReader::new(stdout).lines();
+  while let Ok(Some(line)) = lines.next_line().await {
+    match line.strip_prefix(HANDSHAKE) {
+      Some(addr) if handshake.is_some() => {
+        let _ = handshake.take().unwrap().send(addr.trim().to_string());
+      }
+      _ => log::info!(target: "backend", "{line}"),
+    }
+  }
+}
+
+async fn forward_output(output: impl AsyncRead + Unpin, level: log::Level) {
+  let mut lines = BufReader::new(output).lines();
+  while let Ok(Some(line)) = lines.next_line().await {
+    log::log!(target: "backend", level, "{line}");
+  }
+}
+
+/// Asks the backend to exit (SIGTERM, which it handles like Ctrl-C) and
+/// kills it if it is still running after [`STOP_TIMEOUT`].
+async fn stop(child: &mut Child) {
+  #[cfg(unix)]
+  if let Some(pid) = child.id() {
+    // SAFETY: plain syscall on a pid we own and have not yet reaped.
+    unsafe {
+      libc::kill(pid as libc::pid_t, libc::SIGTERM);
+    }
+    if let Ok(Ok(status)) = tokio::time::timeout(STOP_TIMEOUT, child.wait()).await {
+      log::info!("backend stopped with {status}");
+      return;
+    }
+    log::warn!("backend ignored SIGTERM, killing it");
+  }
+  if let Err(err) = child.kill().await {
+    log::warn!("could not kill backend: {err}");
+  }
+}
@@ -0,0 +1,212 @@
+//! Tauri commands for tasks run by the agent manager, and the watchers that
+//! forward their `StreamProgress` updates to the webview.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager as _, State};
+use tonic::Code;
+
+use crate::error::{Error, Result};
+use crate::events::TASK_PROGRESS;
+use crate::grpc::Backend;
+use crate::manager::{is_terminal, timestamp_ms};
+use crate::proto::common::{FileRef, ProgressUpdate, TaskRequest, TaskStatus};
+use crate::proto::manager::{CancelTaskRequest, TaskProgressRequest};
+
+/// Delay before the first resubscribe after the progress stream drops;
+/// doubles up to [`MAX_RESUBSCRIBE_DELAY`] while the manager stays away.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_millis(500);
+const MAX_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(10);
+
+/// Payload of the [`TASK_PROGRESS`] event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskProgress {
+  pub task_id: String,
+  pub status: TaskState,
+  pub percent: i32,
+  pub message: String,
+  /// Milliseconds since the Unix epoch.
+  pub updated_at: i64,
+}
+
+/// `TaskStatus` without the proto prefix, e.g. `"running"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+  Unknown,
+  Queued,
+  Running,
+  Succeeded,
+  Failed,
+  Cancelled,
+}
+
+impl From<TaskStatus> for TaskState {
+  fn from(status: TaskStatus) -> Self {
+    match status {
+      TaskStatus::TaskUnknown => Self::Unknown,
+      TaskStatus::TaskQueued => Self::Queued,
+      TaskStatus::TaskRunning => Self::Running,
+      TaskStatus::TaskSucceeded => Self::Succeeded,
+      TaskStatus::TaskFailed => Self::Failed,
+      TaskStatus::TaskCancelled => Self::Cancelled,
+    }
+  }
+}
+
+impl From<ProgressUpdate> for TaskProgress {
+  fn from(update: ProgressUpdate) -> Self {
+    Self {
+      status: update.status().into(),
+      updated_at: update.updated_at.as_ref().map_or(0, timestamp_ms),
+      task_id: update.task_id,
+      percent: update.percent,
+      message: update.message,
+    }
+  }
+}
+
+/// Task ids with a running progress watcher, so each task is streamed once.
+#[derive(Default)]
+pub struct TaskWatchers {
+  watching: Mutex<HashSet<String>>,
+}
+
+impl TaskWatchers {
+  /// Returns `false` if `task_id` is already being watched.
+  fn start(&self, task_id: &str) -> bool {
+    self.watching.lock().unwrap().insert(task_id.to_string())
+  }
+
+  fn stop(&self, task_id: &str) {
+    self.watching.lock().unwrap().remove(task_id);
+  }
+}
+
+/// Submits a task to the agent manager and starts emitting
+/// [`TASK_PROGRESS`] events for it. Resolves to the task id.
+#[tauri::command]
+pub async fn submit_task(
+  app: AppHandle,
+  backend: State<'_, Backend>,
+  watchers: State<'_, TaskWatchers>,
+  input_uri: String,
+  agent_type: Option<String>,
+  parameters: Option<Value>,
+  priority: Option<i32>,
+) -> Result<String> {
+  let task_id = uuid::Uuid::new_v4().to_string();
+  let response = backend
+    .agent_manager()
+    .await?
+    .assign_task(TaskRequest {
+      task_id: task_id.clone(),
+      agent_type_hint: agent_type.unwrap_or_default(),
+      input: Some(FileRef {
+        uri: input_uri,
+        ..Default::default()
+      }),
+      parameters_json: parameters
+        .map(|value| value.to_string())
+        .unwrap_or_default(),
+      priority: priority.unwrap_or_default(),
+      ..Default::default()
+    })
+    .await?
+    .into_inner();
+  if !response.accepted {
+    return Err(Error::Rejected(response.message));
+  }
+  watch(app, &watchers, task_id.clone());
+  Ok(task_id)
+}
+
+/// Emits [`TASK_PROGRESS`] events for an already submitted task, e.g. after
+/// the webview reloads. Watching a task twice is a no-op.
+#[tauri::command]
+pub fn watch_task(app: AppHandle, watchers: State<'_, TaskWatchers>, task_id: String) {
+  watch(app, &watchers, task_id);
+}
+
+/// Cancels a queued or running task. Resolves to `false` if it had already
+/// finished; watchers of the task receive a final `TASK_CANCELLED` update.
+#[tauri::command]
+pub async fn cancel_task(
+  backend: State<'_, Backend>,
+  task_id: String,
+  reason: Option<String>,
+) -> Result<bool> {
+  let request = CancelTaskRequest {
+    task_id: task_id.clone(),
+    reason: reason.unwrap_or_else(|| "Cancelled by user".into()),
+  };
+  match backend.agent_manager().await?.cancel_task(request).await {
+    Ok(response) => Ok(response.into_inner().cancelled),
+    Err(status) if status.code() == Code::NotFound => Err(Error::UnknownTask(task_id)),
+    Err(status) => Err(status.into()),
+  }
+}
+
+fn watch(app: AppHandle, watchers: &TaskWatchers, task_id: String) {
+  if !watchers.start(&task_id) {
+    return;
+  }
+  tauri::async_runtime::spawn(async move {
+    forward_progress(&app, &task_id).await;
+    app.state::<TaskWatchers>().stop(&task_id);
+  });
+}
+
+/// Streams progress for `task_id` until a terminal status arrives,
+/// resubscribing whenever the stream breaks off before that.
+async fn forward_progress(app: &AppHandle, task_id: &str) {
+  let backend = app.state::<Backend>();
+  let mut last: Option<TaskProgress> = None;
+  let mut delay = RESUBSCRIBE_DELAY;
+  loop {
+    let result = async {
+      let mut stream = backend
+        .agent_manager()
+        .await?
+        .stream_progress(TaskProgressRequest {
+          task_id: task_id.to_string(),
+          ..Default::default()
+        })
+        .await?
+        .into_inner();
+      while let Some(update) = stream.message().await? {
+        delay = RESUBSCRIBE_DELAY;
+        let terminal = is_terminal(update.status());
+        let progress = TaskProgress::from(update);
+        // A resubscribe starts with a snapshot we may already have sent.
+        if last.as_ref() != Some(&progress) {
+          if let Err(err) = app.emit(TASK_PROGRESS, &progress) {
+            log::warn!("failed to emit {TASK_PROGRESS}: {err}");
+          }
+          last = Some(progress);
+        }
+        if terminal {
+          return Ok(true);
+        }
+      }
+      Ok::<_, Error>(false)
+    }
+    .await;
+
+    match result {
+      Ok(true) => return,
+      Ok(false) => log::warn!("progress stream for task {task_id} ended early"),
+      Err(Error::Status(status)) if status.code() == Code::NotFound => {
+        log::warn!("stopped watching unknown task {task_id}");
+        return;
+      }
+      Err(err) => log::warn!("progress stream for task {task_id} failed: {err}"),
+    }
+    tokio::time::sleep(delay).await;
+    delay = (delay * 2).min(MAX_RESUBSCRIBE_DELAY);
+  }
+}
@@ -0,0 +1,290 @@
+//! How the shell reaches the backend and the agent manager: TCP on
+//! loopback, or Unix domain sockets that only the current user can open.
+//!
+//! Sockets live in [`SOCKET_DIR`] under the data directory, which is made
+//! private to the user; `backend/agents/agent_base.py` looks there for the
+//! manager socket. Endpoints on a socket are written `unix:<path>`, the form
+//! Python gRPC accepts too.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use tonic::transport::{Channel, Endpoint, Error};
+
+/// Prefix marking an endpoint as a socket path rather than a URI.
+pub const UNIX_PREFIX: &str = "unix:";
+
+/// Directory under the data directory holding the sockets.
+pub const SOCKET_DIR: &str = "run";
+pub const BACKEND_SOCKET: &str = "backend.sock";
+pub const MANAGER_SOCKET: &str = "manager.sock";
+
+/// Transport for the backend and the agent manager
+/// (`VIDEO_ANALYZER_TRANSPORT`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+  Tcp,
+  Unix,
+}
+
+impl Default for Transport {
+  /// Sockets where the platform has them, TCP elsewhere.
+  fn default() -> Self {
+    if cfg!(unix) {
+      Transport::Unix
+    } else {
+      Transport::Tcp
+    }
+  }
+}
+
+impl FromStr for Transport {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value.to_ascii_lowercase().as_str() {
+      "tcp" => Ok(Transport::Tcp),
+      "unix" if cfg!(unix) => Ok(Transport::Unix),
+      "unix" => Err("Unix domain sockets are not supported on this platform".into()),
+      _ => Err("expected \"tcp\" or \"unix\"".into()),
+    }
+  }
+}
+
+/// Returns the socket directory under `data_dir`, creating it readable by
+/// the current user only. The sockets inherit that protection: connecting
+/// needs search permission on every directory of the path.
+pub fn socket_dir(data_dir: &Path) -> std::io::Result<PathBuf> {
+  let dir = data_dir.join(SOCKET_DIR);
+  std::fs::create_dir_all(&dir)?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
+  }
+  Ok(dir)
+}
+
+/// Formats `path` as a `unix:` endpoint.
+pub fn unix_uri(path: &Path) -> String {
+  format!("{UNIX_PREFIX}{}", path.display())
+}
+
+/// A gRPC endpoint: a TCP URI, or a socket path reached through a
+/// placeholder URI that only supplies the HTTP/2 authority.
+#[derive(Debug, Clone)]
+pub struct Address {
+  pub endpoint: Endpoint,
+  pub socket: Option<PathBuf>,
+}
+
+impl Address {
+  /// Parses `http://host:port`, or `unix:<path>` where sockets exist.
+  pub fn parse(uri: String) -> Result<Self, Error> {
+    #[cfg(unix)]
+    if let Some(path) = uri.strip_prefix(UNIX_PREFIX) {
+      return Ok(Self {
+        endpoint: Endpoint::from_static("http://localhost"),
+        socket: Some(PathBuf::from(path)),
+      });
+    }
+    Ok(Self {
+      endpoint: Endpoint::from_shared(uri)?,
+      socket: None,
+    })
+  }
+
+  pub async fn connect(&self) -> Result<Channel, Error> {
+    #[cfg(unix)]
+    if let Some(path) = &self.socket {
+      return connect_unix(&self.endpoint, path.clone()).await;
+    }
+    self.endpoint.connect().await
+  }
+}
+
+#[cfg(unix)]
+async fn connect_unix(endpoint: &Endpoint, path: PathBuf) -> Result<Channel, Error> {
+  use hyper_util::rt::TokioIo;
+  use tokio::net::UnixStream;
+
+  endpoint
+    .connect_with_connector(tower::service_fn(move |_| {
+      let path = path.clone();
+      async move { Ok::<_, std::io::Error>(TokioIo::new(UnixStream::connect(path).await?)) }
+    }))
+    .await
+}
+
+/// Binds a socket at `path` readable and writable by the current user
+/// only, replacing one left behind by an earlier run.
+#[cfg(unix)]
+pub fn bind_unix(path: &Path) -> std::io::Result<tokio::net::UnixListener> {
+  use std::os::unix::fs::PermissionsExt;
+
+  match std::fs::remove_file(path) {
+    Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+    _ => {}
+  }
+  let listener = tokio::net::UnixListener::bind(path)?;
+  std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+  Ok(listener)
+}
+
+/// Where a server listens.
+#[derive(Debug, Clone)]
+pub enum Listen {
+  Tcp(SocketAddr),
+  Unix(PathBuf),
+}
+
+impl Listen {
+  /// Picks socket `name` when `transport` is [`Transport::Unix`], `tcp`
+  /// otherwise, or when the socket directory is unusable.
+  pub fn choose(transport: Transport, data_dir: &Path, name: &str, tcp: SocketAddr) -> Self {
+    match socket_path(transport, data_dir, name) {
+      Some(path) => Listen::Unix(path),
+      None => Listen::Tcp(tcp),
+    }
+  }
+
+  /// URI clients connect to.
+  pub fn uri(&self) -> String {
+    match self {
+      Listen::Tcp(addr) => format!("http://{addr}"),
+      Listen::Unix(path) => unix_uri(path),
+    }
+  }
+}
+
+/// Path of socket `name` when `transport` is [`Transport::Unix`]; `None`
+/// means fall back to TCP.
+pub fn socket_path(transport: Transport, data_dir: &Path, name: &str) -> Option<PathBuf> {
+  if transport != Transport::Unix {
+    return None;
+  }
+  let path = match socket_dir(data_dir) {
+    Ok(dir) => dir.join(name),
+    Err(err) => {
+      log::warn!("cannot use Unix sockets, falling back to TCP: {err}");
+      return None;
+    }
+  };
+  // sun_path holds 104 bytes on macOS, 108 on Linux, including the NUL.
+  if path.as_os_str().len() >= 104 {
+    log::warn!(
+      "socket path {} is too long, falling back to TCP",
+      path.display()
+    );
+    return None;
+  }
+  Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::history::testing::ScratchDir;
+
+  #[test]
+  fn parses_transports() {
+    assert_eq!("tcp".parse(), Ok(Transport::Tcp));
+    assert_eq!("TCP".parse(), Ok(Transport::Tcp));
+    assert!("pipe".parse::<Transport>().is_err());
+    #[cfg(unix)]
+    assert_eq!("Unix".parse(), Ok(Transport::Unix));
+  }
+
+  #[test]
+  fn addresses_are_uris_or_sockets() {
+    let tcp = Address::parse("http://127.0.0.1:50051".into()).unwrap();
+    assert_eq!(tcp.socket, None);
+    assert_eq!(tcp.endpoint.uri().to_string(), "http://127.0.0.1:50051/");
+
+    assert!(Address::parse("not a uri".into()).is_err());
+
+    #[cfg(unix)]
+    {
+      let unix = Address::parse("unix:/tmp/run/backend.sock".into()).unwrap();
+      assert_eq!(unix.socket, Some(PathBuf::from("/tmp/run/backend.sock")));
+      assert_eq!(unix.endpoint.uri().to_string(), "http://localhost/");
+    }
+  }
+
+  #[test]
+  fn listen_uris_round_trip() {
+    let tcp = Listen::Tcp("127.0.0.1:50052".parse().unwrap());
+    assert_eq!(tcp.uri(), "http://127.0.0.1:50052");
+
+    let path = PathBuf::from("/data/run/manager.sock");
+    let unix = Listen::Unix(path.clone());
+    assert_eq!(unix.uri(), "unix:/data/run/manager.sock");
+    #[cfg(unix)]
+    assert_eq!(Address::parse(unix.uri()).unwrap().socket, Some(path));
+  }
+
+  #[test]
+  fn tcp_transport_has_no_socket() {
+    let dir = ScratchDir::new("transport");
+    assert_eq!(
+      socket_path(Transport::Tcp, dir.path(), BACKEND_SOCKET),
+      None
+    );
+    assert!(!dir.path().join(SOCKET_DIR).exists());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn sockets_live_in_a_private_directory() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = ScratchDir::new("transport");
+    let path = socket_path(Transport::Unix, dir.path(), MANAGER_SOCKET).unwrap();
+    assert_eq!(path, dir.path().join(SOCKET_DIR).join(MANAGER_SOCKET));
+    let mode = std::fs::metadata(dir.path().join(SOCKET_DIR))
+      .unwrap()
+      .permissions()
+      .mode();
+    assert_eq!(mode & 0o777, 0o700);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn long_socket_paths_fall_back_to_tcp() {
+    let dir = ScratchDir::new("transport");
+    let run = format!("/{SOCKET_DIR}/");
+    let base = dir.path().as_os_str().len() + run.len() + MANAGER_SOCKET.len();
+    // Pad the data directory so the socket path is exactly `len` bytes.
+    let data_dir = |len: usize| dir.path().join("d".repeat(len - base - 1));
+
+    let longest = socket_path(Transport::Unix, &data_dir(103), MANAGER_SOCKET).unwrap();
+    assert_eq!(longest.as_os_str().len(), 103);
+    assert_eq!(
+      socket_path(Transport::Unix, &data_dir(104), MANAGER_SOCKET),
+      None
+    );
+
+    let tcp = "127.0.0.1:50052".parse().unwrap();
+    assert!(matches!(
+      Listen::choose(Transport::Unix, &data_dir(104), MANAGER_SOCKET, tcp),
+      Listen::Tcp(addr) if addr == tcp
+    ));
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn binding_replaces_a_stale_socket() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = ScratchDir::new("transport");
+    let path = socket_dir(dir.path()).unwrap().join(BACKEND_SOCKET);
+    std::fs::write(&path, "left behind").unwrap();
+
+    let _listener = bind_unix(&path).unwrap();
+    let metadata = std::fs::metadata(&path).unwrap();
+    assert!(!metadata.is_file());
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    tokio::net::UnixStream::connect(&path).await.unwrap();
+  }
+}
@@ -38,7 +38,15 @@ export interface HeartbeatRequest {
 }
 
 export interface HeartbeatResponse {
-  /** e.g., "run-task:<task_id>" */
+  /**
+   * Commands for the agent, cancellations first:
+   *   "cancel-task:<task_id>"           stop the task and remove its partial output
+   *   "run-task:<task_id>:<input_uri>"  run the task; split on the first two ':' only,
+   *                                     as the URI may contain more, and it is empty
+   *                                     for a task without input
+   * An agent acknowledges run-task with "ack:<task_id>" in its next heartbeat's
+   * capabilities; unacknowledged commands are resent, and fail after 3 sends.
+   */
   commands: string[];
 }
 
@@ -57,6 +65,18 @@ export interface TaskProgressRequest {
   agentId: string;
 }
 
+export interface CancelTaskRequest {
+  taskId: string;
+  /** optional, shown as the final progress message */
+  reason: string;
+}
+
+export interface CancelTaskResponse {
+  /** false if the task had already finished */
+  cancelled: boolean;
+  message: string;
+}
+
 function createBaseRegisterResponse(): RegisterResponse {
   return { ok: false, assignedId: "", message: "" };
 }
@@ -497,6 +517,158 @@ export const TaskProgressRequest: MessageFns<TaskProgressRequest> = {
   },
 };
 
+function createBaseCancelTaskRequest(): CancelTaskRequest {
+  return { taskId: "", reason: "" };
+}
+
+export const CancelTaskRequest: MessageFns<CancelTaskRequest> = {
+  encode(message: CancelTaskRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.taskId !== "") {
+      writer.uint32(10).string(message.taskId);
+    }
+    if (message.reason !== "") {
+      writer.uint32(18).string(message.reason);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): CancelTaskRequest {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseCancelTaskRequest();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.taskId = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.reason = reader.string();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): CancelTaskRequest {
+    return {
+      taskId: isSet(object.taskId) ? globalThis.String(object.taskId) : "",
+      reason: isSet(object.reason) ? globalThis.String(object.reason) : "",
+    };
+  },
+
+  toJSON(message: CancelTaskRequest): unknown {
+    const obj: any = {};
+    if (message.taskId !== "") {
+      obj.taskId = message.taskId;
+    }
+    if (message.reason !== "") {
+      obj.reason = message.reason;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<CancelTaskRequest>, I>>(base?: I): CancelTaskRequest {
+    return CancelTaskRequest.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<CancelTaskRequest>, I>>(object: I): CancelTaskRequest {
+    const message = createBaseCancelTaskRequest();
+    message.taskId = object.taskId ?? "";
+    message.reason = object.reason ?? "";
+    return message;
+  },
+};
+
+function createBaseCancelTaskResponse(): CancelTaskResponse {
+  return { cancelled: false, message: "" };
+}
+
+export const CancelTaskResponse: MessageFns<CancelTaskResponse> = {
+  encode(message: CancelTaskResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.cancelled !== false) {
+      writer.uint32(8).bool(message.cancelled);
+    }
+    if (message.message !== "") {
+      writer.uint32(18).string(message.message);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): CancelTaskResponse {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseCancelTaskResponse();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 8) {
+            break;
+          }
+
+          message.cancelled = reader.bool();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.message = reader.string();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): CancelTaskResponse {
+    return {
+      cancelled: isSet(object.cancelled) ? globalThis.Boolean(object.cancelled) : false,
+      message: isSet(object.message) ? globalThis.String(object.message) : "",
+    };
+  },
+
+  toJSON(message: CancelTaskResponse): unknown {
+    const obj: any = {};
+    if (message.cancelled !== false) {
+      obj.cancelled = message.cancelled;
+    }
+    if (message.message !== "") {
+      obj.message = message.message;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<CancelTaskResponse>, I>>(base?: I): CancelTaskResponse {
+    return CancelTaskResponse.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<CancelTaskResponse>, I>>(object: I): CancelTaskResponse {
+    const message = createBaseCancelTaskResponse();
+    message.cancelled = object.cancelled ?? false;
+    message.message = object.message ?? "";
+    return message;
+  },
+};
+
 export type AgentManagerService = typeof AgentManagerService;
 export const AgentManagerService = {
   /** Agents call this to register themselves with MCP */
@@ -549,6 +721,16 @@ export const AgentManagerService = {
     responseSerialize: (value: ProgressUpdate): Buffer => Buffer.from(ProgressUpdate.encode(value).finish()),
     responseDeserialize: (value: Buffer): ProgressUpdate => ProgressUpdate.decode(value),
   },
+  /** Cancel a queued or running task; its agent is told to stop via Heartbeat */
+  cancelTask: {
+    path: "/genai.manager.AgentManager/CancelTask",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: CancelTaskRequest): Buffer => Buffer.from(CancelTaskRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): CancelTaskRequest => CancelTaskRequest.decode(value),
+    responseSerialize: (value: CancelTaskResponse): Buffer => Buffer.from(CancelTaskResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): CancelTaskResponse => CancelTaskResponse.decode(value),
+  },
 } as const;
 
 export interface AgentManagerServer extends UntypedServiceImplementation {
@@ -562,6 +744,8 @@ export interface AgentManagerServer extends UntypedServiceImplementation {
   listAgents: handleUnaryCall<Empty, AgentList>;
   /** Stream task progress updates (server -> client) */
   streamProgress: handleServerStreamingCall<TaskProgressRequest, ProgressUpdate>;
+  /** Cancel a queued or running task; its agent is told to stop via Heartbeat */
+  cancelTask: handleUnaryCall<CancelTaskRequest, CancelTaskResponse>;
 }
 
 export interface AgentManagerClient extends Client {
@@ -624,6 +808,22 @@ export interface AgentManagerClient extends Client {
     metadata?: Metadata,
     options?: Partial<CallOptions>,
   ): ClientReadableStream<ProgressUpdate>;
+  /** Cancel a queued or running task; its agent is told to stop via Heartbeat */
+  cancelTask(
+    request: CancelTaskRequest,
+    callback: (error: ServiceError | null, response: CancelTaskResponse) => void,
+  ): ClientUnaryCall;
+  cancelTask(
+    request: CancelTaskRequest,
+    metadata: Metadata,
+    callback: (error: ServiceError | null, response: CancelTaskResponse) => void,
+  ): ClientUnaryCall;
+  cancelTask(
+    request: CancelTaskRequest,
+    metadata: Metadata,
+    options: Partial<CallOptions>,
+    callback: (error: ServiceError | null, response: CancelTaskResponse) => void,
+  ): ClientUnaryCall;
 }
 
 export const AgentManagerClient = makeGenericClientConstructor(
@@ -10,98 +10,240 @@ import {
   type CallOptions,
   type ChannelCredentials,
   Client,
-  type ClientDuplexStream,
   type ClientOptions,
+  type ClientReadableStream,
   type ClientUnaryCall,
-  type handleBidiStreamingCall,
+  type handleServerStreamingCall,
   type handleUnaryCall,
   makeGenericClientConstructor,
   type Metadata,
   type ServiceError,
   type UntypedServiceImplementation,
 } from "@grpc/grpc-js";
-import { Timestamp } from "./google/protobuf/timestamp";
 
-export const protobufPackage = "genai.chat";
+export const protobufPackage = "videoanalyzer.chat";
 
-export interface ChatMessage {
-  messageId: string;
-  userId: string;
+export interface Message {
+  /** uuid */
+  id: string;
+  /** allow multiple chats */
+  conversationId: string;
+  /** "user" or "system" or "agent" */
+  sender: string;
   text: string;
-  /** file paths */
+  /** unix epoch ms */
+  createdAt: number;
+  /** optional, 0..1 */
+  confidence: number;
+  /** human-in-loop flag */
+  needsClarification: boolean;
+  /** paths or IDs */
   attachments: string[];
-  createdAt?:
-    | Date
+  /** optional extra */
+  metadataJson: string;
+  /**
+   * Message this one follows; empty for the first one. Left empty on input,
+   * it follows the active branch.
+   */
+  parentId: string;
+  /** output only: versions of this message, oldest first */
+  siblingIds: string[];
+}
+
+export interface SendMessageRequest {
+  conversationId: string;
+  message?:
+    | Message
     | undefined;
-  /** optional: if the message references a task */
-  referencedTaskId: string;
+  /** if true, server will also stream agent responses */
+  streamResponses: boolean;
+  /**
+   * Optional: store the message as a new version of this earlier user
+   * message, branching the conversation there.
+   */
+  editOf: string;
 }
 
-export interface ChatResponse {
-  /** id of assistant response */
-  messageId: string;
-  /** original message id */
-  inReplyTo: string;
-  text: string;
-  /** e.g., /artifacts/summary.pdf */
-  attachments: string[];
-  createdAt?:
-    | Date
+export interface SendMessageResponse {
+  storedMessage?: Message | undefined;
+}
+
+export interface GetHistoryRequest {
+  conversationId: string;
+  limit: number;
+  offset: number;
+}
+
+export interface GetHistoryResponse {
+  messages: Message[];
+}
+
+export interface StreamResponse {
+  /** partial token/string while model generates */
+  partialText?:
+    | string
     | undefined;
-  /** optional confidence for assertions */
-  confidence: number;
-  needsClarification: boolean;
-  clarificationOptions: string[];
+  /** final stored message */
+  message?:
+    | Message
+    | undefined;
+  /** true when stream finished */
+  done: boolean;
 }
 
-export interface ChatAck {
-  ok: boolean;
-  message: string;
-  serverTimestamp: string;
+export interface Conversation {
+  id: string;
+  title: string;
+  /** unix epoch ms */
+  createdAt: number;
+  /** last message, or created_at if none */
+  updatedAt: number;
+  archived: boolean;
+  messageCount: number;
+  attachmentCount: number;
+  /** start of the last message's text */
+  lastMessagePreview: string;
+  lastMessageSender: string;
 }
 
-export interface HistoryRequest {
-  userId: string;
-  page: number;
-  pageSize: number;
+export interface ListConversationsRequest {
+  includeArchived: boolean;
+  /** 0 = all */
+  limit: number;
+  offset: number;
 }
 
-export interface HistoryResponse {
-  messages: ChatMessage[];
-  total: number;
+export interface ListConversationsResponse {
+  /** most recently active first */
+  conversations: Conversation[];
 }
 
-function createBaseChatMessage(): ChatMessage {
-  return { messageId: "", userId: "", text: "", attachments: [], createdAt: undefined, referencedTaskId: "" };
+export interface RenameConversationRequest {
+  conversationId: string;
+  title: string;
 }
 
-export const ChatMessage: MessageFns<ChatMessage> = {
-  encode(message: ChatMessage, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
-    if (message.messageId !== "") {
-      writer.uint32(10).string(message.messageId);
+export interface ArchiveConversationRequest {
+  conversationId: string;
+  /** false restores it */
+  archived: boolean;
+}
+
+export interface DeleteConversationRequest {
+  conversationId: string;
+}
+
+export interface DeleteConversationResponse {
+  /** false if there was no such conversation */
+  deleted: boolean;
+}
+
+export interface SwitchBranchRequest {
+  conversationId: string;
+  /** a version to show; its latest reply chain becomes active */
+  messageId: string;
+}
+
+export interface RegenerateRequest {
+  conversationId: string;
+  /**
+   * Optional: user message to answer again; default the last one on the
+   * active branch.
+   */
+  messageId: string;
+}
+
+export interface SearchMessagesRequest {
+  /** words to find; the last one also matches as a prefix */
+  query: string;
+  /** optional, exact match */
+  sender: string;
+  /** optional, created_at >= from (unix epoch ms) */
+  from: number;
+  /** optional, created_at <= to (unix epoch ms) */
+  to: number;
+  /** optional, search one conversation */
+  conversationId: string;
+  /** default 50 */
+  limit: number;
+  offset: number;
+}
+
+export interface SearchHit {
+  messageId: string;
+  conversationId: string;
+  conversationTitle: string;
+  sender: string;
+  createdAt: number;
+  /** HTML-escaped excerpt, matches wrapped in <mark></mark> */
+  snippet: string;
+  /** higher is more relevant */
+  score: number;
+}
+
+export interface SearchMessagesResponse {
+  /** best match first */
+  hits: SearchHit[];
+}
+
+function createBaseMessage(): Message {
+  return {
+    id: "",
+    conversationId: "",
+    sender: "",
+    text: "",
+    createdAt: 0,
+    confidence: 0,
+    needsClarification: false,
+    attachments: [],
+    metadataJson: "",
+    parentId: "",
+    siblingIds: [],
+  };
+}
+
+export const Message: MessageFns<Message> = {
+  encode(message: Message, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.id !== "") {
+      writer.uint32(10).string(message.id);
     }
-    if (message.userId !== "") {
-      writer.uint32(18).string(message.userId);
+    if (message.conversationId !== "") {
+      writer.uint32(18).string(message.conversationId);
+    }
+    if (message.sender !== "") {
+      writer.uint32(26).string(message.sender);
     }
     if (message.text !== "") {
-      writer.uint32(26).string(message.text);
+      writer.uint32(34).string(message.text);
+    }
+    if (message.createdAt !== 0) {
+      writer.uint32(40).int64(message.createdAt);
+    }
+    if (message.confidence !== 0) {
+      writer.uint32(49).double(message.confidence);
+    }
+    if (message.needsClarification !== false) {
+      writer.uint32(56).bool(message.needsClarification);
     }
     for (const v of message.attachments) {
-      writer.uint32(34).string(v!);
+      writer.uint32(66).string(v!);
+    }
+    if (message.metadataJson !== "") {
+      writer.uint32(74).string(message.metadataJson);
     }
-    if (message.createdAt !== undefined) {
-      Timestamp.encode(toTimestamp(message.createdAt), writer.uint32(42).fork()).join();
+    if (message.parentId !== "") {
+      writer.uint32(82).string(message.parentId);
     }
-    if (message.referencedTaskId !== "") {
-      writer.uint32(50).string(message.referencedTaskId);
+    for (const v of message.siblingIds) {
+      writer.uint32(90).string(v!);
     }
     return writer;
   },
 
-  decode(input: BinaryReader | Uint8Array, length?: number): ChatMessage {
+  decode(input: BinaryReader | Uint8Array, length?: number): Message {
     const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
     const end = length === undefined ? reader.len : reader.pos + length;
-    const message = createBaseChatMessage();
+    const message = createBaseMessage();
     while (reader.pos < end) {
       const tag = reader.uint32();
       switch (tag >>> 3) {
@@ -110,7 +252,7 @@ export const ChatMessage: MessageFns<ChatMessage> = {
             break;
           }
 
-          message.messageId = reader.string();
+          message.id = reader.string();
           continue;
         }
         case 2: {
@@ -118,7 +260,7 @@ export const ChatMessage: MessageFns<ChatMessage> = {
             break;
           }
 
-          message.userId = reader.string();
+          message.conversationId = reader.string();
           continue;
         }
         case 3: {
@@ -126,7 +268,7 @@ export const ChatMessage: MessageFns<ChatMessage> = {
             break;
           }
 
-          message.text = reader.string();
+          message.sender = reader.string();
           continue;
         }
         case 4: {
@@ -134,23 +276,63 @@ export const ChatMessage: MessageFns<ChatMessage> = {
             break;
           }
 
-          message.attachments.push(reader.string());
+          message.text = reader.string();
           continue;
         }
         case 5: {
-          if (tag !== 42) {
+          if (tag !== 40) {
             break;
           }
 
-          message.createdAt = fromTimestamp(Timestamp.decode(reader, reader.uint32()));
+          message.createdAt = longToNumber(reader.int64());
           continue;
         }
         case 6: {
-          if (tag !== 50) {
+          if (tag !== 49) {
+            break;
+          }
+
+          message.confidence = reader.double();
+          continue;
+        }
+        case 7: {
+          if (tag !== 56) {
+            break;
+          }
+
+          message.needsClarification = reader.bool();
+          continue;
+        }
+        case 8: {
+          if (tag !== 66) {
+            break;
+          }
+
+          message.attachments.push(reader.string());
+          continue;
+        }
+        case 9: {
+          if (tag !== 74) {
+            break;
+          }
+
+          message.metadataJson = reader.string();
+          continue;
+        }
+        case 10: {
+          if (tag !== 82) {
+            break;
+          }
+
+          message.parentId = reader.string();
+          continue;
+        }
+        case 11: {
+          if (tag !== 90) {
             break;
           }
 
-          message.referencedTaskId = reader.string();
+          message.siblingIds.push(reader.string());
           continue;
         }
       }
@@ -162,103 +344,109 @@ export const ChatMessage: MessageFns<ChatMessage> = {
     return message;
   },
 
-  fromJSON(object: any): ChatMessage {
+  fromJSON(object: any): Message {
     return {
-      messageId: isSet(object.messageId) ? globalThis.String(object.messageId) : "",
-      userId: isSet(object.userId) ? globalThis.String(object.userId) : "",
+      id: isSet(object.id) ? globalThis.String(object.id) : "",
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      sender: isSet(object.sender) ? globalThis.String(object.sender) : "",
       text: isSet(object.text) ? globalThis.String(object.text) : "",
+      createdAt: isSet(object.createdAt) ? globalThis.Number(object.createdAt) : 0,
+      confidence: isSet(object.confidence) ? globalThis.Number(object.confidence) : 0,
+      needsClarification: isSet(object.needsClarification) ? globalThis.Boolean(object.needsClarification) : false,
       attachments: globalThis.Array.isArray(object?.attachments)
         ? object.attachments.map((e: any) => globalThis.String(e))
         : [],
-      createdAt: isSet(object.createdAt) ? fromJsonTimestamp(object.createdAt) : undefined,
-      referencedTaskId: isSet(object.referencedTaskId) ? globalThis.String(object.referencedTaskId) : "",
+      metadataJson: isSet(object.metadataJson) ? globalThis.String(object.metadataJson) : "",
+      parentId: isSet(object.parentId) ? globalThis.String(object.parentId) : "",
+      siblingIds: globalThis.Array.isArray(object?.siblingIds)
+        ? object.siblingIds.map((e: any) => globalThis.String(e))
+        : [],
     };
   },
 
-  toJSON(message: ChatMessage): unknown {
+  toJSON(message: Message): unknown {
     const obj: any = {};
-    if (message.messageId !== "") {
-      obj.messageId = message.messageId;
+    if (message.id !== "") {
+      obj.id = message.id;
+    }
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
     }
-    if (message.userId !== "") {
-      obj.userId = message.userId;
+    if (message.sender !== "") {
+      obj.sender = message.sender;
     }
     if (message.text !== "") {
       obj.text = message.text;
     }
+    if (message.createdAt !== 0) {
+      obj.createdAt = Math.round(message.createdAt);
+    }
+    if (message.confidence !== 0) {
+      obj.confidence = message.confidence;
+    }
+    if (message.needsClarification !== false) {
+      obj.needsClarification = message.needsClarification;
+    }
     if (message.attachments?.length) {
       obj.attachments = message.attachments;
     }
-    if (message.createdAt !== undefined) {
-      obj.createdAt = message.createdAt.toISOString();
+    if (message.metadataJson !== "") {
+      obj.metadataJson = message.metadataJson;
+    }
+    if (message.parentId !== "") {
+      obj.parentId = message.parentId;
     }
-    if (message.referencedTaskId !== "") {
-      obj.referencedTaskId = message.referencedTaskId;
+    if (message.siblingIds?.length) {
+      obj.siblingIds = message.siblingIds;
     }
     return obj;
   },
 
-  create<I extends Exact<DeepPartial<ChatMessage>, I>>(base?: I): ChatMessage {
-    return ChatMessage.fromPartial(base ?? ({} as any));
+  create<I extends Exact<DeepPartial<Message>, I>>(base?: I): Message {
+    return Message.fromPartial(base ?? ({} as any));
   },
-  fromPartial<I extends Exact<DeepPartial<ChatMessage>, I>>(object: I): ChatMessage {
-    const message = createBaseChatMessage();
-    message.messageId = object.messageId ?? "";
-    message.userId = object.userId ?? "";
+  fromPartial<I extends Exact<DeepPartial<Message>, I>>(object: I): Message {
+    const message = createBaseMessage();
+    message.id = object.id ?? "";
+    message.conversationId = object.conversationId ?? "";
+    message.sender = object.sender ?? "";
     message.text = object.text ?? "";
+    message.createdAt = object.createdAt ?? 0;
+    message.confidence = object.confidence ?? 0;
+    message.needsClarification = object.needsClarification ?? false;
     message.attachments = object.attachments?.map((e) => e) || [];
-    message.createdAt = object.createdAt ?? undefined;
-    message.referencedTaskId = object.referencedTaskId ?? "";
+    message.metadataJson = object.metadataJson ?? "";
+    message.parentId = object.parentId ?? "";
+    message.siblingIds = object.siblingIds?.map((e) => e) || [];
     return message;
   },
 };
 
-function createBaseChatResponse(): ChatResponse {
-  return {
-    messageId: "",
-    inReplyTo: "",
-    text: "",
-    attachments: [],
-    createdAt: undefined,
-    confidence: 0,
-    needsClarification: false,
-    clarificationOptions: [],
-  };
+function createBaseSendMessageRequest(): SendMessageRequest {
+  return { conversationId: "", message: undefined, streamResponses: false, editOf: "" };
 }
 
-export const ChatResponse: MessageFns<ChatResponse> = {
-  encode(message: ChatResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
-    if (message.messageId !== "") {
-      writer.uint32(10).string(message.messageId);
-    }
-    if (message.inReplyTo !== "") {
-      writer.uint32(18).string(message.inReplyTo);
-    }
-    if (message.text !== "") {
-      writer.uint32(26).string(message.text);
+export const SendMessageRequest: MessageFns<SendMessageRequest> = {
+  encode(message: SendMessageRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.conversationId !== "") {
+      writer.uint32(10).string(message.conversationId);
     }
-    for (const v of message.attachments) {
-      writer.uint32(34).string(v!);
-    }
-    if (message.createdAt !== undefined) {
-      Timestamp.encode(toTimestamp(message.createdAt), writer.uint32(42).fork()).join();
-    }
-    if (message.confidence !== 0) {
-      writer.uint32(49).double(message.confidence);
+    if (message.message !== undefined) {
+      Message.encode(message.message, writer.uint32(18).fork()).join();
     }
-    if (message.needsClarification !== false) {
-      writer.uint32(56).bool(message.needsClarification);
+    if (message.streamResponses !== false) {
+      writer.uint32(24).bool(message.streamResponses);
     }
-    for (const v of message.clarificationOptions) {
-      writer.uint32(66).string(v!);
+    if (message.editOf !== "") {
+      writer.uint32(34).string(message.editOf);
     }
     return writer;
   },
 
-  decode(input: BinaryReader | Uint8Array, length?: number): ChatResponse {
+  decode(input: BinaryReader | Uint8Array, length?: number): SendMessageRequest {
     const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
     const end = length === undefined ? reader.len : reader.pos + length;
-    const message = createBaseChatResponse();
+    const message = createBaseSendMessageRequest();
     while (reader.pos < end) {
       const tag = reader.uint32();
       switch (tag >>> 3) {
@@ -267,7 +455,7 @@ export const ChatResponse: MessageFns<ChatResponse> = {
             break;
           }
 
-          message.messageId = reader.string();
+          message.conversationId = reader.string();
           continue;
         }
         case 2: {
@@ -275,15 +463,15 @@ export const ChatResponse: MessageFns<ChatResponse> = {
             break;
           }
 
-          message.inReplyTo = reader.string();
+          message.message = Message.decode(reader, reader.uint32());
           continue;
         }
         case 3: {
-          if (tag !== 26) {
+          if (tag !== 24) {
             break;
           }
 
-          message.text = reader.string();
+          message.streamResponses = reader.bool();
           continue;
         }
         case 4: {
@@ -291,39 +479,7 @@ export const ChatResponse: MessageFns<ChatResponse> = {
             break;
           }
 
-          message.attachments.push(reader.string());
-          continue;
-        }
-        case 5: {
-          if (tag !== 42) {
-            break;
-          }
-
-          message.createdAt = fromTimestamp(Timestamp.decode(reader, reader.uint32()));
-          continue;
-        }
-        case 6: {
-          if (tag !== 49) {
-            break;
-          }
-
-          message.confidence = reader.double();
-          continue;
-        }
-        case 7: {
-          if (tag !== 56) {
-            break;
-          }
-
-          message.needsClarification = reader.bool();
-          continue;
-        }
-        case 8: {
-          if (tag !== 66) {
-            break;
-          }
-
-          message.clarificationOptions.push(reader.string());
+          message.editOf = reader.string();
           continue;
         }
       }
@@ -335,116 +491,72 @@ export const ChatResponse: MessageFns<ChatResponse> = {
     return message;
   },
 
-  fromJSON(object: any): ChatResponse {
+  fromJSON(object: any): SendMessageRequest {
     return {
-      messageId: isSet(object.messageId) ? globalThis.String(object.messageId) : "",
-      inReplyTo: isSet(object.inReplyTo) ? globalThis.String(object.inReplyTo) : "",
-      text: isSet(object.text) ? globalThis.String(object.text) : "",
-      attachments: globalThis.Array.isArray(object?.attachments)
-        ? object.attachments.map((e: any) => globalThis.String(e))
-        : [],
-      createdAt: isSet(object.createdAt) ? fromJsonTimestamp(object.createdAt) : undefined,
-      confidence: isSet(object.confidence) ? globalThis.Number(object.confidence) : 0,
-      needsClarification: isSet(object.needsClarification) ? globalThis.Boolean(object.needsClarification) : false,
-      clarificationOptions: globalThis.Array.isArray(object?.clarificationOptions)
-        ? object.clarificationOptions.map((e: any) => globalThis.String(e))
-        : [],
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      message: isSet(object.message) ? Message.fromJSON(object.message) : undefined,
+      streamResponses: isSet(object.streamResponses) ? globalThis.Boolean(object.streamResponses) : false,
+      editOf: isSet(object.editOf) ? globalThis.String(object.editOf) : "",
     };
   },
 
-  toJSON(message: ChatResponse): unknown {
+  toJSON(message: SendMessageRequest): unknown {
     const obj: any = {};
-    if (message.messageId !== "") {
-      obj.messageId = message.messageId;
-    }
-    if (message.inReplyTo !== "") {
-      obj.inReplyTo = message.inReplyTo;
-    }
-    if (message.text !== "") {
-      obj.text = message.text;
-    }
-    if (message.attachments?.length) {
-      obj.attachments = message.attachments;
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
     }
-    if (message.createdAt !== undefined) {
-      obj.createdAt = message.createdAt.toISOString();
+    if (message.message !== undefined) {
+      obj.message = Message.toJSON(message.message);
     }
-    if (message.confidence !== 0) {
-      obj.confidence = message.confidence;
-    }
-    if (message.needsClarification !== false) {
-      obj.needsClarification = message.needsClarification;
+    if (message.streamResponses !== false) {
+      obj.streamResponses = message.streamResponses;
     }
-    if (message.clarificationOptions?.length) {
-      obj.clarificationOptions = message.clarificationOptions;
+    if (message.editOf !== "") {
+      obj.editOf = message.editOf;
     }
     return obj;
   },
 
-  create<I extends Exact<DeepPartial<ChatResponse>, I>>(base?: I): ChatResponse {
-    return ChatResponse.fromPartial(base ?? ({} as any));
+  create<I extends Exact<DeepPartial<SendMessageRequest>, I>>(base?: I): SendMessageRequest {
+    return SendMessageRequest.fromPartial(base ?? ({} as any));
   },
-  fromPartial<I extends Exact<DeepPartial<ChatResponse>, I>>(object: I): ChatResponse {
-    const message = createBaseChatResponse();
-    message.messageId = object.messageId ?? "";
-    message.inReplyTo = object.inReplyTo ?? "";
-    message.text = object.text ?? "";
-    message.attachments = object.attachments?.map((e) => e) || [];
-    message.createdAt = object.createdAt ?? undefined;
-    message.confidence = object.confidence ?? 0;
-    message.needsClarification = object.needsClarification ?? false;
-    message.clarificationOptions = object.clarificationOptions?.map((e) => e) || [];
+  fromPartial<I extends Exact<DeepPartial<SendMessageRequest>, I>>(object: I): SendMessageRequest {
+    const message = createBaseSendMessageRequest();
+    message.conversationId = object.conversationId ?? "";
+    message.message = (object.message !== undefined && object.message !== null)
+      ? Message.fromPartial(object.message)
+      : undefined;
+    message.streamResponses = object.streamResponses ?? false;
+    message.editOf = object.editOf ?? "";
     return message;
   },
 };
 
-function createBaseChatAck(): ChatAck {
-  return { ok: false, message: "", serverTimestamp: "" };
+function createBaseSendMessageResponse(): SendMessageResponse {
+  return { storedMessage: undefined };
 }
 
-export const ChatAck: MessageFns<ChatAck> = {
-  encode(message: ChatAck, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
-    if (message.ok !== false) {
-      writer.uint32(8).bool(message.ok);
-    }
-    if (message.message !== "") {
-      writer.uint32(18).string(message.message);
-    }
-    if (message.serverTimestamp !== "") {
-      writer.uint32(26).string(message.serverTimestamp);
+export const SendMessageResponse: MessageFns<SendMessageResponse> = {
+  encode(message: SendMessageResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.storedMessage !== undefined) {
+      Message.encode(message.storedMessage, writer.uint32(10).fork()).join();
     }
     return writer;
   },
 
-  decode(input: BinaryReader | Uint8Array, length?: number): ChatAck {
+  decode(input: BinaryReader | Uint8Array, length?: number): SendMessageResponse {
     const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
     const end = length === undefined ? reader.len : reader.pos + length;
-    const message = createBaseChatAck();
+    const message = createBaseSendMessageResponse();
     while (reader.pos < end) {
       const tag = reader.uint32();
       switch (tag >>> 3) {
         case 1: {
-          if (tag !== 8) {
-            break;
-          }
-
-          message.ok = reader.bool();
-          continue;
-        }
-        case 2: {
-          if (tag !== 18) {
-            break;
-          }
-
-          message.message = reader.string();
-          continue;
-        }
-        case 3: {
-          if (tag !== 26) {
+          if (tag !== 10) {
             break;
           }
 
-          message.serverTimestamp = reader.string();
+          message.storedMessage = Message.decode(reader, reader.uint32());
           continue;
         }
       }
@@ -456,62 +568,52 @@ export const ChatAck: MessageFns<ChatAck> = {
     return message;
   },
 
-  fromJSON(object: any): ChatAck {
-    return {
-      ok: isSet(object.ok) ? globalThis.Boolean(object.ok) : false,
-      message: isSet(object.message) ? globalThis.String(object.message) : "",
-      serverTimestamp: isSet(object.serverTimestamp) ? globalThis.String(object.serverTimestamp) : "",
-    };
+  fromJSON(object: any): SendMessageResponse {
+    return { storedMessage: isSet(object.storedMessage) ? Message.fromJSON(object.storedMessage) : undefined };
   },
 
-  toJSON(message: ChatAck): unknown {
+  toJSON(message: SendMessageResponse): unknown {
     const obj: any = {};
-    if (message.ok !== false) {
-      obj.ok = message.ok;
-    }
-    if (message.message !== "") {
-      obj.message = message.message;
-    }
-    if (message.serverTimestamp !== "") {
-      obj.serverTimestamp = message.serverTimestamp;
+    if (message.storedMessage !== undefined) {
+      obj.storedMessage = Message.toJSON(message.storedMessage);
     }
     return obj;
   },
 
-  create<I extends Exact<DeepPartial<ChatAck>, I>>(base?: I): ChatAck {
-    return ChatAck.fromPartial(base ?? ({} as any));
+  create<I extends Exact<DeepPartial<SendMessageResponse>, I>>(base?: I): SendMessageResponse {
+    return SendMessageResponse.fromPartial(base ?? ({} as any));
   },
-  fromPartial<I extends Exact<DeepPartial<ChatAck>, I>>(object: I): ChatAck {
-    const message = createBaseChatAck();
-    message.ok = object.ok ?? false;
-    message.message = object.message ?? "";
-    message.serverTimestamp = object.serverTimestamp ?? "";
+  fromPartial<I extends Exact<DeepPartial<SendMessageResponse>, I>>(object: I): SendMessageResponse {
+    const message = createBaseSendMessageResponse();
+    message.storedMessage = (object.storedMessage !== undefined && object.storedMessage !== null)
+      ? Message.fromPartial(object.storedMessage)
+      : undefined;
     return message;
   },
 };
 
-function createBaseHistoryRequest(): HistoryRequest {
-  return { userId: "", page: 0, pageSize: 0 };
+function createBaseGetHistoryRequest(): GetHistoryRequest {
+  return { conversationId: "", limit: 0, offset: 0 };
 }
 
-export const HistoryRequest: MessageFns<HistoryRequest> = {
-  encode(message: HistoryRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
-    if (message.userId !== "") {
-      writer.uint32(10).string(message.userId);
+export const GetHistoryRequest: MessageFns<GetHistoryRequest> = {
+  encode(message: GetHistoryRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.conversationId !== "") {
+      writer.uint32(10).string(message.conversationId);
     }
-    if (message.page !== 0) {
-      writer.uint32(16).int32(message.page);
+    if (message.limit !== 0) {
+      writer.uint32(16).int32(message.limit);
     }
-    if (message.pageSize !== 0) {
-      writer.uint32(24).int32(message.pageSize);
+    if (message.offset !== 0) {
+      writer.uint32(24).int32(message.offset);
     }
     return writer;
   },
 
-  decode(input: BinaryReader | Uint8Array, length?: number): HistoryRequest {
+  decode(input: BinaryReader | Uint8Array, length?: number): GetHistoryRequest {
     const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
     const end = length === undefined ? reader.len : reader.pos + length;
-    const message = createBaseHistoryRequest();
+    const message = createBaseGetHistoryRequest();
     while (reader.pos < end) {
       const tag = reader.uint32();
       switch (tag >>> 3) {
@@ -520,7 +622,7 @@ export const HistoryRequest: MessageFns<HistoryRequest> = {
             break;
           }
 
-          message.userId = reader.string();
+          message.conversationId = reader.string();
           continue;
         }
         case 2: {
@@ -528,7 +630,7 @@ export const HistoryRequest: MessageFns<HistoryRequest> = {
             break;
           }
 
-          message.page = reader.int32();
+          message.limit = reader.int32();
           continue;
         }
         case 3: {
@@ -536,7 +638,7 @@ export const HistoryRequest: MessageFns<HistoryRequest> = {
             break;
           }
 
-          message.pageSize = reader.int32();
+          message.offset = reader.int32();
           continue;
         }
       }
@@ -548,59 +650,56 @@ export const HistoryRequest: MessageFns<HistoryRequest> = {
     return message;
   },
 
-  fromJSON(object: any): HistoryRequest {
+  fromJSON(object: any): GetHistoryRequest {
     return {
-      userId: isSet(object.userId) ? globalThis.String(object.userId) : "",
-      page: isSet(object.page) ? globalThis.Number(object.page) : 0,
-      pageSize: isSet(object.pageSize) ? globalThis.Number(object.pageSize) : 0,
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      limit: isSet(object.limit) ? globalThis.Number(object.limit) : 0,
+      offset: isSet(object.offset) ? globalThis.Number(object.offset) : 0,
     };
   },
 
-  toJSON(message: HistoryRequest): unknown {
+  toJSON(message: GetHistoryRequest): unknown {
     const obj: any = {};
-    if (message.userId !== "") {
-      obj.userId = message.userId;
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
     }
-    if (message.page !== 0) {
-      obj.page = Math.round(message.page);
+    if (message.limit !== 0) {
+      obj.limit = Math.round(message.limit);
     }
-    if (message.pageSize !== 0) {
-      obj.pageSize = Math.round(message.pageSize);
+    if (message.offset !== 0) {
+      obj.offset = Math.round(message.offset);
     }
     return obj;
   },
 
-  create<I extends Exact<DeepPartial<HistoryRequest>, I>>(base?: I): HistoryRequest {
-    return HistoryRequest.fromPartial(base ?? ({} as any));
+  create<I extends Exact<DeepPartial<GetHistoryRequest>, I>>(base?: I): GetHistoryRequest {
+    return GetHistoryRequest.fromPartial(base ?? ({} as any));
   },
-  fromPartial<I extends Exact<DeepPartial<HistoryRequest>, I>>(object: I): HistoryRequest {
-    const message = createBaseHistoryRequest();
-    message.userId = object.userId ?? "";
-    message.page = object.page ?? 0;
-    message.pageSize = object.pageSize ?? 0;
+  fromPartial<I extends Exact<DeepPartial<GetHistoryRequest>, I>>(object: I): GetHistoryRequest {
+    const message = createBaseGetHistoryRequest();
+    message.conversationId = object.conversationId ?? "";
+    message.limit = object.limit ?? 0;
+    message.offset = object.offset ?? 0;
     return message;
   },
 };
 
-function createBaseHistoryResponse(): HistoryResponse {
-  return { messages: [], total: 0 };
+function createBaseGetHistoryResponse(): GetHistoryResponse {
+  return { messages: [] };
 }
 
-export const HistoryResponse: MessageFns<HistoryResponse> = {
-  encode(message: HistoryResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+export const GetHistoryResponse: MessageFns<GetHistoryResponse> = {
+  encode(message: GetHistoryResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
     for (const v of message.messages) {
-      ChatMessage.encode(v!, writer.uint32(10).fork()).join();
-    }
-    if (message.total !== 0) {
-      writer.uint32(16).int32(message.total);
+      Message.encode(v!, writer.uint32(10).fork()).join();
     }
     return writer;
   },
 
-  decode(input: BinaryReader | Uint8Array, length?: number): HistoryResponse {
+  decode(input: BinaryReader | Uint8Array, length?: number): GetHistoryResponse {
     const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
     const end = length === undefined ? reader.len : reader.pos + length;
-    const message = createBaseHistoryResponse();
+    const message = createBaseGetHistoryResponse();
     while (reader.pos < end) {
       const tag = reader.uint32();
       switch (tag >>> 3) {
@@ -609,15 +708,7 @@ export const HistoryResponse: MessageFns<HistoryResponse> = {
             break;
           }
 
-          message.messages.push(ChatMessage.decode(reader, reader.uint32()));
-          continue;
-        }
-        case 2: {
-          if (tag !== 16) {
-            break;
-          }
-
-          message.total = reader.int32();
+          message.messages.push(Message.decode(reader, reader.uint32()));
           continue;
         }
       }
@@ -629,119 +720,1540 @@ export const HistoryResponse: MessageFns<HistoryResponse> = {
     return message;
   },
 
-  fromJSON(object: any): HistoryResponse {
+  fromJSON(object: any): GetHistoryResponse {
     return {
-      messages: globalThis.Array.isArray(object?.messages)
-        ? object.messages.map((e: any) => ChatMessage.fromJSON(e))
-        : [],
-      total: isSet(object.total) ? globalThis.Number(object.total) : 0,
+      messages: globalThis.Array.isArray(object?.messages) ? object.messages.map((e: any) => Message.fromJSON(e)) : [],
     };
   },
 
-  toJSON(message: HistoryResponse): unknown {
+  toJSON(message: GetHistoryResponse): unknown {
     const obj: any = {};
     if (message.messages?.length) {
-      obj.messages = message.messages.map((e) => ChatMessage.toJSON(e));
-    }
-    if (message.total !== 0) {
-      obj.total = Math.round(message.total);
+      obj.messages = message.messages.map((e) => Message.toJSON(e));
     }
     return obj;
   },
 
-  create<I extends Exact<DeepPartial<HistoryResponse>, I>>(base?: I): HistoryResponse {
-    return HistoryResponse.fromPartial(base ?? ({} as any));
+  create<I extends Exact<DeepPartial<GetHistoryResponse>, I>>(base?: I): GetHistoryResponse {
+    return GetHistoryResponse.fromPartial(base ?? ({} as any));
   },
-  fromPartial<I extends Exact<DeepPartial<HistoryResponse>, I>>(object: I): HistoryResponse {
-    const message = createBaseHistoryResponse();
-    message.messages = object.messages?.map((e) => ChatMessage.fromPartial(e)) || [];
-    message.total = object.total ?? 0;
+  fromPartial<I extends Exact<DeepPartial<GetHistoryResponse>, I>>(object: I): GetHistoryResponse {
+    const message = createBaseGetHistoryResponse();
+    message.messages = object.messages?.map((e) => Message.fromPartial(e)) || [];
     return message;
   },
 };
 
-export type ChatServiceService = typeof ChatServiceService;
-export const ChatServiceService = {
-  /** Bi-directional stream: client sends messages, server replies with streamed responses */
-  streamChat: {
-    path: "/genai.chat.ChatService/StreamChat",
-    requestStream: true,
-    responseStream: true,
-    requestSerialize: (value: ChatMessage): Buffer => Buffer.from(ChatMessage.encode(value).finish()),
-    requestDeserialize: (value: Buffer): ChatMessage => ChatMessage.decode(value),
-    responseSerialize: (value: ChatResponse): Buffer => Buffer.from(ChatResponse.encode(value).finish()),
-    responseDeserialize: (value: Buffer): ChatResponse => ChatResponse.decode(value),
-  },
-  /** Non-streaming simple send */
-  sendMessage: {
-    path: "/genai.chat.ChatService/SendMessage",
-    requestStream: false,
-    responseStream: false,
-    requestSerialize: (value: ChatMessage): Buffer => Buffer.from(ChatMessage.encode(value).finish()),
-    requestDeserialize: (value: Buffer): ChatMessage => ChatMessage.decode(value),
-    responseSerialize: (value: ChatAck): Buffer => Buffer.from(ChatAck.encode(value).finish()),
-    responseDeserialize: (value: Buffer): ChatAck => ChatAck.decode(value),
-  },
-  /** Retrieve history (pagination) */
-  getHistory: {
-    path: "/genai.chat.ChatService/GetHistory",
-    requestStream: false,
-    responseStream: false,
-    requestSerialize: (value: HistoryRequest): Buffer => Buffer.from(HistoryRequest.encode(value).finish()),
-    requestDeserialize: (value: Buffer): HistoryRequest => HistoryRequest.decode(value),
-    responseSerialize: (value: HistoryResponse): Buffer => Buffer.from(HistoryResponse.encode(value).finish()),
-    responseDeserialize: (value: Buffer): HistoryResponse => HistoryResponse.decode(value),
+function createBaseStreamResponse(): StreamResponse {
+  return { partialText: undefined, message: undefined, done: false };
+}
+
+export const StreamResponse: MessageFns<StreamResponse> = {
+  encode(message: StreamResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.partialText !== undefined) {
+      writer.uint32(10).string(message.partialText);
+    }
+    if (message.message !== undefined) {
+      Message.encode(message.message, writer.uint32(18).fork()).join();
+    }
+    if (message.done !== false) {
+      writer.uint32(24).bool(message.done);
+    }
+    return writer;
   },
-} as const;
 
-export interface ChatServiceServer extends UntypedServiceImplementation {
-  /** Bi-directional stream: client sends messages, server replies with streamed responses */
-  streamChat: handleBidiStreamingCall<ChatMessage, ChatResponse>;
-  /** Non-streaming simple send */
-  sendMessage: handleUnaryCall<ChatMessage, ChatAck>;
-  /** Retrieve history (pagination) */
-  getHistory: handleUnaryCall<HistoryRequest, HistoryResponse>;
-}
+  decode(input: BinaryReader | Uint8Array, length?: number): StreamResponse {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseStreamResponse();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
 
-export interface ChatServiceClient extends Client {
-  /** Bi-directional stream: client sends messages, server replies with streamed responses */
-  streamChat(): ClientDuplexStream<ChatMessage, ChatResponse>;
-  streamChat(options: Partial<CallOptions>): ClientDuplexStream<ChatMessage, ChatResponse>;
-  streamChat(metadata: Metadata, options?: Partial<CallOptions>): ClientDuplexStream<ChatMessage, ChatResponse>;
-  /** Non-streaming simple send */
-  sendMessage(request: ChatMessage, callback: (error: ServiceError | null, response: ChatAck) => void): ClientUnaryCall;
+          message.partialText = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.message = Message.decode(reader, reader.uint32());
+          continue;
+        }
+        case 3: {
+          if (tag !== 24) {
+            break;
+          }
+
+          message.done = reader.bool();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): StreamResponse {
+    return {
+      partialText: isSet(object.partialText) ? globalThis.String(object.partialText) : undefined,
+      message: isSet(object.message) ? Message.fromJSON(object.message) : undefined,
+      done: isSet(object.done) ? globalThis.Boolean(object.done) : false,
+    };
+  },
+
+  toJSON(message: StreamResponse): unknown {
+    const obj: any = {};
+    if (message.partialText !== undefined) {
+      obj.partialText = message.partialText;
+    }
+    if (message.message !== undefined) {
+      obj.message = Message.toJSON(message.message);
+    }
+    if (message.done !== false) {
+      obj.done = message.done;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<StreamResponse>, I>>(base?: I): StreamResponse {
+    return StreamResponse.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<StreamResponse>, I>>(object: I): StreamResponse {
+    const message = createBaseStreamResponse();
+    message.partialText = object.partialText ?? undefined;
+    message.message = (object.message !== undefined && object.message !== null)
+      ? Message.fromPartial(object.message)
+      : undefined;
+    message.done = object.done ?? false;
+    return message;
+  },
+};
+
+function createBaseConversation(): Conversation {
+  return {
+    id: "",
+    title: "",
+    createdAt: 0,
+    updatedAt: 0,
+    archived: false,
+    messageCount: 0,
+    attachmentCount: 0,
+    lastMessagePreview: "",
+    lastMessageSender: "",
+  };
+}
+
+export const Conversation: MessageFns<Conversation> = {
+  encode(message: Conversation, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.id !== "") {
+      writer.uint32(10).string(message.id);
+    }
+    if (message.title !== "") {
+      writer.uint32(18).string(message.title);
+    }
+    if (message.createdAt !== 0) {
+      writer.uint32(24).int64(message.createdAt);
+    }
+    if (message.updatedAt !== 0) {
+      writer.uint32(32).int64(message.updatedAt);
+    }
+    if (message.archived !== false) {
+      writer.uint32(40).bool(message.archived);
+    }
+    if (message.messageCount !== 0) {
+      writer.uint32(48).int32(message.messageCount);
+    }
+    if (message.attachmentCount !== 0) {
+      writer.uint32(56).int32(message.attachmentCount);
+    }
+    if (message.lastMessagePreview !== "") {
+      writer.uint32(66).string(message.lastMessagePreview);
+    }
+    if (message.lastMessageSender !== "") {
+      writer.uint32(74).string(message.lastMessageSender);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): Conversation {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseConversation();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.id = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.title = reader.string();
+          continue;
+        }
+        case 3: {
+          if (tag !== 24) {
+            break;
+          }
+
+          message.createdAt = longToNumber(reader.int64());
+          continue;
+        }
+        case 4: {
+          if (tag !== 32) {
+            break;
+          }
+
+          message.updatedAt = longToNumber(reader.int64());
+          continue;
+        }
+        case 5: {
+          if (tag !== 40) {
+            break;
+          }
+
+          message.archived = reader.bool();
+          continue;
+        }
+        case 6: {
+          if (tag !== 48) {
+            break;
+          }
+
+          message.messageCount = reader.int32();
+          continue;
+        }
+        case 7: {
+          if (tag !== 56) {
+            break;
+          }
+
+          message.attachmentCount = reader.int32();
+          continue;
+        }
+        case 8: {
+          if (tag !== 66) {
+            break;
+          }
+
+          message.lastMessagePreview = reader.string();
+          continue;
+        }
+        case 9: {
+          if (tag !== 74) {
+            break;
+          }
+
+          message.lastMessageSender = reader.string();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): Conversation {
+    return {
+      id: isSet(object.id) ? globalThis.String(object.id) : "",
+      title: isSet(object.title) ? globalThis.String(object.title) : "",
+      createdAt: isSet(object.createdAt) ? globalThis.Number(object.createdAt) : 0,
+      updatedAt: isSet(object.updatedAt) ? globalThis.Number(object.updatedAt) : 0,
+      archived: isSet(object.archived) ? globalThis.Boolean(object.archived) : false,
+      messageCount: isSet(object.messageCount) ? globalThis.Number(object.messageCount) : 0,
+      attachmentCount: isSet(object.attachmentCount) ? globalThis.Number(object.attachmentCount) : 0,
+      lastMessagePreview: isSet(object.lastMessagePreview) ? globalThis.String(object.lastMessagePreview) : "",
+      lastMessageSender: isSet(object.lastMessageSender) ? globalThis.String(object.lastMessageSender) : "",
+    };
+  },
+
+  toJSON(message: Conversation): unknown {
+    const obj: any = {};
+    if (message.id !== "") {
+      obj.id = message.id;
+    }
+    if (message.title !== "") {
+      obj.title = message.title;
+    }
+    if (message.createdAt !== 0) {
+      obj.createdAt = Math.round(message.createdAt);
+    }
+    if (message.updatedAt !== 0) {
+      obj.updatedAt = Math.round(message.updatedAt);
+    }
+    if (message.archived !== false) {
+      obj.archived = message.archived;
+    }
+    if (message.messageCount !== 0) {
+      obj.messageCount = Math.round(message.messageCount);
+    }
+    if (message.attachmentCount !== 0) {
+      obj.attachmentCount = Math.round(message.attachmentCount);
+    }
+    if (message.lastMessagePreview !== "") {
+      obj.lastMessagePreview = message.lastMessagePreview;
+    }
+    if (message.lastMessageSender !== "") {
+      obj.lastMessageSender = message.lastMessageSender;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<Conversation>, I>>(base?: I): Conversation {
+    return Conversation.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<Conversation>, I>>(object: I): Conversation {
+    const message = createBaseConversation();
+    message.id = object.id ?? "";
+    message.title = object.title ?? "";
+    message.createdAt = object.createdAt ?? 0;
+    message.updatedAt = object.updatedAt ?? 0;
+    message.archived = object.archived ?? false;
+    message.messageCount = object.messageCount ?? 0;
+    message.attachmentCount = object.attachmentCount ?? 0;
+    message.lastMessagePreview = object.lastMessagePreview ?? "";
+    message.lastMessageSender = object.lastMessageSender ?? "";
+    return message;
+  },
+};
+
+function createBaseListConversationsRequest(): ListConversationsRequest {
+  return { includeArchived: false, limit: 0, offset: 0 };
+}
+
+export const ListConversationsRequest: MessageFns<ListConversationsRequest> = {
+  encode(message: ListConversationsRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.includeArchived !== false) {
+      writer.uint32(8).bool(message.includeArchived);
+    }
+    if (message.limit !== 0) {
+      writer.uint32(16).int32(message.limit);
+    }
+    if (message.offset !== 0) {
+      writer.uint32(24).int32(message.offset);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): ListConversationsRequest {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseListConversationsRequest();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 8) {
+            break;
+          }
+
+          message.includeArchived = reader.bool();
+          continue;
+        }
+        case 2: {
+          if (tag !== 16) {
+            break;
+          }
+
+          message.limit = reader.int32();
+          continue;
+        }
+        case 3: {
+          if (tag !== 24) {
+            break;
+          }
+
+          message.offset = reader.int32();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): ListConversationsRequest {
+    return {
+      includeArchived: isSet(object.includeArchived) ? globalThis.Boolean(object.includeArchived) : false,
+      limit: isSet(object.limit) ? globalThis.Number(object.limit) : 0,
+      offset: isSet(object.offset) ? globalThis.Number(object.offset) : 0,
+    };
+  },
+
+  toJSON(message: ListConversationsRequest): unknown {
+    const obj: any = {};
+    if (message.includeArchived !== false) {
+      obj.includeArchived = message.includeArchived;
+    }
+    if (message.limit !== 0) {
+      obj.limit = Math.round(message.limit);
+    }
+    if (message.offset !== 0) {
+      obj.offset = Math.round(message.offset);
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<ListConversationsRequest>, I>>(base?: I): ListConversationsRequest {
+    return ListConversationsRequest.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<ListConversationsRequest>, I>>(object: I): ListConversationsRequest {
+    const message = createBaseListConversationsRequest();
+    message.includeArchived = object.includeArchived ?? false;
+    message.limit = object.limit ?? 0;
+    message.offset = object.offset ?? 0;
+    return message;
+  },
+};
+
+function createBaseListConversationsResponse(): ListConversationsResponse {
+  return { conversations: [] };
+}
+
+export const ListConversationsResponse: MessageFns<ListConversationsResponse> = {
+  encode(message: ListConversationsResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    for (const v of message.conversations) {
+      Conversation.encode(v!, writer.uint32(10).fork()).join();
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): ListConversationsResponse {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseListConversationsResponse();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.conversations.push(Conversation.decode(reader, reader.uint32()));
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): ListConversationsResponse {
+    return {
+      conversations: globalThis.Array.isArray(object?.conversations)
+        ? object.conversations.map((e: any) => Conversation.fromJSON(e))
+        : [],
+    };
+  },
+
+  toJSON(message: ListConversationsResponse): unknown {
+    const obj: any = {};
+    if (message.conversations?.length) {
+      obj.conversations = message.conversations.map((e) => Conversation.toJSON(e));
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<ListConversationsResponse>, I>>(base?: I): ListConversationsResponse {
+    return ListConversationsResponse.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<ListConversationsResponse>, I>>(object: I): ListConversationsResponse {
+    const message = createBaseListConversationsResponse();
+    message.conversations = object.conversations?.map((e) => Conversation.fromPartial(e)) || [];
+    return message;
+  },
+};
+
+function createBaseRenameConversationRequest(): RenameConversationRequest {
+  return { conversationId: "", title: "" };
+}
+
+export const RenameConversationRequest: MessageFns<RenameConversationRequest> = {
+  encode(message: RenameConversationRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.conversationId !== "") {
+      writer.uint32(10).string(message.conversationId);
+    }
+    if (message.title !== "") {
+      writer.uint32(18).string(message.title);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): RenameConversationRequest {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseRenameConversationRequest();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.conversationId = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.title = reader.string();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): RenameConversationRequest {
+    return {
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      title: isSet(object.title) ? globalThis.String(object.title) : "",
+    };
+  },
+
+  toJSON(message: RenameConversationRequest): unknown {
+    const obj: any = {};
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
+    }
+    if (message.title !== "") {
+      obj.title = message.title;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<RenameConversationRequest>, I>>(base?: I): RenameConversationRequest {
+    return RenameConversationRequest.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<RenameConversationRequest>, I>>(object: I): RenameConversationRequest {
+    const message = createBaseRenameConversationRequest();
+    message.conversationId = object.conversationId ?? "";
+    message.title = object.title ?? "";
+    return message;
+  },
+};
+
+function createBaseArchiveConversationRequest(): ArchiveConversationRequest {
+  return { conversationId: "", archived: false };
+}
+
+export const ArchiveConversationRequest: MessageFns<ArchiveConversationRequest> = {
+  encode(message: ArchiveConversationRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.conversationId !== "") {
+      writer.uint32(10).string(message.conversationId);
+    }
+    if (message.archived !== false) {
+      writer.uint32(16).bool(message.archived);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): ArchiveConversationRequest {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseArchiveConversationRequest();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.conversationId = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 16) {
+            break;
+          }
+
+          message.archived = reader.bool();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): ArchiveConversationRequest {
+    return {
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      archived: isSet(object.archived) ? globalThis.Boolean(object.archived) : false,
+    };
+  },
+
+  toJSON(message: ArchiveConversationRequest): unknown {
+    const obj: any = {};
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
+    }
+    if (message.archived !== false) {
+      obj.archived = message.archived;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<ArchiveConversationRequest>, I>>(base?: I): ArchiveConversationRequest {
+    return ArchiveConversationRequest.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<ArchiveConversationRequest>, I>>(object: I): ArchiveConversationRequest {
+    const message = createBaseArchiveConversationRequest();
+    message.conversationId = object.conversationId ?? "";
+    message.archived = object.archived ?? false;
+    return message;
+  },
+};
+
+function createBaseDeleteConversationRequest(): DeleteConversationRequest {
+  return { conversationId: "" };
+}
+
+export const DeleteConversationRequest: MessageFns<DeleteConversationRequest> = {
+  encode(message: DeleteConversationRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.conversationId !== "") {
+      writer.uint32(10).string(message.conversationId);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): DeleteConversationRequest {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseDeleteConversationRequest();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.conversationId = reader.string();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): DeleteConversationRequest {
+    return { conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "" };
+  },
+
+  toJSON(message: DeleteConversationRequest): unknown {
+    const obj: any = {};
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<DeleteConversationRequest>, I>>(base?: I): DeleteConversationRequest {
+    return DeleteConversationRequest.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<DeleteConversationRequest>, I>>(object: I): DeleteConversationRequest {
+    const message = createBaseDeleteConversationRequest();
+    message.conversationId = object.conversationId ?? "";
+    return message;
+  },
+};
+
+function createBaseDeleteConversationResponse(): DeleteConversationResponse {
+  return { deleted: false };
+}
+
+export const DeleteConversationResponse: MessageFns<DeleteConversationResponse> = {
+  encode(message: DeleteConversationResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.deleted !== false) {
+      writer.uint32(8).bool(message.deleted);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): DeleteConversationResponse {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseDeleteConversationResponse();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 8) {
+            break;
+          }
+
+          message.deleted = reader.bool();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): DeleteConversationResponse {
+    return { deleted: isSet(object.deleted) ? globalThis.Boolean(object.deleted) : false };
+  },
+
+  toJSON(message: DeleteConversationResponse): unknown {
+    const obj: any = {};
+    if (message.deleted !== false) {
+      obj.deleted = message.deleted;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<DeleteConversationResponse>, I>>(base?: I): DeleteConversationResponse {
+    return DeleteConversationResponse.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<DeleteConversationResponse>, I>>(object: I): DeleteConversationResponse {
+    const message = createBaseDeleteConversationResponse();
+    message.deleted = object.deleted ?? false;
+    return message;
+  },
+};
+
+function createBaseSwitchBranchRequest(): SwitchBranchRequest {
+  return { conversationId: "", messageId: "" };
+}
+
+export const SwitchBranchRequest: MessageFns<SwitchBranchRequest> = {
+  encode(message: SwitchBranchRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.conversationId !== "") {
+      writer.uint32(10).string(message.conversationId);
+    }
+    if (message.messageId !== "") {
+      writer.uint32(18).string(message.messageId);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): SwitchBranchRequest {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseSwitchBranchRequest();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.conversationId = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.messageId = reader.string();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): SwitchBranchRequest {
+    return {
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      messageId: isSet(object.messageId) ? globalThis.String(object.messageId) : "",
+    };
+  },
+
+  toJSON(message: SwitchBranchRequest): unknown {
+    const obj: any = {};
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
+    }
+    if (message.messageId !== "") {
+      obj.messageId = message.messageId;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<SwitchBranchRequest>, I>>(base?: I): SwitchBranchRequest {
+    return SwitchBranchRequest.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<SwitchBranchRequest>, I>>(object: I): SwitchBranchRequest {
+    const message = createBaseSwitchBranchRequest();
+    message.conversationId = object.conversationId ?? "";
+    message.messageId = object.messageId ?? "";
+    return message;
+  },
+};
+
+function createBaseRegenerateRequest(): RegenerateRequest {
+  return { conversationId: "", messageId: "" };
+}
+
+export const RegenerateRequest: MessageFns<RegenerateRequest> = {
+  encode(message: RegenerateRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.conversationId !== "") {
+      writer.uint32(10).string(message.conversationId);
+    }
+    if (message.messageId !== "") {
+      writer.uint32(18).string(message.messageId);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): RegenerateRequest {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseRegenerateRequest();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.conversationId = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.messageId = reader.string();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): RegenerateRequest {
+    return {
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      messageId: isSet(object.messageId) ? globalThis.String(object.messageId) : "",
+    };
+  },
+
+  toJSON(message: RegenerateRequest): unknown {
+    const obj: any = {};
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
+    }
+    if (message.messageId !== "") {
+      obj.messageId = message.messageId;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<RegenerateRequest>, I>>(base?: I): RegenerateRequest {
+    return RegenerateRequest.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<RegenerateRequest>, I>>(object: I): RegenerateRequest {
+    const message = createBaseRegenerateRequest();
+    message.conversationId = object.conversationId ?? "";
+    message.messageId = object.messageId ?? "";
+    return message;
+  },
+};
+
+function createBaseSearchMessagesRequest(): SearchMessagesRequest {
+  return { query: "", sender: "", from: 0, to: 0, conversationId: "", limit: 0, offset: 0 };
+}
+
+export const SearchMessagesRequest: MessageFns<SearchMessagesRequest> = {
+  encode(message: SearchMessagesRequest, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.query !== "") {
+      writer.uint32(10).string(message.query);
+    }
+    if (message.sender !== "") {
+      writer.uint32(18).string(message.sender);
+    }
+    if (message.from !== 0) {
+      writer.uint32(24).int64(message.from);
+    }
+    if (message.to !== 0) {
+      writer.uint32(32).int64(message.to);
+    }
+    if (message.conversationId !== "") {
+      writer.uint32(42).string(message.conversationId);
+    }
+    if (message.limit !== 0) {
+      writer.uint32(48).int32(message.limit);
+    }
+    if (message.offset !== 0) {
+      writer.uint32(56).int32(message.offset);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): SearchMessagesRequest {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseSearchMessagesRequest();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.query = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.sender = reader.string();
+          continue;
+        }
+        case 3: {
+          if (tag !== 24) {
+            break;
+          }
+
+          message.from = longToNumber(reader.int64());
+          continue;
+        }
+        case 4: {
+          if (tag !== 32) {
+            break;
+          }
+
+          message.to = longToNumber(reader.int64());
+          continue;
+        }
+        case 5: {
+          if (tag !== 42) {
+            break;
+          }
+
+          message.conversationId = reader.string();
+          continue;
+        }
+        case 6: {
+          if (tag !== 48) {
+            break;
+          }
+
+          message.limit = reader.int32();
+          continue;
+        }
+        case 7: {
+          if (tag !== 56) {
+            break;
+          }
+
+          message.offset = reader.int32();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): SearchMessagesRequest {
+    return {
+      query: isSet(object.query) ? globalThis.String(object.query) : "",
+      sender: isSet(object.sender) ? globalThis.String(object.sender) : "",
+      from: isSet(object.from) ? globalThis.Number(object.from) : 0,
+      to: isSet(object.to) ? globalThis.Number(object.to) : 0,
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      limit: isSet(object.limit) ? globalThis.Number(object.limit) : 0,
+      offset: isSet(object.offset) ? globalThis.Number(object.offset) : 0,
+    };
+  },
+
+  toJSON(message: SearchMessagesRequest): unknown {
+    const obj: any = {};
+    if (message.query !== "") {
+      obj.query = message.query;
+    }
+    if (message.sender !== "") {
+      obj.sender = message.sender;
+    }
+    if (message.from !== 0) {
+      obj.from = Math.round(message.from);
+    }
+    if (message.to !== 0) {
+      obj.to = Math.round(message.to);
+    }
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
+    }
+    if (message.limit !== 0) {
+      obj.limit = Math.round(message.limit);
+    }
+    if (message.offset !== 0) {
+      obj.offset = Math.round(message.offset);
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<SearchMessagesRequest>, I>>(base?: I): SearchMessagesRequest {
+    return SearchMessagesRequest.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<SearchMessagesRequest>, I>>(object: I): SearchMessagesRequest {
+    const message = createBaseSearchMessagesRequest();
+    message.query = object.query ?? "";
+    message.sender = object.sender ?? "";
+    message.from = object.from ?? 0;
+    message.to = object.to ?? 0;
+    message.conversationId = object.conversationId ?? "";
+    message.limit = object.limit ?? 0;
+    message.offset = object.offset ?? 0;
+    return message;
+  },
+};
+
+function createBaseSearchHit(): SearchHit {
+  return { messageId: "", conversationId: "", conversationTitle: "", sender: "", createdAt: 0, snippet: "", score: 0 };
+}
+
+export const SearchHit: MessageFns<SearchHit> = {
+  encode(message: SearchHit, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    if (message.messageId !== "") {
+      writer.uint32(10).string(message.messageId);
+    }
+    if (message.conversationId !== "") {
+      writer.uint32(18).string(message.conversationId);
+    }
+    if (message.conversationTitle !== "") {
+      writer.uint32(26).string(message.conversationTitle);
+    }
+    if (message.sender !== "") {
+      writer.uint32(34).string(message.sender);
+    }
+    if (message.createdAt !== 0) {
+      writer.uint32(40).int64(message.createdAt);
+    }
+    if (message.snippet !== "") {
+      writer.uint32(50).string(message.snippet);
+    }
+    if (message.score !== 0) {
+      writer.uint32(57).double(message.score);
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): SearchHit {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseSearchHit();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.messageId = reader.string();
+          continue;
+        }
+        case 2: {
+          if (tag !== 18) {
+            break;
+          }
+
+          message.conversationId = reader.string();
+          continue;
+        }
+        case 3: {
+          if (tag !== 26) {
+            break;
+          }
+
+          message.conversationTitle = reader.string();
+          continue;
+        }
+        case 4: {
+          if (tag !== 34) {
+            break;
+          }
+
+          message.sender = reader.string();
+          continue;
+        }
+        case 5: {
+          if (tag !== 40) {
+            break;
+          }
+
+          message.createdAt = longToNumber(reader.int64());
+          continue;
+        }
+        case 6: {
+          if (tag !== 50) {
+            break;
+          }
+
+          message.snippet = reader.string();
+          continue;
+        }
+        case 7: {
+          if (tag !== 57) {
+            break;
+          }
+
+          message.score = reader.double();
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): SearchHit {
+    return {
+      messageId: isSet(object.messageId) ? globalThis.String(object.messageId) : "",
+      conversationId: isSet(object.conversationId) ? globalThis.String(object.conversationId) : "",
+      conversationTitle: isSet(object.conversationTitle) ? globalThis.String(object.conversationTitle) : "",
+      sender: isSet(object.sender) ? globalThis.String(object.sender) : "",
+      createdAt: isSet(object.createdAt) ? globalThis.Number(object.createdAt) : 0,
+      snippet: isSet(object.snippet) ? globalThis.String(object.snippet) : "",
+      score: isSet(object.score) ? globalThis.Number(object.score) : 0,
+    };
+  },
+
+  toJSON(message: SearchHit): unknown {
+    const obj: any = {};
+    if (message.messageId !== "") {
+      obj.messageId = message.messageId;
+    }
+    if (message.conversationId !== "") {
+      obj.conversationId = message.conversationId;
+    }
+    if (message.conversationTitle !== "") {
+      obj.conversationTitle = message.conversationTitle;
+    }
+    if (message.sender !== "") {
+      obj.sender = message.sender;
+    }
+    if (message.createdAt !== 0) {
+      obj.createdAt = Math.round(message.createdAt);
+    }
+    if (message.snippet !== "") {
+      obj.snippet = message.snippet;
+    }
+    if (message.score !== 0) {
+      obj.score = message.score;
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<SearchHit>, I>>(base?: I): SearchHit {
+    return SearchHit.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<SearchHit>, I>>(object: I): SearchHit {
+    const message = createBaseSearchHit();
+    message.messageId = object.messageId ?? "";
+    message.conversationId = object.conversationId ?? "";
+    message.conversationTitle = object.conversationTitle ?? "";
+    message.sender = object.sender ?? "";
+    message.createdAt = object.createdAt ?? 0;
+    message.snippet = object.snippet ?? "";
+    message.score = object.score ?? 0;
+    return message;
+  },
+};
+
+function createBaseSearchMessagesResponse(): SearchMessagesResponse {
+  return { hits: [] };
+}
+
+export const SearchMessagesResponse: MessageFns<SearchMessagesResponse> = {
+  encode(message: SearchMessagesResponse, writer: BinaryWriter = new BinaryWriter()): BinaryWriter {
+    for (const v of message.hits) {
+      SearchHit.encode(v!, writer.uint32(10).fork()).join();
+    }
+    return writer;
+  },
+
+  decode(input: BinaryReader | Uint8Array, length?: number): SearchMessagesResponse {
+    const reader = input instanceof BinaryReader ? input : new BinaryReader(input);
+    const end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseSearchMessagesResponse();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1: {
+          if (tag !== 10) {
+            break;
+          }
+
+          message.hits.push(SearchHit.decode(reader, reader.uint32()));
+          continue;
+        }
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skip(tag & 7);
+    }
+    return message;
+  },
+
+  fromJSON(object: any): SearchMessagesResponse {
+    return { hits: globalThis.Array.isArray(object?.hits) ? object.hits.map((e: any) => SearchHit.fromJSON(e)) : [] };
+  },
+
+  toJSON(message: SearchMessagesResponse): unknown {
+    const obj: any = {};
+    if (message.hits?.length) {
+      obj.hits = message.hits.map((e) => SearchHit.toJSON(e));
+    }
+    return obj;
+  },
+
+  create<I extends Exact<DeepPartial<SearchMessagesResponse>, I>>(base?: I): SearchMessagesResponse {
+    return SearchMessagesResponse.fromPartial(base ?? ({} as any));
+  },
+  fromPartial<I extends Exact<DeepPartial<SearchMessagesResponse>, I>>(object: I): SearchMessagesResponse {
+    const message = createBaseSearchMessagesResponse();
+    message.hits = object.hits?.map((e) => SearchHit.fromPartial(e)) || [];
+    return message;
+  },
+};
+
+export type ChatServiceService = typeof ChatServiceService;
+export const ChatServiceService = {
+  sendMessage: {
+    path: "/videoanalyzer.chat.ChatService/SendMessage",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: SendMessageRequest): Buffer => Buffer.from(SendMessageRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): SendMessageRequest => SendMessageRequest.decode(value),
+    responseSerialize: (value: SendMessageResponse): Buffer => Buffer.from(SendMessageResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): SendMessageResponse => SendMessageResponse.decode(value),
+  },
+  /** Messages of the active branch, oldest first */
+  getHistory: {
+    path: "/videoanalyzer.chat.ChatService/GetHistory",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: GetHistoryRequest): Buffer => Buffer.from(GetHistoryRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): GetHistoryRequest => GetHistoryRequest.decode(value),
+    responseSerialize: (value: GetHistoryResponse): Buffer => Buffer.from(GetHistoryResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): GetHistoryResponse => GetHistoryResponse.decode(value),
+  },
+  streamResponses: {
+    path: "/videoanalyzer.chat.ChatService/StreamResponses",
+    requestStream: false,
+    responseStream: true,
+    requestSerialize: (value: SendMessageRequest): Buffer => Buffer.from(SendMessageRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): SendMessageRequest => SendMessageRequest.decode(value),
+    responseSerialize: (value: StreamResponse): Buffer => Buffer.from(StreamResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): StreamResponse => StreamResponse.decode(value),
+  },
+  /** Conversations with message counts and a preview of the last message */
+  listConversations: {
+    path: "/videoanalyzer.chat.ChatService/ListConversations",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: ListConversationsRequest): Buffer =>
+      Buffer.from(ListConversationsRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): ListConversationsRequest => ListConversationsRequest.decode(value),
+    responseSerialize: (value: ListConversationsResponse): Buffer =>
+      Buffer.from(ListConversationsResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): ListConversationsResponse => ListConversationsResponse.decode(value),
+  },
+  renameConversation: {
+    path: "/videoanalyzer.chat.ChatService/RenameConversation",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: RenameConversationRequest): Buffer =>
+      Buffer.from(RenameConversationRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): RenameConversationRequest => RenameConversationRequest.decode(value),
+    responseSerialize: (value: Conversation): Buffer => Buffer.from(Conversation.encode(value).finish()),
+    responseDeserialize: (value: Buffer): Conversation => Conversation.decode(value),
+  },
+  /** Hide a conversation from ListConversations without deleting it */
+  archiveConversation: {
+    path: "/videoanalyzer.chat.ChatService/ArchiveConversation",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: ArchiveConversationRequest): Buffer =>
+      Buffer.from(ArchiveConversationRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): ArchiveConversationRequest => ArchiveConversationRequest.decode(value),
+    responseSerialize: (value: Conversation): Buffer => Buffer.from(Conversation.encode(value).finish()),
+    responseDeserialize: (value: Buffer): Conversation => Conversation.decode(value),
+  },
+  /** Delete a conversation with its messages and attachment rows */
+  deleteConversation: {
+    path: "/videoanalyzer.chat.ChatService/DeleteConversation",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: DeleteConversationRequest): Buffer =>
+      Buffer.from(DeleteConversationRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): DeleteConversationRequest => DeleteConversationRequest.decode(value),
+    responseSerialize: (value: DeleteConversationResponse): Buffer =>
+      Buffer.from(DeleteConversationResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): DeleteConversationResponse => DeleteConversationResponse.decode(value),
+  },
+  /** Full-text search over message text */
+  searchMessages: {
+    path: "/videoanalyzer.chat.ChatService/SearchMessages",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: SearchMessagesRequest): Buffer =>
+      Buffer.from(SearchMessagesRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): SearchMessagesRequest => SearchMessagesRequest.decode(value),
+    responseSerialize: (value: SearchMessagesResponse): Buffer =>
+      Buffer.from(SearchMessagesResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): SearchMessagesResponse => SearchMessagesResponse.decode(value),
+  },
+  /** Make the branch through message_id active; returns the new active branch */
+  switchBranch: {
+    path: "/videoanalyzer.chat.ChatService/SwitchBranch",
+    requestStream: false,
+    responseStream: false,
+    requestSerialize: (value: SwitchBranchRequest): Buffer => Buffer.from(SwitchBranchRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): SwitchBranchRequest => SwitchBranchRequest.decode(value),
+    responseSerialize: (value: GetHistoryResponse): Buffer => Buffer.from(GetHistoryResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): GetHistoryResponse => GetHistoryResponse.decode(value),
+  },
+  /** Answer a user message again, storing the reply as another version of the previous one */
+  regenerate: {
+    path: "/videoanalyzer.chat.ChatService/Regenerate",
+    requestStream: false,
+    responseStream: true,
+    requestSerialize: (value: RegenerateRequest): Buffer => Buffer.from(RegenerateRequest.encode(value).finish()),
+    requestDeserialize: (value: Buffer): RegenerateRequest => RegenerateRequest.decode(value),
+    responseSerialize: (value: StreamResponse): Buffer => Buffer.from(StreamResponse.encode(value).finish()),
+    responseDeserialize: (value: Buffer): StreamResponse => StreamResponse.decode(value),
+  },
+} as const;
+
+export interface ChatServiceServer extends UntypedServiceImplementation {
+  sendMessage: handleUnaryCall<SendMessageRequest, SendMessageResponse>;
+  /** Messages of the active branch, oldest first */
+  getHistory: handleUnaryCall<GetHistoryRequest, GetHistoryResponse>;
+  streamResponses: handleServerStreamingCall<SendMessageRequest, StreamResponse>;
+  /** Conversations with message counts and a preview of the last message */
+  listConversations: handleUnaryCall<ListConversationsRequest, ListConversationsResponse>;
+  renameConversation: handleUnaryCall<RenameConversationRequest, Conversation>;
+  /** Hide a conversation from ListConversations without deleting it */
+  archiveConversation: handleUnaryCall<ArchiveConversationRequest, Conversation>;
+  /** Delete a conversation with its messages and attachment rows */
+  deleteConversation: handleUnaryCall<DeleteConversationRequest, DeleteConversationResponse>;
+  /** Full-text search over message text */
+  searchMessages: handleUnaryCall<SearchMessagesRequest, SearchMessagesResponse>;
+  /** Make the branch through message_id active; returns the new active branch */
+  switchBranch: handleUnaryCall<SwitchBranchRequest, GetHistoryResponse>;
+  /** Answer a user message again, storing the reply as another version of the previous one */
+  regenerate: handleServerStreamingCall<RegenerateRequest, StreamResponse>;
+}
+
+export interface ChatServiceClient extends Client {
+  sendMessage(
+    request: SendMessageRequest,
+    callback: (error: ServiceError | null, response: SendMessageResponse) => void,
+  ): ClientUnaryCall;
   sendMessage(
-    request: ChatMessage,
+    request: SendMessageRequest,
     metadata: Metadata,
-    callback: (error: ServiceError | null, response: ChatAck) => void,
+    callback: (error: ServiceError | null, response: SendMessageResponse) => void,
   ): ClientUnaryCall;
   sendMessage(
-    request: ChatMessage,
+    request: SendMessageRequest,
     metadata: Metadata,
     options: Partial<CallOptions>,
-    callback: (error: ServiceError | null, response: ChatAck) => void,
+    callback: (error: ServiceError | null, response: SendMessageResponse) => void,
   ): ClientUnaryCall;
-  /** Retrieve history (pagination) */
+  /** Messages of the active branch, oldest first */
   getHistory(
-    request: HistoryRequest,
-    callback: (error: ServiceError | null, response: HistoryResponse) => void,
+    request: GetHistoryRequest,
+    callback: (error: ServiceError | null, response: GetHistoryResponse) => void,
   ): ClientUnaryCall;
   getHistory(
-    request: HistoryRequest,
+    request: GetHistoryRequest,
     metadata: Metadata,
-    callback: (error: ServiceError | null, response: HistoryResponse) => void,
+    callback: (error: ServiceError | null, response: GetHistoryResponse) => void,
   ): ClientUnaryCall;
   getHistory(
-    request: HistoryRequest,
+    request: GetHistoryRequest,
+    metadata: Metadata,
+    options: Partial<CallOptions>,
+    callback: (error: ServiceError | null, response: GetHistoryResponse) => void,
+  ): ClientUnaryCall;
+  streamResponses(request: SendMessageRequest, options?: Partial<CallOptions>): ClientReadableStream<StreamResponse>;
+  streamResponses(
+    request: SendMessageRequest,
+    metadata?: Metadata,
+    options?: Partial<CallOptions>,
+  ): ClientReadableStream<StreamResponse>;
+  /** Conversations with message counts and a preview of the last message */
+  listConversations(
+    request: ListConversationsRequest,
+    callback: (error: ServiceError | null, response: ListConversationsResponse) => void,
+  ): ClientUnaryCall;
+  listConversations(
+    request: ListConversationsRequest,
+    metadata: Metadata,
+    callback: (error: ServiceError | null, response: ListConversationsResponse) => void,
+  ): ClientUnaryCall;
+  listConversations(
+    request: ListConversationsRequest,
+    metadata: Metadata,
+    options: Partial<CallOptions>,
+    callback: (error: ServiceError | null, response: ListConversationsResponse) => void,
+  ): ClientUnaryCall;
+  renameConversation(
+    request: RenameConversationRequest,
+    callback: (error: ServiceError | null, response: Conversation) => void,
+  ): ClientUnaryCall;
+  renameConversation(
+    request: RenameConversationRequest,
+    metadata: Metadata,
+    callback: (error: ServiceError | null, response: Conversation) => void,
+  ): ClientUnaryCall;
+  renameConversation(
+    request: RenameConversationRequest,
+    metadata: Metadata,
+    options: Partial<CallOptions>,
+    callback: (error: ServiceError | null, response: Conversation) => void,
+  ): ClientUnaryCall;
+  /** Hide a conversation from ListConversations without deleting it */
+  archiveConversation(
+    request: ArchiveConversationRequest,
+    callback: (error: ServiceError | null, response: Conversation) => void,
+  ): ClientUnaryCall;
+  archiveConversation(
+    request: ArchiveConversationRequest,
+    metadata: Metadata,
+    callback: (error: ServiceError | null, response: Conversation) => void,
+  ): ClientUnaryCall;
+  archiveConversation(
+    request: ArchiveConversationRequest,
+    metadata: Metadata,
+    options: Partial<CallOptions>,
+    callback: (error: ServiceError | null, response: Conversation) => void,
+  ): ClientUnaryCall;
+  /** Delete a conversation with its messages and attachment rows */
+  deleteConversation(
+    request: DeleteConversationRequest,
+    callback: (error: ServiceError | null, response: DeleteConversationResponse) => void,
+  ): ClientUnaryCall;
+  deleteConversation(
+    request: DeleteConversationRequest,
+    metadata: Metadata,
+    callback: (error: ServiceError | null, response: DeleteConversationResponse) => void,
+  ): ClientUnaryCall;
+  deleteConversation(
+    request: DeleteConversationRequest,
+    metadata: Metadata,
+    options: Partial<CallOptions>,
+    callback: (error: ServiceError | null, response: DeleteConversationResponse) => void,
+  ): ClientUnaryCall;
+  /** Full-text search over message text */
+  searchMessages(
+    request: SearchMessagesRequest,
+    callback: (error: ServiceError | null, response: SearchMessagesResponse) => void,
+  ): ClientUnaryCall;
+  searchMessages(
+    request: SearchMessagesRequest,
+    metadata: Metadata,
+    callback: (error: ServiceError | null, response: SearchMessagesResponse) => void,
+  ): ClientUnaryCall;
+  searchMessages(
+    request: SearchMessagesRequest,
+    metadata: Metadata,
+    options: Partial<CallOptions>,
+    callback: (error: ServiceError | null, response: SearchMessagesResponse) => void,
+  ): ClientUnaryCall;
+  /** Make the branch through message_id active; returns the new active branch */
+  switchBranch(
+    request: SwitchBranchRequest,
+    callback: (error: ServiceError | null, response: GetHistoryResponse) => void,
+  ): ClientUnaryCall;
+  switchBranch(
+    request: SwitchBranchRequest,
+    metadata: Metadata,
+    callback: (error: ServiceError | null, response: GetHistoryResponse) => void,
+  ): ClientUnaryCall;
+  switchBranch(
+    request: SwitchBranchRequest,
     metadata: Metadata,
     options: Partial<CallOptions>,
-    callback: (error: ServiceError | null, response: HistoryResponse) => void,
+    callback: (error: ServiceError | null, response: GetHistoryResponse) => void,
   ): ClientUnaryCall;
+  /** Answer a user message again, storing the reply as another version of the previous one */
+  regenerate(request: RegenerateRequest, options?: Partial<CallOptions>): ClientReadableStream<StreamResponse>;
+  regenerate(
+    request: RegenerateRequest,
+    metadata?: Metadata,
+    options?: Partial<CallOptions>,
+  ): ClientReadableStream<StreamResponse>;
 }
 
 export const ChatServiceClient = makeGenericClientConstructor(
   ChatServiceService,
-  "genai.chat.ChatService",
+  "videoanalyzer.chat.ChatService",
 ) as unknown as {
   new (address: string, credentials: ChannelCredentials, options?: Partial<ClientOptions>): ChatServiceClient;
   service: typeof ChatServiceService;
@@ -760,26 +2272,15 @@ type KeysOfUnion<T> = T extends T ? keyof T : never;
 export type Exact<P, I extends P> = P extends Builtin ? P
   : P & { [K in keyof P]: Exact<P[K], I[K]> } & { [K in Exclude<keyof I, KeysOfUnion<P>>]: never };
 
-function toTimestamp(date: Date): Timestamp {
-  const seconds = Math.trunc(date.getTime() / 1_000);
-  const nanos = (date.getTime() % 1_000) * 1_000_000;
-  return { seconds, nanos };
-}
-
-function fromTimestamp(t: Timestamp): Date {
-  let millis = (t.seconds || 0) * 1_000;
-  millis += (t.nanos || 0) / 1_000_000;
-  return new globalThis.Date(millis);
-}
-
-function fromJsonTimestamp(o: any): Date {
-  if (o instanceof globalThis.Date) {
-    return o;
-  } else if (typeof o === "string") {
-    return new globalThis.Date(o);
-  } else {
-    return fromTimestamp(Timestamp.fromJSON(o));
+function longToNumber(int64: { toString(): string }): number {
+  const num = globalThis.Number(int64.toString());
+  if (num > globalThis.Number.MAX_SAFE_INTEGER) {
+    throw new globalThis.Error("Value is larger than Number.MAX_SAFE_INTEGER");
+  }
+  if (num < globalThis.Number.MIN_SAFE_INTEGER) {
+    throw new globalThis.Error("Value is smaller than Number.MIN_SAFE_INTEGER");
   }
+  return num;
 }
 
 function isSet(value: any): boolean {
@@ -61,6 +61,46 @@ export function agentTypeToJSON(object: AgentType): string {
   }
 }
 
+export enum AgentHealth {
+  AGENT_HEALTH_UNKNOWN = 0,
+  AGENT_HEALTHY = 1,
+  /** AGENT_STALE - missed heartbeats; no new tasks are routed to it */
+  AGENT_STALE = 2,
+  UNRECOGNIZED = -1,
+}
+
+export function agentHealthFromJSON(object: any): AgentHealth {
+  switch (object) {
+    case 0:
+    case "AGENT_HEALTH_UNKNOWN":
+      return AgentHealth.AGENT_HEALTH_UNKNOWN;
+    case 1:
+    case "AGENT_HEALTHY":
+      return AgentHealth.AGENT_HEALTHY;
+    case 2:
+    case "AGENT_STALE":
+      return AgentHealth.AGENT_STALE;
+    case -1:
+    case "UNRECOGNIZED":
+    default:
+      return AgentHealth.UNRECOGNIZED;
+  }
+}
+
+export function agentHealthToJSON(object: AgentHealth): string {
+  switch (object) {
+    case AgentHealth.AGENT_HEALTH_UNKNOWN:
+      return "AGENT_HEALTH_UNKNOWN";
+    case AgentHealth.AGENT_HEALTHY:
+      return "AGENT_HEALTHY";
+    case AgentHealth.AGENT_STALE:
+      return "AGENT_STALE";
+    case AgentHealth.UNRECOGNIZED:
+    default:
+      return "UNRECOGNIZED";
+  }
+}
+
 export enum TaskStatus {
   TASK_UNKNOWN = 0,
   TASK_QUEUED = 1,
@@ -127,6 +167,8 @@ export interface AgentInfo {
   /** comma-separated or JSON describing models supported */
   capabilities: string;
   lastSeen?: Date | undefined;
+  /** set by the manager; agents leave it unset */
+  health: AgentHealth;
 }
 
 export interface FileRef {
@@ -146,7 +188,11 @@ export interface TaskRequest {
     | undefined;
   /** optional params (model, thresholds) as JSON string */
   parametersJson: string;
-  createdAt?: Date | undefined;
+  createdAt?:
+    | Date
+    | undefined;
+  /** higher runs first; default 0 */
+  priority: number;
 }
 
 export interface ProgressUpdate {
@@ -173,7 +219,7 @@ export interface Empty {
 }
 
 function createBaseAgentInfo(): AgentInfo {
-  return { id: "", name: "", type: 0, version: "", capabilities: "", lastSeen: undefined };
+  return { id: "", name: "", type: 0, version: "", capabilities: "", lastSeen: undefined, health: 0 };
 }
 
 export const AgentInfo: MessageFns<AgentInfo> = {
@@ -196,6 +242,9 @@ export const AgentInfo: MessageFns<AgentInfo> = {
     if (message.lastSeen !== undefined) {
       Timestamp.encode(toTimestamp(message.lastSeen), writer.uint32(50).fork()).join();
     }
+    if (message.health !== 0) {
+      writer.uint32(56).int32(message.health);
+    }
     return writer;
   },
 
@@ -254,6 +303,14 @@ export const AgentInfo: MessageFns<AgentInfo> = {
           message.lastSeen = fromTimestamp(Timestamp.decode(reader, reader.uint32()));
           continue;
         }
+        case 7: {
+          if (tag !== 56) {
+            break;
+          }
+
+          message.health = reader.int32() as any;
+          continue;
+        }
       }
       if ((tag & 7) === 4 || tag === 0) {
         break;
@@ -271,6 +328,7 @@ export const AgentInfo: MessageFns<AgentInfo> = {
       version: isSet(object.version) ? globalThis.String(object.version) : "",
       capabilities: isSet(object.capabilities) ? globalThis.String(object.capabilities) : "",
       lastSeen: isSet(object.lastSeen) ? fromJsonTimestamp(object.lastSeen) : undefined,
+      health: isSet(object.health) ? agentHealthFromJSON(object.health) : 0,
     };
   },
 
@@ -294,6 +352,9 @@ export const AgentInfo: MessageFns<AgentInfo> = {
     if (message.lastSeen !== undefined) {
       obj.lastSeen = message.lastSeen.toISOString();
     }
+    if (message.health !== 0) {
+      obj.health = agentHealthToJSON(message.health);
+    }
     return obj;
   },
 
@@ -308,6 +369,7 @@ export const AgentInfo: MessageFns<AgentInfo> = {
     message.version = object.version ?? "";
     message.capabilities = object.capabilities ?? "";
     message.lastSeen = object.lastSeen ?? undefined;
+    message.health = object.health ?? 0;
     return message;
   },
 };
@@ -405,7 +467,7 @@ export const FileRef: MessageFns<FileRef> = {
 };
 
 function createBaseTaskRequest(): TaskRequest {
-  return { taskId: "", agentTypeHint: "", input: undefined, parametersJson: "", createdAt: undefined };
+  return { taskId: "", agentTypeHint: "", input: undefined, parametersJson: "", createdAt: undefined, priority: 0 };
 }
 
 export const TaskRequest: MessageFns<TaskRequest> = {
@@ -425,6 +487,9 @@ export const TaskRequest: MessageFns<TaskRequest> = {
     if (message.createdAt !== undefined) {
       Timestamp.encode(toTimestamp(message.createdAt), writer.uint32(42).fork()).join();
     }
+    if (message.priority !== 0) {
+      writer.uint32(48).int32(message.priority);
+    }
     return writer;
   },
 
@@ -475,6 +540,14 @@ export const TaskRequest: MessageFns<TaskRequest> = {
           message.createdAt = fromTimestamp(Timestamp.decode(reader, reader.uint32()));
           continue;
         }
+        case 6: {
+          if (tag !== 48) {
+            break;
+          }
+
+          message.priority = reader.int32();
+          continue;
+        }
       }
       if ((tag & 7) === 4 || tag === 0) {
         break;
@@ -491,6 +564,7 @@ export const TaskRequest: MessageFns<TaskRequest> = {
       input: isSet(object.input) ? FileRef.fromJSON(object.input) : undefined,
       parametersJson: isSet(object.parametersJson) ? globalThis.String(object.parametersJson) : "",
       createdAt: isSet(object.createdAt) ? fromJsonTimestamp(object.createdAt) : undefined,
+      priority: isSet(object.priority) ? globalThis.Number(object.priority) : 0,
     };
   },
 
@@ -511,6 +585,9 @@ export const TaskRequest: MessageFns<TaskRequest> = {
     if (message.createdAt !== undefined) {
       obj.createdAt = message.createdAt.toISOString();
     }
+    if (message.priority !== 0) {
+      obj.priority = Math.round(message.priority);
+    }
     return obj;
   },
 
@@ -526,6 +603,7 @@ export const TaskRequest: MessageFns<TaskRequest> = {
       : undefined;
     message.parametersJson = object.parametersJson ?? "";
     message.createdAt = object.createdAt ?? undefined;
+    message.priority = object.priority ?? 0;
     return message;
   },
 };